@@ -0,0 +1,155 @@
+use crate::sim::movement::MovementMode;
+
+/// Options given on the command line
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CliArgs {
+    /// `--seed <n>`: fixes the seed of every run
+    pub seed: Option<u64>,
+    /// `--replay <path>`: plays back a recorded run instead of taking player input
+    pub replay: Option<String>,
+    /// `--map <path>`: opens this map at startup instead of an empty grid
+    pub map: Option<String>,
+    /// `--play`: starts in Running mode rather than the editor
+    pub play: bool,
+    /// `--headless`: runs the simulation without a window (see `tools::run_headless`)
+    pub headless: bool,
+    /// `--validate <path>`: lints a map and exits with a status code
+    pub validate: Option<String>,
+    /// `--generate <path>`: generates a zone from the seed and writes it to this path, then exits
+    pub generate: Option<String>,
+    /// `--gen-params <path>`: JSON `ZoneGenParams` for `--generate` (defaults otherwise)
+    pub gen_params: Option<String>,
+    /// `--movement <cardinal|diagonal>`: movement mode for maps that don't set one
+    pub movement: Option<MovementMode>,
+}
+
+impl CliArgs {
+    /// Parses `--flag <value>` or `--flag=<value>` options and `--switch` flags; anything else is an error
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+        let mut cli = CliArgs::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg, None),
+            };
+
+            match flag.as_str() {
+                "--seed" => {
+                    let value = flag_value(&flag, inline_value, &mut args)?;
+                    let seed = value
+                        .parse()
+                        .map_err(|_| format!("Invalid seed '{}': expected an unsigned integer", value))?;
+                    cli.seed = Some(seed);
+                }
+                "--replay" => {
+                    cli.replay = Some(flag_value(&flag, inline_value, &mut args)?);
+                }
+                "--map" => {
+                    cli.map = Some(flag_value(&flag, inline_value, &mut args)?);
+                }
+                "--validate" => {
+                    cli.validate = Some(flag_value(&flag, inline_value, &mut args)?);
+                }
+                "--generate" => {
+                    cli.generate = Some(flag_value(&flag, inline_value, &mut args)?);
+                }
+                "--gen-params" => {
+                    cli.gen_params = Some(flag_value(&flag, inline_value, &mut args)?);
+                }
+                "--movement" => {
+                    let value = flag_value(&flag, inline_value, &mut args)?;
+                    cli.movement = Some(MovementMode::parse(&value)?);
+                }
+                "--play" => {
+                    no_value(&flag, inline_value)?;
+                    cli.play = true;
+                }
+                "--headless" => {
+                    no_value(&flag, inline_value)?;
+                    cli.headless = true;
+                }
+                _ => return Err(format!("Unknown option '{}'", flag)),
+            }
+        }
+
+        Ok(cli)
+    }
+}
+
+fn flag_value(
+    flag: &str,
+    inline_value: Option<String>,
+    args: &mut impl Iterator<Item = String>,
+) -> Result<String, String> {
+    inline_value
+        .or_else(|| args.next())
+        .ok_or_else(|| format!("{} requires a value", flag))
+}
+
+fn no_value(flag: &str, inline_value: Option<String>) -> Result<(), String> {
+    match inline_value {
+        Some(value) => Err(format!("{} doesn't take a value (got '{}')", flag, value)),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(list: &[&str]) -> Result<CliArgs, String> {
+        CliArgs::parse(list.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn test_parse_seed() {
+        assert_eq!(parse(&[]).unwrap().seed, None);
+        assert_eq!(parse(&["--seed", "123"]).unwrap().seed, Some(123));
+        assert_eq!(parse(&["--seed=7"]).unwrap().seed, Some(7));
+        assert!(parse(&["--seed"]).is_err());
+        assert!(parse(&["--seed", "abc"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_replay() {
+        let cli = parse(&["--replay", "replays/last_run.json", "--seed=3"]).unwrap();
+        assert_eq!(cli.replay.as_deref(), Some("replays/last_run.json"));
+        assert_eq!(cli.seed, Some(3));
+        assert!(parse(&["--replay"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_launch_options() {
+        let cli = parse(&["--map", "assets/maps/cellar.json", "--play", "--headless"]).unwrap();
+        assert_eq!(cli.map.as_deref(), Some("assets/maps/cellar.json"));
+        assert!(cli.play && cli.headless);
+        assert_eq!(parse(&["--validate=assets/maps/current.json"]).unwrap().validate.as_deref(), Some("assets/maps/current.json"));
+        assert!(!parse(&[]).unwrap().play);
+        assert!(parse(&["--play=yes"]).is_err());
+        assert!(parse(&["--validate"]).is_err());
+        assert_eq!(parse(&["--sed", "5"]).unwrap_err(), "Unknown option '--sed'");
+        assert_eq!(parse(&["--valdate=map.json"]).unwrap_err(), "Unknown option '--valdate'");
+        assert!(parse(&["map.json"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_generate() {
+        let cli = parse(&["--generate", "assets/maps/cave.json", "--gen-params=cave.json", "--seed", "9"]).unwrap();
+        assert_eq!(cli.generate.as_deref(), Some("assets/maps/cave.json"));
+        assert_eq!(cli.gen_params.as_deref(), Some("cave.json"));
+        assert_eq!(cli.seed, Some(9));
+        assert!(parse(&["--generate"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_movement() {
+        assert_eq!(parse(&[]).unwrap().movement, None);
+        assert_eq!(parse(&["--movement", "diagonal"]).unwrap().movement, Some(MovementMode::Diagonal));
+        assert_eq!(parse(&["--movement=8"]).unwrap().movement, Some(MovementMode::Diagonal));
+        assert_eq!(parse(&["--movement", "Cardinal"]).unwrap().movement, Some(MovementMode::Cardinal));
+        assert!(parse(&["--movement", "hex"]).is_err());
+        assert!(parse(&["--movement"]).is_err());
+    }
+}
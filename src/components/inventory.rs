@@ -1,8 +1,9 @@
 use bevy::prelude::*;
-use crate::components::item::Item;
+use crate::components::item::{add_to_stacks, take_from_stacks, Item};
+use crate::resources::item_registry::{ItemRegistry, METAL_DETECTOR};
 
 /// Component attached to player representing their carried items
-#[derive(Component, Debug, Default, Clone)]
+#[derive(Component, Debug, Default, Clone, PartialEq)]
 pub struct Inventory {
     pub items: Vec<Item>,
 }
@@ -12,37 +13,45 @@ impl Inventory {
         Self { items: Vec::new() }
     }
 
-    pub fn add_item(&mut self, item: Item) {
-        self.items.push(item);
+    pub fn add_item(&mut self, item: Item, registry: &ItemRegistry) {
+        add_to_stacks(&mut self.items, item, registry);
     }
 
-    pub fn remove_item(&mut self, index: usize) -> Option<Item> {
-        if index < self.items.len() {
-            Some(self.items.remove(index))
-        } else {
-            None
-        }
+    /// Takes `quantity` units (or what there is) from the stack at `index`
+    pub fn remove_item(&mut self, index: usize, quantity: u32) -> Option<Item> {
+        take_from_stacks(&mut self.items, index, quantity)
     }
 
     pub fn total_weight(&self) -> u32 {
-        self.items.iter().map(|item| item.weight).sum()
+        self.items.iter().map(Item::total_weight).sum()
     }
 
     pub fn is_empty(&self) -> bool {
         self.items.is_empty()
     }
 
+    /// Number of stacks carried
     pub fn count(&self) -> usize {
         self.items.len()
     }
 
+    /// Index of the first carried item of a kind
+    pub fn find(&self, id: &str) -> Option<usize> {
+        self.items.iter().position(|item| item.id == id)
+    }
+
+    /// How many units of a kind are carried, across stacks
+    pub fn count_of(&self, id: &str) -> u32 {
+        self.items.iter().filter(|item| item.id == id).map(|item| item.quantity).sum()
+    }
+
     pub fn has_metal_detector(&self) -> bool {
-        self.items.iter().any(|item| item.name == "Metal Detector")
+        self.find(METAL_DETECTOR).is_some()
     }
 }
 
 /// Resource defining carry capacity limits
-#[derive(Resource, Debug)]
+#[derive(Resource, Debug, Clone, Copy)]
 pub struct CarryCapacity {
     pub normal: u32,
     pub in_gravity: u32,
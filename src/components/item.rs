@@ -1,44 +1,126 @@
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
-use crate::resources::game_grid::ItemType;
+use crate::resources::item_registry::ItemRegistry;
 
-/// Represents a single item with its properties
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Stable identifier of an item kind, the key of its catalogue entry
+/// Game rules match on this, never on the display name
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ItemId(pub String);
+
+impl ItemId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<&str> for ItemId {
+    fn from(id: &str) -> Self {
+        Self(id.to_string())
+    }
+}
+
+impl From<String> for ItemId {
+    fn from(id: String) -> Self {
+        Self(id)
+    }
+}
+
+impl PartialEq<&str> for ItemId {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl std::fmt::Display for ItemId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+fn one() -> u32 {
+    1
+}
+
+/// A stack of items: its kind plus the properties of this particular instance
+/// Properties start out as the catalogue's but belong to the instance, so saved items keep theirs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Item {
-    pub name: String,
-    pub weight: u32,
+    #[serde(default)]
+    pub id: ItemId,          // Kind of item (catalogue key); empty in files saved before items had ids
+    pub name: String,        // Display name, for the player only
+    pub weight: u32,         // Weight of one unit
     pub value: Option<u32>, // Some items have no value (tools, bolts, etc.)
     pub is_metal: bool,      // Whether the item is metal (for detector and Rust anomaly)
+    #[serde(default = "one")]
+    pub quantity: u32,       // Units in this stack (always 1 for non-stackable items)
 }
 
 impl Item {
-    pub fn new(name: impl Into<String>, weight: u32, value: Option<u32>, is_metal: bool) -> Self {
+    pub fn new(id: impl Into<ItemId>, name: impl Into<String>, weight: u32, value: Option<u32>, is_metal: bool) -> Self {
         Self {
+            id: id.into(),
             name: name.into(),
             weight,
             value,
             is_metal,
+            quantity: 1,
         }
     }
-}
 
-impl From<ItemType> for Item {
-    fn from(item_type: ItemType) -> Self {
-        match item_type {
-            ItemType::FullyEmpty => Item::new("Fully Empty", 100, Some(200), false),
-            ItemType::Scrap => Item::new("Scrap", 10, Some(5), true),
-            ItemType::GlassJar => Item::new("Glass Jar", 5, Some(2), false),
-            ItemType::Battery => Item::new("Battery", 3, Some(3), false),
-            ItemType::Bolt => Item::new("Bolt", 1, Some(1), false),
-            ItemType::MetalDetector => Item::new("Metal Detector", 50, None, true),
-            ItemType::RustSlag => Item::new("Rust Slag", 5, Some(0), true),
+    /// Whether two stacks hold the same kind of unit and can be merged
+    /// Whether a kind stacks at all is up to its catalogue entry
+    pub fn stacks_with(&self, other: &Item, registry: &ItemRegistry) -> bool {
+        registry.get(self.id.as_str()).is_some_and(|definition| definition.stackable)
+            && self.id == other.id
+            && self.name == other.name
+            && self.weight == other.weight
+            && self.value == other.value
+            && self.is_metal == other.is_metal
+    }
+
+    pub fn total_weight(&self) -> u32 {
+        self.weight * self.quantity
+    }
+
+    /// Display name with the stack size, e.g. "Bolt x10"
+    pub fn label(&self) -> String {
+        if self.quantity > 1 {
+            format!("{} x{}", self.name, self.quantity)
+        } else {
+            self.name.clone()
         }
     }
 }
 
+/// Adds an item to a list, merging it into a matching stack if there is one
+pub fn add_to_stacks(items: &mut Vec<Item>, item: Item, registry: &ItemRegistry) {
+    match items.iter_mut().find(|stack| stack.stacks_with(&item, registry)) {
+        Some(stack) => stack.quantity += item.quantity,
+        None => items.push(item),
+    }
+}
+
+/// Takes up to `quantity` units off the stack at `index`; emptied stacks are removed
+pub fn take_from_stacks(items: &mut Vec<Item>, index: usize, quantity: u32) -> Option<Item> {
+    let stack = items.get_mut(index)?;
+    if quantity == 0 {
+        return None;
+    }
+
+    if quantity >= stack.quantity {
+        return Some(items.remove(index));
+    }
+
+    stack.quantity -= quantity;
+    let mut taken = stack.clone();
+    taken.quantity = quantity;
+    Some(taken)
+}
+
 /// Component attached to tile entities that have items on the ground
-/// Multiple items can exist on the same tile
-#[derive(Component, Debug, Default, Clone, Serialize, Deserialize)]
+/// Multiple items can exist on the same tile; identical stackable items share a stack
+#[derive(Component, Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GroundItems {
     pub items: Vec<Item>,
 }
@@ -48,23 +130,54 @@ impl GroundItems {
         Self { items: Vec::new() }
     }
 
-    pub fn add_item(&mut self, item: Item) {
-        self.items.push(item);
+    pub fn add_item(&mut self, item: Item, registry: &ItemRegistry) {
+        add_to_stacks(&mut self.items, item, registry);
     }
 
-    pub fn remove_item(&mut self, index: usize) -> Option<Item> {
-        if index < self.items.len() {
-            Some(self.items.remove(index))
-        } else {
-            None
-        }
+    /// Takes `quantity` units (or what there is) from the stack at `index`
+    pub fn remove_item(&mut self, index: usize, quantity: u32) -> Option<Item> {
+        take_from_stacks(&mut self.items, index, quantity)
     }
 
     pub fn is_empty(&self) -> bool {
         self.items.is_empty()
     }
 
+    /// Number of stacks on the tile
     pub fn count(&self) -> usize {
         self.items.len()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::item_registry::{ITEM_CATALOGUE_PATH, BOLT, METAL_DETECTOR};
+
+    #[test]
+    fn test_stackable_items_merge_and_split() {
+        let registry = ItemRegistry::load_from_file(ITEM_CATALOGUE_PATH).unwrap();
+        let mut ground = GroundItems::new();
+        for _ in 0..3 {
+            ground.add_item(registry.create(BOLT), &registry);
+        }
+        ground.add_item(registry.create(METAL_DETECTOR), &registry);
+        ground.add_item(registry.create(METAL_DETECTOR), &registry);
+        assert_eq!(ground.count(), 3);
+        assert_eq!(ground.items[0].quantity, 3);
+        assert_eq!(ground.items[0].total_weight(), 3);
+
+        // Stacking is the kind's: a bolt saved without any stacking flag still joins the stack
+        let saved: Item = serde_json::from_str(r#"{"id": "bolt", "name": "Bolt", "weight": 1, "value": 1, "is_metal": false}"#).unwrap();
+        ground.add_item(saved, &registry);
+        assert_eq!(ground.items[0].quantity, 4);
+
+        let taken = ground.remove_item(0, 3).unwrap();
+        assert_eq!(taken.quantity, 3);
+        assert_eq!(ground.items[0].quantity, 1);
+
+        // Taking more than the stack holds takes the whole stack
+        assert_eq!(ground.remove_item(0, 5).unwrap().quantity, 1);
+        assert_eq!(ground.count(), 2);
+    }
+}
@@ -1,3 +1,4 @@
+#[allow(clippy::module_inception)]
 pub mod components;
 pub mod item;
 pub mod inventory;
\ No newline at end of file
@@ -2,6 +2,7 @@
 pub const GRID_WIDTH: usize = 25;
 pub const GRID_HEIGHT: usize = 25;
 pub const TILE_SIZE: f32 = 32.0;
+pub const CHUNK_SIZE: usize = 32; // Tiles along each side of a terrain chunk, the unit maps are stored and drawn in
 pub const TICK_RATE_HZ: f64 = 2.0;
 
 // --- Window/Camera Constants ---
@@ -11,8 +12,42 @@ pub const DEFAULT_ZOOM: f32 = 1.0;
 pub const MIN_ZOOM: f32 = 0.1;
 pub const ZOOM_SPEED: f32 = 0.1;
 pub const CAMERA_PAN_SPEED: f32 = 400.0;
+pub const CHUNK_VIEW_MARGIN: usize = 1; // Chunks kept drawn beyond the edge of the camera view
 
 // --- World Generation Constants ---
-pub const WATER_LEVEL: f32 = 0.3; // Tiles below this are lakes
-pub const SCALE: f64 = 0.02;      // Controls how zoomed in/out the noise is
-    
\ No newline at end of file
+pub const WATER_LEVEL: f32 = 0.3; // Default share of a generated zone lying below the noise "water line" (walls)
+pub const SCALE: f64 = 0.12;      // Noise units per tile: smaller values give bigger caves and thicker walls
+pub const MAX_REACHABILITY_CHECKS: usize = 16; // Whole-zone exit checks per anomaly before doubtful spots are skipped
+
+// --- Gameplay Constants ---
+pub const GRAVITY_TIMER_TURNS: u32 = 5; // Turns a player survives inside a gravitational anomaly
+pub const MAX_THROW_RANGE: u32 = 5;     // Most tiles any thrown item travels (a bolt goes this far)
+pub const THROW_STRENGTH: u32 = 15;     // Range of a thrown item is this divided by its weight, at least 1 tile
+pub const HEAVY_THROW_WEIGHT: u32 = 10; // Thrown items this heavy are dragged in by a gravitational anomaly they fly past
+pub const METAL_DETECTOR_RANGE: i32 = 2; // Range of the Metal Detector in steps (metal items and anomalies), see MovementMode::distance
+
+// --- Fog of War Constants ---
+pub const SIGHT_RADIUS: i32 = 6;                // Tiles the player can see in an unobstructed line
+pub const REMEMBERED_TILE_BRIGHTNESS: f32 = 0.4; // Color multiplier for tiles seen earlier but out of sight now
+
+// --- Replay Constants ---
+pub const REPLAY_STEP_SECONDS: f32 = 0.25; // Delay between recorded actions during playback
+
+// --- Travel Constants ---
+pub const TRAVEL_STEP_SECONDS: f32 = 0.1; // Delay between the steps of a click-to-travel walk
+
+// --- Map Marker Constants ---
+pub const MARKER_NOTE_MAX_LENGTH: usize = 40; // Characters allowed in a free-text note
+
+// --- Map File Constants ---
+pub const MAPS_DIRECTORY: &str = "assets/maps"; // Where the editor saves maps and zone links look them up
+
+// --- Editor Constants ---
+pub const MAP_NAME_MAX_LENGTH: usize = 32; // Characters allowed in a map name typed into Save As
+pub const MIN_MAP_SIZE: usize = 5;         // Smallest width/height a new map can have
+pub const MAX_MAP_SIZE: usize = 1000;      // Largest width/height a new map can have
+pub const MAX_ISSUE_ROWS: usize = 12;      // Map issues listed in the editor panel before the rest are summarised
+pub const KEYBINDING_ROWS: usize = 15;     // Actions listed at once on the keybindings screen
+
+// --- Settings Constants ---
+pub const KEYBINDINGS_PATH: &str = "settings/keybindings.json"; // Keys rebound from the defaults, written by the keybindings screen
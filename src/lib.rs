@@ -0,0 +1,16 @@
+//! Picnic game library
+//!
+//! The game rules live in `sim` and have no dependency on a window or on the
+//! Bevy schedule, so they can be driven from tests, bots and tools.
+//! The Bevy systems in `systems` are thin adapters over the same rules.
+
+// Bevy systems routinely take many parameters and nested query filters
+#![allow(clippy::too_many_arguments, clippy::type_complexity)]
+
+pub mod cli;
+pub mod components;
+pub mod constants;
+pub mod resources;
+pub mod sim;
+pub mod systems;
+pub mod tools;
@@ -1,20 +1,29 @@
 use bevy::prelude::*;
 
-mod resources;
-mod systems;
-mod components;
-mod constants;
-
-use resources::{
+use picnic::resources::{
     game_state::GameState,
     camera::{CameraZoom, CameraPosition},
-    editor_state::{EditorState, EditorCursor},
+    editor_state::{EditorState, EditorCursor, EditorMapFile, MapDialog, MapSettings, StartupMap},
+    map_data::MapData,
+    edit_history::EditHistory,
+    map_validation::MapIssues,
     turn_state::{TurnPhase, TurnCounter},
     message_log::MessageLog,
     contract_system::ContractSystem,
+    seed::SeedOverride,
+    debug::DebugReveal,
+    map_markers::MapMarkers,
+    item_registry::{ItemRegistry, ITEM_CATALOGUE_PATH},
+    keybindings::Keybindings,
+    action_input::InputDevice,
 };
-use components::inventory::CarryCapacity;
-use systems::{
+use picnic::cli::CliArgs;
+use picnic::tools::{validate, generate, run_headless};
+use picnic::sim::replay::Replay;
+use picnic::sim::movement::MovementSetting;
+use picnic::components::inventory::CarryCapacity;
+use picnic::sim::zone_sim::ZoneSim;
+use picnic::systems::{
     setup::*,
     input::*,
     editor::*,
@@ -29,11 +38,101 @@ use systems::{
     metal_detector::*,
     contract_ui::*,
     bolt_throwing::*,
+    replay::*,
+    run_save::*,
+    fog::*,
+    anomaly_markers::*,
+    map_markers::*,
+    travel::*,
 };
-use constants::*;
+use picnic::constants::*;
 
 fn main() {
-    App::new()
+    let cli = match CliArgs::parse(std::env::args().skip(1)) {
+        Ok(cli) => cli,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+
+    let items = match ItemRegistry::load_from_file(ITEM_CATALOGUE_PATH) {
+        Ok(items) => items,
+        Err(e) => {
+            eprintln!("Failed to load item catalogue {}: {}", ITEM_CATALOGUE_PATH, e);
+            std::process::exit(2);
+        }
+    };
+
+    if let Some(path) = &cli.validate {
+        std::process::exit(validate(path, &items));
+    }
+    if let Some(path) = &cli.generate {
+        let seed = next_run_seed(&SeedOverride(cli.seed));
+        std::process::exit(generate(path, cli.gen_params.as_deref(), seed, &items));
+    }
+
+    let replay = match cli.replay.as_deref().map(|path| Replay::load_from_file(path, &items)) {
+        Some(Ok(replay)) => Some(replay),
+        Some(Err(e)) => {
+            eprintln!("Failed to load replay: {}", e);
+            std::process::exit(2);
+        }
+        None => None,
+    };
+
+    // Playing needs a map; without --map that's the default one
+    let needs_map = cli.play || cli.headless || replay.is_some();
+    let startup_map = match cli.map.clone().or_else(|| needs_map.then(|| MAP_FILE_PATH.to_string())) {
+        Some(path) => match MapData::load_from_file(&path, &items) {
+            Ok(map) => Some((path, map)),
+            Err(e) => {
+                eprintln!("Failed to load map {}: {}", path, e);
+                std::process::exit(2);
+            }
+        },
+        None => None,
+    };
+
+    if cli.headless {
+        let Some((path, map)) = &startup_map else {
+            unreachable!("--headless always loads a map");
+        };
+        let seed = replay.as_ref().map_or_else(|| next_run_seed(&SeedOverride(cli.seed)), |replay| replay.seed);
+        eprintln!("Running headless with seed {}", seed);
+
+        // The movement setting applies to maps that don't choose a mode
+        let movement = MovementSetting(cli.movement.unwrap_or_default());
+        match run_headless(map, path, items, seed, movement, replay.as_ref(), std::io::stdin().lock(), &mut std::io::stdout()) {
+            Ok(summary) => {
+                println!("{}", serde_json::to_string(&summary).unwrap_or_default());
+                std::process::exit(0);
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    // A broken keybindings file shouldn't keep the game from starting
+    let keybindings = Keybindings::load_or_default(KEYBINDINGS_PATH).unwrap_or_else(|e| {
+        eprintln!("Failed to load keybindings, using the defaults: {}", e);
+        Keybindings::default()
+    });
+    for conflict in keybindings.conflicts() {
+        eprintln!("Keybindings conflict: {}", conflict);
+    }
+
+    let mut app = App::new();
+    if let Some((path, map)) = startup_map {
+        app.insert_resource(StartupMap { path, map, play: cli.play || replay.is_some() });
+    }
+    if let Some(replay) = replay {
+        app.insert_resource(ReplayPlayback::new(replay));
+    }
+
+    app
         .add_plugins(DefaultPlugins.set(WindowPlugin {
             primary_window: Some(Window {
                 title: "Picnic".into(),
@@ -51,44 +150,73 @@ fn main() {
         .init_resource::<CameraPosition>()
         .init_resource::<EditorState>()
         .init_resource::<EditorCursor>()
+        .init_resource::<EditorMapFile>()
+        .init_resource::<EditHistory>()
+        .init_resource::<MapSettings>()
+        .init_resource::<MapIssues>()
         .init_resource::<TurnCounter>()
         .init_resource::<MessageLog>()
         .init_resource::<CarryCapacity>()
-        .init_resource::<ContractSystem>()
+        .insert_resource(ContractSystem::from_registry(&items))
         .init_resource::<AutoRestartFlag>()
+        .init_resource::<DebugReveal>()
+        .init_resource::<TileChunks>()
+        .init_resource::<MapMarkers>()
+        .insert_resource(SeedOverride(cli.seed))
+        .insert_resource(MovementSetting(cli.movement.unwrap_or_default()))
+        .insert_resource(items)
+        .insert_resource(keybindings)
+        .init_resource::<InputDevice>()
         .add_systems(
             Startup,
             (
                 setup_system,
                 setup_camera_system,
-                spawn_tile_sprites_system,
                 spawn_editor_hud_system,
+                load_startup_map_system,
             ).chain(),
         )
         .add_systems(OnEnter(GameState::Running), (
+            snapshot_authored_map_system,
             spawn_player_system,
+            start_zone_system,
             set_entering_zone_phase_system,
             spawn_game_hud_system,
             spawn_ground_item_sprites_system,
             spawn_metal_detector_indicator_system,
         ).chain())
+        .add_systems(OnExit(GameState::Editing), (
+            clear_tool_preview_system,
+            despawn_map_issues_panel_system,
+        ))
         .add_systems(OnExit(GameState::Running), (
             despawn_player_system,
             despawn_game_hud_system,
             despawn_ground_item_sprites_system,
             despawn_metal_detector_indicator_system,
+            save_replay_system,
+            clear_fog_system,
+            despawn_anomaly_labels_system,
+            despawn_path_markers_system,
+            store_map_markers_system.before(despawn_player_system),
             prepare_restart_system,
+            restore_authored_map_system
+                .after(despawn_ground_item_sprites_system)
+                .after(clear_fog_system),
         ))
         .add_systems(
             Update,
             (
-                // Always active
-                editor_toggle_system,
+                // Always active (F2 waits until a map dialog is closed)
+                editor_toggle_system
+                    .after(update_map_issues_system)
+                    .run_if(not(resource_exists::<MapDialog>)),
                 camera_zoom_system,
                 exit_on_escape_system,
+                detect_input_device_system,
+                update_confirm_prompts_system.after(detect_input_device_system),
                 toggle_editor_hud_visibility_system,
-                update_tile_sprite_system,
-                reload_tile_sprites_system,
+                update_tile_chunks_system,
                 update_entity_colors_system,
             ),
         )
@@ -97,7 +225,8 @@ fn main() {
             (
                 // Camera pan only in Editing mode
                 camera_pan_system,
-            ).run_if(in_state(GameState::Editing)),
+            ).run_if(in_state(GameState::Editing))
+             .run_if(not(resource_exists::<MapDialog>)),
         )
         .add_systems(
             Update,
@@ -111,19 +240,65 @@ fn main() {
                 update_ground_item_sprites_system,
                 update_metal_detector_system,
                 update_bolt_trail_system,
+                toggle_debug_reveal_system,
+                update_marker_sprites_system,
             ).run_if(in_state(GameState::Running)),
         )
+        .add_systems(
+            PostUpdate,
+            (
+                // Running mode - mirror the simulation's zone, then the player, ground item and zone entities, then apply fog of war
+                sync_grid_from_sim_system,
+                (
+                    sync_player_from_sim_system,
+                    sync_ground_items_from_sim_system,
+                    sync_entities_from_sim_system,
+                    sync_markers_zone_system,
+                ),
+                (
+                    update_fog_system,
+                    update_anomaly_markers_system,
+                ),
+            ).chain()
+             .run_if(in_state(GameState::Running))
+             .run_if(resource_exists::<ZoneSim>),
+        )
         .add_systems(
             Update,
             (
-                // PlayerTurn phase - handle movement input, item inspection, inventory, bolt throwing, and exit detection
-                player_movement_system,
-                detect_inspect_input_system,
-                detect_inventory_input_system,
-                detect_bolt_throw_input_system,
+                // PlayerTurn phase - handle movement input, click-to-travel, item inspection, inventory, bolt throwing and map markers
+                (
+                    player_movement_system,
+                    (travel_click_system, start_travel_system, auto_travel_system).chain(),
+                    detect_inspect_input_system,
+                    detect_inventory_input_system,
+                    detect_bolt_throw_input_system,
+                    detect_marking_input_system,
+                    save_run_system,
+                ).run_if(not(resource_exists::<ReplayPlayback>)),
                 detect_exit_system,
             ).run_if(in_state(GameState::Running))
-             .run_if(in_state(TurnPhase::PlayerTurn)),
+             .run_if(in_state(TurnPhase::PlayerTurn))
+             .run_if(resource_exists::<ZoneSim>),
+        )
+        .add_systems(
+            Last,
+            (
+                // Quitting mid-run keeps the run for next time
+                autosave_on_exit_system,
+            ).run_if(in_state(GameState::Running))
+             .run_if(resource_exists::<ZoneSim>)
+             .run_if(not(resource_exists::<ReplayPlayback>)),
+        )
+        .add_systems(
+            Update,
+            (
+                // Replay playback - recorded actions stand in for player input
+                replay_controls_system,
+                replay_playback_system.after(detect_exit_system),
+            ).run_if(in_state(GameState::Running))
+             .run_if(resource_exists::<ReplayPlayback>)
+             .run_if(resource_exists::<ZoneSim>),
         )
         .add_systems(
             Update,
@@ -138,14 +313,32 @@ fn main() {
                 transition_to_player_turn_system,
             ).chain()
              .run_if(in_state(GameState::Running))
-             .run_if(in_state(TurnPhase::WorldUpdate)),
+             .run_if(in_state(TurnPhase::WorldUpdate))
+             .run_if(resource_exists::<ZoneSim>),
         )
-        .add_systems(OnEnter(TurnPhase::ThrowingBolt), (
+        .add_systems(OnEnter(TurnPhase::Throwing), (
             spawn_bolt_indicator_system,
         ))
-        .add_systems(OnExit(TurnPhase::ThrowingBolt), (
+        .add_systems(OnExit(TurnPhase::Throwing), (
             despawn_bolt_indicator_system,
         ))
+        .add_systems(OnEnter(TurnPhase::PlacingMarkers), (
+            spawn_marking_ui_system,
+        ))
+        .add_systems(OnExit(TurnPhase::PlacingMarkers), (
+            despawn_marking_ui_system,
+        ))
+        .add_systems(
+            Update,
+            (
+                // PlacingMarkers phase - move the cursor, place markers and type notes
+                marking_input_system,
+                update_marking_ui_system,
+            ).chain()
+             .run_if(in_state(GameState::Running))
+             .run_if(in_state(TurnPhase::PlacingMarkers))
+             .run_if(resource_exists::<MarkingState>),
+        )
         .add_systems(OnEnter(TurnPhase::InspectingItems), (
             spawn_inspect_ui_system,
         ))
@@ -155,11 +348,12 @@ fn main() {
         .add_systems(
             Update,
             (
-                // ThrowingBolt phase - handle direction selection and bolt animation
+                // Throwing phase - handle direction selection and bolt animation
                 bolt_direction_input_system,
                 animate_bolt_flight_system,
             ).run_if(in_state(GameState::Running))
-             .run_if(in_state(TurnPhase::ThrowingBolt)),
+             .run_if(in_state(TurnPhase::Throwing))
+             .run_if(resource_exists::<ZoneSim>),
         )
         .add_systems(
             Update,
@@ -171,7 +365,8 @@ fn main() {
                 update_inspect_ui_selection_system,
                 rebuild_inspect_ui_system,
             ).run_if(in_state(GameState::Running))
-             .run_if(in_state(TurnPhase::InspectingItems)),
+             .run_if(in_state(TurnPhase::InspectingItems))
+             .run_if(resource_exists::<ZoneSim>),
         )
         .add_systems(OnEnter(TurnPhase::ViewingInventory), (
             spawn_inventory_ui_system,
@@ -186,11 +381,13 @@ fn main() {
                 close_inventory_ui_system,
                 inventory_navigation_system,
                 drop_item_system,
+                throw_item_select_system,
                 update_inventory_ui_selection_system,
                 rebuild_inventory_ui_system,
                 auto_scroll_inventory_system,
             ).run_if(in_state(GameState::Running))
-             .run_if(in_state(TurnPhase::ViewingInventory)),
+             .run_if(in_state(TurnPhase::ViewingInventory))
+             .run_if(resource_exists::<ZoneSim>),
         )
         .add_systems(OnEnter(TurnPhase::EnteringZone), (
             spawn_enter_zone_ui_system,
@@ -238,13 +435,32 @@ fn main() {
             Update,
             (
                 // Editor-only systems
-                editor_mode_toggle_system,
-                editor_selection_system,
+                (
+                    // Editing input pauses while a map dialog is open
+                    editor_mode_toggle_system,
+                    editor_selection_system,
+                    editor_tool_select_system,
+                    editor_placement_system,
+                    editor_tool_system,
+                    editor_undo_system,
+                    editor_save_load_system,
+                    editor_link_exit_system,
+                    editor_movement_mode_system,
+                    map_issue_click_system,
+                    resume_run_system,
+                ).run_if(not(resource_exists::<MapDialog>)),
+                map_dialog_input_system
+                    .after(exit_on_escape_system)
+                    .run_if(resource_exists::<MapDialog>),
                 editor_mouse_position_system,
                 editor_cursor_highlight_system,
-                editor_placement_system,
-                editor_save_load_system,
                 update_editor_hud_system,
+                update_editor_file_hud_system,
+                update_editor_tool_hud_system,
+                update_tool_preview_system,
+                update_map_issues_system,
+                update_map_issues_panel_system,
+                update_map_dialog_ui_system,
                 auto_restart_system,
             ).run_if(in_state(GameState::Editing)),
         )
@@ -0,0 +1,236 @@
+use bevy::prelude::*;
+use bevy::ecs::system::SystemParam;
+use crate::resources::keybindings::{InputAction, Keybindings};
+use crate::sim::movement::MovementMode;
+
+/// The device the player pressed something on last; on-screen prompts show its keys or buttons
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputDevice {
+    #[default]
+    Keyboard,
+    Gamepad,
+}
+
+/// The d-pad buttons and the step each one is, in the order of `InputAction::STEPS`
+const DPAD_STEPS: [(GamepadButton, (i32, i32)); 4] = [
+    (GamepadButton::DPadUp, (0, -1)),
+    (GamepadButton::DPadDown, (0, 1)),
+    (GamepadButton::DPadLeft, (-1, 0)),
+    (GamepadButton::DPadRight, (1, 0)),
+];
+
+/// Gamepad buttons of an action (Xbox layout: South is A, East is B)
+/// Only play and the modal screens have buttons; the editor, saving and quitting stay on the keyboard
+pub fn gamepad_buttons(action: InputAction) -> &'static [GamepadButton] {
+    use GamepadButton::*;
+    match action {
+        InputAction::MoveUp | InputAction::MenuUp => &[DPadUp],
+        InputAction::MoveDown | InputAction::MenuDown => &[DPadDown],
+        InputAction::MoveLeft | InputAction::AmountLess => &[DPadLeft],
+        InputAction::MoveRight | InputAction::AmountMore => &[DPadRight],
+        InputAction::Inspect | InputAction::Throw => &[West],
+        InputAction::Inventory => &[North],
+        InputAction::ThrowBolt => &[RightTrigger],
+        InputAction::Pickup | InputAction::Drop | InputAction::Confirm => &[South],
+        InputAction::Cancel => &[East],
+        _ => &[],
+    }
+}
+
+/// Name of a gamepad button for on-screen prompts
+pub fn button_label(button: GamepadButton) -> &'static str {
+    match button {
+        GamepadButton::South => "A",
+        GamepadButton::East => "B",
+        GamepadButton::West => "X",
+        GamepadButton::North => "Y",
+        GamepadButton::LeftTrigger => "LB",
+        GamepadButton::RightTrigger => "RB",
+        GamepadButton::LeftTrigger2 => "LT",
+        GamepadButton::RightTrigger2 => "RT",
+        GamepadButton::Select => "Back",
+        GamepadButton::Start => "Start",
+        GamepadButton::DPadUp => "D-pad Up",
+        GamepadButton::DPadDown => "D-pad Down",
+        GamepadButton::DPadLeft => "D-pad Left",
+        GamepadButton::DPadRight => "D-pad Right",
+        _ => "?",
+    }
+}
+
+/// Actions from the keyboard (as bound) and every connected gamepad, for systems that read play input
+#[derive(SystemParam)]
+pub struct ActionInput<'w, 's> {
+    keyboard: Res<'w, ButtonInput<KeyCode>>,
+    keybindings: Res<'w, Keybindings>,
+    device: Res<'w, InputDevice>,
+    gamepads: Query<'w, 's, &'static Gamepad>,
+}
+
+impl ActionInput<'_, '_> {
+    /// Whether one of the action's keys or buttons went down this frame
+    pub fn just_pressed(&self, action: InputAction) -> bool {
+        self.keybindings.just_pressed(&self.keyboard, action)
+            || self
+                .gamepads
+                .iter()
+                .any(|gamepad| gamepad.any_just_pressed(gamepad_buttons(action).iter().copied()))
+    }
+
+    /// Whether any key or button at all went down this frame
+    pub fn any_just_pressed(&self) -> bool {
+        self.keyboard.get_just_pressed().next().is_some()
+            || self.gamepads.iter().any(|gamepad| gamepad.get_just_pressed().next().is_some())
+    }
+
+    /// The step chosen this frame, if any: a movement key the mode allows, or the d-pad
+    /// On the d-pad a diagonal is two directions held together, taken when the second one goes down
+    pub fn step(&self, movement: MovementMode) -> Option<(i32, i32)> {
+        let key_step = InputAction::STEPS
+            .iter()
+            .map(|(action, step)| (*action, *step))
+            .find(|(action, (dx, dy))| movement.allows(*dx, *dy) && self.keybindings.just_pressed(&self.keyboard, *action));
+        if let Some((_, step)) = key_step {
+            return Some(step);
+        }
+
+        self.gamepads.iter().find_map(|gamepad| dpad_step(gamepad, movement))
+    }
+
+    /// The action's keys or buttons for on-screen hints, on the device in use
+    /// Actions without buttons show their keys
+    pub fn label(&self, action: InputAction) -> String {
+        let buttons = gamepad_buttons(action);
+        if *self.device == InputDevice::Gamepad && !buttons.is_empty() {
+            let labels: Vec<&str> = buttons.iter().map(|button| button_label(*button)).collect();
+            labels.join("/")
+        } else {
+            self.keybindings.label(action)
+        }
+    }
+}
+
+/// The step the d-pad of `gamepad` gives this frame
+fn dpad_step(gamepad: &Gamepad, movement: MovementMode) -> Option<(i32, i32)> {
+    let (_, pressed_step) = DPAD_STEPS.iter().find(|(button, _)| gamepad.just_pressed(*button))?;
+
+    let (dx, dy) = DPAD_STEPS
+        .iter()
+        .filter(|(button, _)| gamepad.pressed(*button))
+        .fold((0, 0), |(x, y), (_, (dx, dy))| (x + dx, y + dy));
+    if (dx, dy) != *pressed_step && movement.allows(dx, dy) {
+        return Some((dx, dy));
+    }
+    movement.allows(pressed_step.0, pressed_step.1).then_some(*pressed_step)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::input::{gamepad::{GamepadConnection, GamepadConnectionEvent, RawGamepadButtonChangedEvent, RawGamepadEvent}, InputPlugin};
+    use crate::resources::keybindings::InputContext;
+
+    /// What the test system read from `ActionInput` in the last frame
+    #[derive(Resource, Default)]
+    struct Seen {
+        pickup: bool,
+        cancel: bool,
+        step: Option<(i32, i32)>,
+        confirm_label: String,
+    }
+
+    fn read_input_system(input: ActionInput, mut seen: ResMut<Seen>) {
+        seen.pickup = input.just_pressed(InputAction::Pickup);
+        seen.cancel = input.just_pressed(InputAction::Cancel);
+        seen.step = input.step(MovementMode::Diagonal);
+        seen.confirm_label = input.label(InputAction::Confirm);
+    }
+
+    /// A headless app with the input plugin, the device tracking and one connected gamepad
+    fn app_with_gamepad() -> (App, Entity) {
+        let mut app = App::new();
+        app.add_plugins(InputPlugin)
+            .init_resource::<Keybindings>()
+            .init_resource::<InputDevice>()
+            .init_resource::<Seen>()
+            .add_systems(Update, (crate::systems::input::detect_input_device_system, read_input_system).chain());
+
+        let gamepad = app.world_mut().spawn_empty().id();
+        app.world_mut().send_event(GamepadConnectionEvent::new(
+            gamepad,
+            GamepadConnection::Connected { name: "Test gamepad".to_string(), vendor_id: None, product_id: None },
+        ));
+        app.update();
+        (app, gamepad)
+    }
+
+    fn press(app: &mut App, gamepad: Entity, buttons: &[(GamepadButton, f32)]) {
+        for (button, value) in buttons {
+            app.world_mut()
+                .send_event(RawGamepadEvent::Button(RawGamepadButtonChangedEvent::new(gamepad, *button, *value)));
+        }
+        app.update();
+    }
+
+    #[test]
+    fn test_gamepad_buttons_trigger_actions_and_switch_prompts() {
+        let (mut app, gamepad) = app_with_gamepad();
+        assert_eq!(app.world().resource::<Seen>().confirm_label, "E");
+
+        press(&mut app, gamepad, &[(GamepadButton::South, 1.0)]);
+        let seen = app.world().resource::<Seen>();
+        assert!(seen.pickup && !seen.cancel);
+        assert_eq!(seen.confirm_label, "A");
+        assert_eq!(*app.world().resource::<InputDevice>(), InputDevice::Gamepad);
+
+        // Held, not pressed again
+        press(&mut app, gamepad, &[]);
+        assert!(!app.world().resource::<Seen>().pickup);
+
+        press(&mut app, gamepad, &[(GamepadButton::South, 0.0), (GamepadButton::East, 1.0)]);
+        assert!(app.world().resource::<Seen>().cancel);
+    }
+
+    #[test]
+    fn test_dpad_steps_and_diagonals() {
+        let (mut app, gamepad) = app_with_gamepad();
+
+        press(&mut app, gamepad, &[(GamepadButton::DPadUp, 1.0)]);
+        assert_eq!(app.world().resource::<Seen>().step, Some((0, -1)));
+
+        // Up still held when Right goes down: up-right
+        press(&mut app, gamepad, &[(GamepadButton::DPadRight, 1.0)]);
+        assert_eq!(app.world().resource::<Seen>().step, Some((1, -1)));
+
+        press(&mut app, gamepad, &[]);
+        assert_eq!(app.world().resource::<Seen>().step, None);
+    }
+
+    #[test]
+    fn test_dpad_diagonal_in_cardinal_mode_takes_one_direction() {
+        let (mut app, gamepad) = app_with_gamepad();
+        press(&mut app, gamepad, &[(GamepadButton::DPadUp, 1.0), (GamepadButton::DPadRight, 1.0)]);
+
+        let mut gamepads = app.world_mut().query::<&Gamepad>();
+        let gamepad = gamepads.single(app.world()).unwrap();
+        assert_eq!(dpad_step(gamepad, MovementMode::Cardinal), Some((0, -1)));
+        assert_eq!(dpad_step(gamepad, MovementMode::Diagonal), Some((1, -1)));
+    }
+
+    #[test]
+    fn test_default_buttons_have_no_conflicts() {
+        for action in InputAction::ALL {
+            for other in InputAction::ALL.iter().filter(|other| **other != action) {
+                let shared = gamepad_buttons(action).iter().any(|button| gamepad_buttons(*other).contains(button));
+                let context = action.contexts().iter().find(|context| other.contexts().contains(context));
+                assert!(
+                    !shared || context.is_none(),
+                    "{:?} and {:?} share a button in {:?}",
+                    action,
+                    other,
+                    context.map(|context: &InputContext| context.name())
+                );
+            }
+        }
+    }
+}
@@ -1,5 +1,7 @@
 use bevy::prelude::*;
-use crate::components::inventory::Inventory;
+use serde::{Deserialize, Serialize};
+use crate::components::{inventory::Inventory, item::ItemId};
+use crate::resources::item_registry::{ItemRegistry, TAG_CONTRACT};
 
 /// Resource managing all active contracts
 #[derive(Resource, Debug)]
@@ -7,41 +9,36 @@ pub struct ContractSystem {
     pub active_contracts: Vec<Contract>,
 }
 
-impl Default for ContractSystem {
-    fn default() -> Self {
-        Self {
-            active_contracts: vec![
-                // POC: Single hardcoded contract
-                Contract {
-                    id: "contract_001".to_string(),
-                    description: "Bring back one Fully Empty from the Zone".to_string(),
-                    requirements: vec![
-                        ItemRequirement {
-                            item_name: "Fully Empty".to_string(),
-                            quantity: 1,
-                        },
-                    ],
-                    completed: false,
-                },
-            ],
-        }
+impl ContractSystem {
+    /// One contract per catalogue item tagged as wanted
+    pub fn from_registry(registry: &ItemRegistry) -> Self {
+        let active_contracts = registry
+            .with_tag(TAG_CONTRACT)
+            .map(|definition| Contract {
+                id: format!("contract_{}", definition.id),
+                description: format!("Bring back one {} from the Zone", definition.name),
+                requirements: vec![
+                    ItemRequirement {
+                        item_id: definition.id.clone(),
+                        quantity: 1,
+                    },
+                ],
+                completed: false,
+            })
+            .collect();
+
+        Self { active_contracts }
     }
-}
 
-impl ContractSystem {
     /// Check if all contracts are completed based on player inventory
     pub fn validate_contracts(&mut self, inventory: &Inventory) -> Vec<ContractStatus> {
         self.active_contracts
             .iter_mut()
             .map(|contract| {
-                let all_requirements_met = contract.requirements.iter().all(|req| {
-                    let count = inventory
-                        .items
-                        .iter()
-                        .filter(|item| item.name == req.item_name)
-                        .count() as u32;
-                    count >= req.quantity
-                });
+                let all_requirements_met = contract
+                    .requirements
+                    .iter()
+                    .all(|req| inventory.count_of(req.item_id.as_str()) >= req.quantity);
 
                 contract.completed = all_requirements_met;
 
@@ -62,7 +59,7 @@ impl ContractSystem {
 }
 
 /// Represents a contract/objective for the player
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Contract {
     pub id: String,
     pub description: String,
@@ -71,9 +68,9 @@ pub struct Contract {
 }
 
 /// Represents a required item for contract completion
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ItemRequirement {
-    pub item_name: String,
+    pub item_id: ItemId,
     pub quantity: u32,
 }
 
@@ -0,0 +1,5 @@
+use bevy::prelude::Resource;
+
+/// Debug toggle (F12): lifts the fog and shows every anomaly as it really is
+#[derive(Resource, Default)]
+pub struct DebugReveal(pub bool);
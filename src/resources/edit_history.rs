@@ -0,0 +1,172 @@
+use bevy::prelude::*;
+use std::collections::HashSet;
+use crate::components::{components::Position, item::Item};
+use crate::resources::game_grid::{EntityType, TileKind};
+use crate::resources::spawn_points::SpawnPoint;
+use crate::resources::zone_links::ZoneLink;
+use crate::sim::movement::MovementMode;
+
+/// One reversible change to the map being edited
+#[derive(Debug, Clone, PartialEq)]
+pub enum EditChange {
+    Terrain { x: usize, y: usize, before: TileKind, after: TileKind },
+    PlaceEntity { position: Position, entity_type: EntityType },
+    RemoveEntity { position: Position, entity_type: EntityType },
+    /// The items on a tile went from `before` to `after`; an empty list means no pile
+    Items { position: Position, before: Vec<Item>, after: Vec<Item> },
+    /// The spawn point on a tile went from `before` to `after`; None means there is none
+    Spawn { position: Position, before: Option<SpawnPoint>, after: Option<SpawnPoint> },
+    /// The zone link on a tile went from `before` to `after`; None means there is none
+    Link { position: Position, before: Option<ZoneLink>, after: Option<ZoneLink> },
+    /// The map's movement mode went from `before` to `after`; None leaves it to the settings
+    Movement { before: Option<MovementMode>, after: Option<MovementMode> },
+}
+
+impl EditChange {
+    /// The change that undoes this one
+    pub fn inverted(&self) -> EditChange {
+        match self {
+            EditChange::Terrain { x, y, before, after } => {
+                EditChange::Terrain { x: *x, y: *y, before: *after, after: *before }
+            }
+            EditChange::PlaceEntity { position, entity_type } => {
+                EditChange::RemoveEntity { position: *position, entity_type: *entity_type }
+            }
+            EditChange::RemoveEntity { position, entity_type } => {
+                EditChange::PlaceEntity { position: *position, entity_type: *entity_type }
+            }
+            EditChange::Items { position, before, after } => {
+                EditChange::Items { position: *position, before: after.clone(), after: before.clone() }
+            }
+            EditChange::Spawn { position, before, after } => {
+                EditChange::Spawn { position: *position, before: after.clone(), after: before.clone() }
+            }
+            EditChange::Link { position, before, after } => {
+                EditChange::Link { position: *position, before: after.clone(), after: before.clone() }
+            }
+            EditChange::Movement { before, after } => EditChange::Movement { before: *after, after: *before },
+        }
+    }
+}
+
+/// Changes undone and redone together: a click, or a whole drag-paint stroke
+pub type Edit = Vec<EditChange>;
+
+/// A drag-paint stroke in progress
+#[derive(Debug, Default)]
+struct Stroke {
+    changes: Edit,
+    /// Tiles already painted, so dragging back over one doesn't paint it twice
+    visited: HashSet<(usize, usize)>,
+}
+
+/// Undo/redo stacks for the editor
+#[derive(Resource, Debug, Default)]
+pub struct EditHistory {
+    undo: Vec<Edit>,
+    redo: Vec<Edit>,
+    stroke: Option<Stroke>,
+}
+
+impl EditHistory {
+    /// Starts a stroke, finishing any stroke still open
+    pub fn begin_stroke(&mut self) {
+        self.end_stroke();
+        self.stroke = Some(Stroke::default());
+    }
+
+    /// Marks a tile as painted by the current stroke; false if the stroke already painted it
+    /// (or no stroke is open)
+    pub fn visit(&mut self, x: usize, y: usize) -> bool {
+        self.stroke.as_mut().is_some_and(|stroke| stroke.visited.insert((x, y)))
+    }
+
+    /// Adds a change to the current stroke
+    pub fn record(&mut self, change: EditChange) {
+        self.stroke.get_or_insert_with(Stroke::default).changes.push(change);
+    }
+
+    /// Whether a stroke is still being painted
+    pub fn in_stroke(&self) -> bool {
+        self.stroke.is_some()
+    }
+
+    /// Finishes the current stroke; a stroke that changed anything becomes one undo step
+    pub fn end_stroke(&mut self) {
+        let Some(stroke) = self.stroke.take() else {
+            return;
+        };
+        if !stroke.changes.is_empty() {
+            self.undo.push(stroke.changes);
+            self.redo.clear();
+        }
+    }
+
+    /// The changes that undo the last edit, in the order to apply them
+    pub fn undo(&mut self) -> Option<Edit> {
+        self.end_stroke();
+        let edit = self.undo.pop()?;
+        let undone = edit.iter().rev().map(EditChange::inverted).collect();
+        self.redo.push(edit);
+        Some(undone)
+    }
+
+    /// The changes that redo the last undone edit, in the order to apply them
+    pub fn redo(&mut self) -> Option<Edit> {
+        self.end_stroke();
+        let edit = self.redo.pop()?;
+        self.undo.push(edit.clone());
+        Some(edit)
+    }
+
+    /// Forgets everything, e.g. when another map is loaded
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paint(x: usize) -> EditChange {
+        EditChange::Terrain { x, y: 0, before: TileKind::Floor, after: TileKind::Wall }
+    }
+
+    #[test]
+    fn test_strokes_undo_and_redo_as_one_edit() {
+        let mut history = EditHistory::default();
+
+        history.begin_stroke();
+        for x in [0, 1, 1, 2] {
+            if history.visit(x, 0) {
+                history.record(paint(x));
+            }
+        }
+        assert!(history.in_stroke());
+        history.end_stroke();
+        assert!(!history.in_stroke());
+
+        // Undo reverts the whole stroke, last tile first
+        let undone = history.undo().unwrap();
+        assert_eq!(undone, vec![paint(2).inverted(), paint(1).inverted(), paint(0).inverted()]);
+        assert!(history.undo().is_none());
+
+        assert_eq!(history.redo().unwrap(), vec![paint(0), paint(1), paint(2)]);
+
+        // A new edit after an undo drops the redo stack
+        history.undo();
+        history.begin_stroke();
+        history.record(EditChange::PlaceEntity { position: Position { x: 3, y: 3 }, entity_type: EntityType::Exit });
+        history.end_stroke();
+        assert!(history.redo().is_none());
+
+        // Empty strokes don't create undo steps
+        history.begin_stroke();
+        history.end_stroke();
+        assert_eq!(
+            history.undo().unwrap(),
+            vec![EditChange::RemoveEntity { position: Position { x: 3, y: 3 }, entity_type: EntityType::Exit }]
+        );
+    }
+}
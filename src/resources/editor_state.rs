@@ -1,12 +1,21 @@
 use bevy::prelude::*;
-use crate::resources::game_grid::{TileKind, EntityType, ItemType};
+use crate::resources::{
+    game_grid::{TileKind, EntityType},
+    map_data::{MapData, map_name},
+    editor_tools::{EditorTool, ToolDrag, MapRegion},
+};
+use crate::sim::movement::MovementMode;
 
 #[derive(Resource)]
 pub struct EditorState {
     pub mode: EditorMode,
     pub selected_terrain: TileKind,
     pub selected_entity: EntityType,
-    pub selected_item: ItemType,
+    pub selected_item: usize, // Index into the item catalogue
+    pub selected_spawn: usize, // Index into the spawn point presets
+    pub tool: EditorTool,
+    pub drag: Option<ToolDrag>, // Shape being dragged out by the current tool
+    pub clipboard: Option<MapRegion>, // Last region copied with the Copy tool
 }
 
 impl Default for EditorState {
@@ -15,7 +24,22 @@ impl Default for EditorState {
             mode: EditorMode::Terrain,
             selected_terrain: TileKind::Floor,
             selected_entity: EntityType::GravitationalAnomaly,
-            selected_item: ItemType::FullyEmpty,
+            selected_item: 0,
+            selected_spawn: 0,
+            tool: EditorTool::Brush,
+            drag: None,
+            clipboard: None,
+        }
+    }
+}
+
+impl EditorState {
+    /// The tool the mouse actually uses: terrain-only tools act as the brush outside Terrain mode
+    pub fn effective_tool(&self) -> EditorTool {
+        if self.tool.terrain_only() && self.mode != EditorMode::Terrain {
+            EditorTool::Brush
+        } else {
+            self.tool
         }
     }
 }
@@ -25,9 +49,72 @@ pub enum EditorMode {
     Terrain,
     Entity,
     Item,
+    Spawn,
 }
 
 #[derive(Resource, Default)]
 pub struct EditorCursor {
     pub grid_position: Option<(usize, usize)>,
 }
+
+/// Map-wide settings of the map being edited, saved with it
+#[derive(Resource, Default, Debug, Clone, Copy)]
+pub struct MapSettings {
+    /// Movement mode runs on this map use; None leaves it to the settings
+    pub movement: Option<MovementMode>,
+}
+
+/// The map as authored in the editor, set aside while a play session runs on a copy of it
+#[derive(Resource)]
+pub struct AuthoredMap(pub MapData);
+
+/// The file the map being edited belongs to, and whether it changed since it was saved or loaded
+#[derive(Resource, Default)]
+pub struct EditorMapFile {
+    /// None for a map that was never saved
+    pub path: Option<String>,
+    pub unsaved_changes: bool,
+    /// Outcome of the last save or load, shown in the editor HUD
+    pub status: Option<String>,
+}
+
+impl EditorMapFile {
+    /// Map name for the HUD, with a '*' when there are unsaved changes
+    pub fn title(&self) -> String {
+        let name = self.path.as_deref().map_or("untitled".to_string(), map_name);
+        if self.unsaved_changes {
+            format!("{}*", name)
+        } else {
+            name
+        }
+    }
+}
+
+/// Map file dialog open over the editor; placement and editor hotkeys pause while it exists
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub enum MapDialog {
+    /// Browse the maps directory and load one
+    Open { maps: Vec<String>, selected: usize },
+    /// Type a name to save the map under
+    SaveAs { name: String },
+    /// Type the size of a new, empty map
+    NewMap { width: String, height: String, editing_height: bool },
+    /// Type a seed and generator settings, then generate a zone into the current map
+    /// `fields` holds the text of each field, in the order the editor lists them
+    Generate { fields: Vec<String>, selected: usize },
+    /// Type the map and entry point the Exit at (x, y) leads to
+    /// `fields` holds the map name, entry x and entry y; an empty map name removes the link
+    LinkExit { x: usize, y: usize, fields: Vec<String>, selected: usize },
+    /// Browse the input actions and press a new key for one
+    /// `capturing` is set while waiting for that key; `status` reports the last rebind or conflict
+    Keybindings { selected: usize, capturing: bool, status: Option<String> },
+}
+
+/// Map to open at startup (`--map`, or the default map for `--play` and `--replay`)
+#[derive(Resource)]
+pub struct StartupMap {
+    pub path: String,
+    pub map: MapData,
+    /// Start playing the map straight away instead of editing it
+    pub play: bool,
+}
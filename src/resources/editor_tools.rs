@@ -0,0 +1,199 @@
+use bevy::prelude::*;
+use std::collections::{HashSet, VecDeque};
+use crate::components::item::Item;
+use crate::resources::game_grid::{EntityType, GameGrid, TileKind};
+
+/// What a mouse stroke does in the editor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EditorTool {
+    /// Paint every tile the cursor crosses
+    #[default]
+    Brush,
+    /// Fill the dragged rectangle with terrain
+    Rectangle,
+    /// Draw the border of the dragged rectangle
+    Outline,
+    /// Draw a straight line from press to release
+    Line,
+    /// Flood the connected area of the clicked terrain kind
+    Fill,
+    /// Copy the dragged rectangle (terrain, entities and items) to the clipboard
+    Copy,
+    /// Stamp the clipboard with its top-left corner on the clicked tile
+    Paste,
+}
+
+impl EditorTool {
+    pub const ALL: [EditorTool; 7] = [
+        EditorTool::Brush,
+        EditorTool::Rectangle,
+        EditorTool::Outline,
+        EditorTool::Line,
+        EditorTool::Fill,
+        EditorTool::Copy,
+        EditorTool::Paste,
+    ];
+
+    pub fn key(self) -> KeyCode {
+        match self {
+            EditorTool::Brush => KeyCode::KeyB,
+            EditorTool::Rectangle => KeyCode::KeyR,
+            EditorTool::Outline => KeyCode::KeyO,
+            EditorTool::Line => KeyCode::KeyL,
+            EditorTool::Fill => KeyCode::KeyF,
+            EditorTool::Copy => KeyCode::KeyC,
+            EditorTool::Paste => KeyCode::KeyV,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            EditorTool::Brush => "Brush",
+            EditorTool::Rectangle => "Rect",
+            EditorTool::Outline => "Outline",
+            EditorTool::Line => "Line",
+            EditorTool::Fill => "Fill",
+            EditorTool::Copy => "Copy",
+            EditorTool::Paste => "Paste",
+        }
+    }
+
+    /// Tools that only paint terrain; in Entity and Item mode they act as the brush
+    pub fn terrain_only(self) -> bool {
+        matches!(self, EditorTool::Rectangle | EditorTool::Outline | EditorTool::Line | EditorTool::Fill)
+    }
+
+    /// Tools that act on the shape dragged out between press and release
+    pub fn drags_shape(self) -> bool {
+        matches!(self, EditorTool::Rectangle | EditorTool::Outline | EditorTool::Line | EditorTool::Copy)
+    }
+}
+
+/// A shape being dragged out with the mouse
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ToolDrag {
+    pub start: (usize, usize),
+    pub end: (usize, usize),
+    /// Dragged with the right button: paint Floor instead of the selected terrain
+    pub erase: bool,
+}
+
+/// Top-left and bottom-right corners of the rectangle spanned by two tiles
+pub fn bounds(a: (usize, usize), b: (usize, usize)) -> ((usize, usize), (usize, usize)) {
+    ((a.0.min(b.0), a.1.min(b.1)), (a.0.max(b.0), a.1.max(b.1)))
+}
+
+/// Every tile of the rectangle spanned by two tiles
+pub fn rectangle(a: (usize, usize), b: (usize, usize)) -> Vec<(usize, usize)> {
+    let (min, max) = bounds(a, b);
+    (min.1..=max.1)
+        .flat_map(|y| (min.0..=max.0).map(move |x| (x, y)))
+        .collect()
+}
+
+/// The border tiles of the rectangle spanned by two tiles
+pub fn rectangle_outline(a: (usize, usize), b: (usize, usize)) -> Vec<(usize, usize)> {
+    let (min, max) = bounds(a, b);
+    rectangle(a, b)
+        .into_iter()
+        .filter(|(x, y)| *x == min.0 || *x == max.0 || *y == min.1 || *y == max.1)
+        .collect()
+}
+
+/// Tiles of a straight line between two tiles (Bresenham), both ends included
+pub fn line(a: (usize, usize), b: (usize, usize)) -> Vec<(usize, usize)> {
+    let (mut x, mut y) = (a.0 as i64, a.1 as i64);
+    let (x1, y1) = (b.0 as i64, b.1 as i64);
+    let (dx, dy) = ((x1 - x).abs(), -(y1 - y).abs());
+    let (sx, sy) = ((x1 - x).signum(), (y1 - y).signum());
+    let mut error = dx + dy;
+
+    let mut tiles = vec![(x as usize, y as usize)];
+    while (x, y) != (x1, y1) {
+        let doubled = 2 * error;
+        if doubled >= dy {
+            error += dy;
+            x += sx;
+        }
+        if doubled <= dx {
+            error += dx;
+            y += sy;
+        }
+        tiles.push((x as usize, y as usize));
+    }
+    tiles
+}
+
+/// Tiles a shape tool paints for a drag
+pub fn shape_tiles(tool: EditorTool, drag: &ToolDrag) -> Vec<(usize, usize)> {
+    match tool {
+        EditorTool::Outline => rectangle_outline(drag.start, drag.end),
+        EditorTool::Line => line(drag.start, drag.end),
+        _ => rectangle(drag.start, drag.end),
+    }
+}
+
+/// The tiles of the same terrain kind connected to `start` (4-way)
+pub fn flood_fill(grid: &GameGrid, start: (usize, usize)) -> Vec<(usize, usize)> {
+    let Some(kind) = grid.get_tile(start.0, start.1).map(|tile| tile.kind) else {
+        return Vec::new();
+    };
+
+    let mut reached = HashSet::from([start]);
+    let mut frontier = VecDeque::from([start]);
+    let mut tiles = Vec::new();
+    while let Some((x, y)) = frontier.pop_front() {
+        tiles.push((x, y));
+        let neighbours = [
+            (x.wrapping_sub(1), y),
+            (x + 1, y),
+            (x, y.wrapping_sub(1)),
+            (x, y + 1),
+        ];
+        for (nx, ny) in neighbours {
+            let same = grid.get_tile(nx, ny).is_some_and(|tile| tile.kind == kind);
+            if same && reached.insert((nx, ny)) {
+                frontier.push_back((nx, ny));
+            }
+        }
+    }
+    tiles
+}
+
+/// A copied rectangle of the map, with positions relative to its top-left corner
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapRegion {
+    pub width: usize,
+    pub height: usize,
+    /// Rows of terrain, `height` rows of `width` tiles
+    pub terrain: Vec<Vec<TileKind>>,
+    pub entities: Vec<((usize, usize), EntityType)>,
+    pub items: Vec<((usize, usize), Vec<Item>)>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shapes() {
+        assert_eq!(rectangle((2, 1), (0, 0)).len(), 6);
+        assert_eq!(rectangle_outline((0, 0), (3, 3)).len(), 12);
+        assert_eq!(rectangle_outline((0, 0), (0, 3)).len(), 4);
+        assert_eq!(line((0, 0), (4, 2)), vec![(0, 0), (1, 1), (2, 1), (3, 2), (4, 2)]);
+        assert_eq!(line((3, 3), (3, 0)), vec![(3, 3), (3, 2), (3, 1), (3, 0)]);
+        assert_eq!(line((1, 1), (1, 1)), vec![(1, 1)]);
+    }
+
+    #[test]
+    fn test_flood_fill_stops_at_other_terrain() {
+        let mut grid = GameGrid::new_empty(5, 5);
+        for y in 0..5 {
+            grid.set_tile(2, y, crate::resources::game_grid::Tile::new(TileKind::Wall));
+        }
+
+        assert_eq!(flood_fill(&grid, (0, 0)).len(), 10);
+        assert_eq!(flood_fill(&grid, (2, 4)).len(), 5);
+        assert!(flood_fill(&grid, (9, 9)).is_empty());
+    }
+}
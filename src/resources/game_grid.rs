@@ -1,42 +1,66 @@
 use bevy::prelude::{Resource, Entity, Component};
 use std::collections::HashMap;
 use crate::components::components::Position;
+use crate::constants::CHUNK_SIZE;
 
-#[derive(Resource)]
+/// Terrain of the map being played or edited
+/// Tiles are stored in CHUNK_SIZE x CHUNK_SIZE chunks, row by row, so a large map can be drawn and
+/// redrawn a chunk at a time; chunks on the right and bottom edges are padded with Floor
+#[derive(Resource, Clone, PartialEq)]
 pub struct GameGrid {
-    pub tiles: Vec<Vec<Tile>>,
     pub width: usize,
     pub height: usize,
+    chunks: Vec<Vec<Tile>>,
 }
 
 impl GameGrid {
     pub fn new_empty(width: usize, height: usize) -> Self {
-        let mut tiles = Vec::with_capacity(height);
-        for _ in 0..height {
-            let mut row = Vec::with_capacity(width);
-            for _ in 0..width {
-                row.push(Tile::new(TileKind::Floor));
-            }
-            tiles.push(row);
-        }
-
+        let chunk_count = width.div_ceil(CHUNK_SIZE) * height.div_ceil(CHUNK_SIZE);
         Self {
-            tiles,
             width,
             height,
+            chunks: vec![vec![Tile::new(TileKind::Floor); CHUNK_SIZE * CHUNK_SIZE]; chunk_count],
+        }
+    }
+
+    /// Chunks across and down the map
+    pub fn chunks_wide(&self) -> usize {
+        self.width.div_ceil(CHUNK_SIZE)
+    }
+
+    pub fn chunks_high(&self) -> usize {
+        self.height.div_ceil(CHUNK_SIZE)
+    }
+
+    /// Tiles of a chunk, row by row (CHUNK_SIZE per row, edge padding included)
+    pub fn chunk(&self, chunk_x: usize, chunk_y: usize) -> Option<&[Tile]> {
+        if chunk_x >= self.chunks_wide() {
+            return None;
+        }
+        self.chunks.get(chunk_y * self.chunks_wide() + chunk_x).map(Vec::as_slice)
+    }
+
+    // Chunk and index within it of an on-map tile
+    fn locate(&self, x: usize, y: usize) -> Option<(usize, usize)> {
+        if x >= self.width || y >= self.height {
+            return None;
         }
+        let chunk = (y / CHUNK_SIZE) * self.chunks_wide() + x / CHUNK_SIZE;
+        Some((chunk, (y % CHUNK_SIZE) * CHUNK_SIZE + x % CHUNK_SIZE))
     }
 
     pub fn get_tile(&self, x: usize, y: usize) -> Option<&Tile> {
-        self.tiles.get(y)?.get(x)
+        let (chunk, index) = self.locate(x, y)?;
+        Some(&self.chunks[chunk][index])
     }
 
     pub fn set_tile(&mut self, x: usize, y: usize, tile: Tile) -> bool {
-        if y < self.height && x < self.width {
-            self.tiles[y][x] = tile;
-            true
-        } else {
-            false
+        match self.locate(x, y) {
+            Some((chunk, index)) => {
+                self.chunks[chunk][index] = tile;
+                true
+            }
+            None => false,
         }
     }
 }
@@ -48,7 +72,7 @@ pub enum TileKind {
     Wall,
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Tile {
     pub kind: TileKind,
     pub move_cost: i32,
@@ -78,72 +102,26 @@ pub enum EntityType {
     LampPost,
 }
 
-// Item layer - items that can be placed on ground tiles
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-pub enum ItemType {
-    FullyEmpty,
-    Scrap,
-    GlassJar,
-    Battery,
-    Bolt,
-    MetalDetector,
-    RustSlag,
-}
-
-impl ItemType {
-    /// Returns all ItemType variants for dynamic querying
-    /// Used by Philosopher's Stone anomaly for dynamic transformations
-    pub fn all_variants() -> Vec<ItemType> {
-        vec![
-            ItemType::FullyEmpty,
-            ItemType::Scrap,
-            ItemType::GlassJar,
-            ItemType::Battery,
-            ItemType::Bolt,
-            ItemType::MetalDetector,
-            ItemType::RustSlag,
-        ]
-    }
-}
-
 #[derive(Resource, Default)]
 pub struct SpatialGrid(pub HashMap<Position, Vec<Entity>>);
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::components::item::Item;
 
     #[test]
-    fn test_all_item_variants_covered() {
-        // This test ensures that all ItemType variants are included in all_variants()
-        // If a new ItemType is added but forgotten in all_variants(), this test will fail
-
-        let variants = ItemType::all_variants();
-
-        // Test each variant can be converted to Item and back
-        for variant in &variants {
-            let item: Item = (*variant).into();
-            // If conversion works, the variant is properly defined
-            assert!(!item.name.is_empty(), "Item name should not be empty for {:?}", variant);
-        }
-
-        // Check count matches expected number of variants
-        // Update this number when adding new ItemType variants
-        assert_eq!(
-            variants.len(),
-            7,
-            "Expected 7 ItemType variants. If you added a new variant, update this test and all_variants()"
-        );
-
-        // Verify no duplicates in all_variants()
-        let mut seen = std::collections::HashSet::new();
-        for variant in &variants {
-            assert!(
-                seen.insert(variant),
-                "Duplicate variant {:?} in all_variants()",
-                variant
-            );
-        }
+    fn test_tiles_land_in_their_chunk() {
+        let mut grid = GameGrid::new_empty(CHUNK_SIZE + 3, 2);
+        assert_eq!((grid.chunks_wide(), grid.chunks_high()), (2, 1));
+
+        assert!(grid.set_tile(CHUNK_SIZE + 1, 1, Tile::new(TileKind::Wall)));
+        assert!(!grid.set_tile(CHUNK_SIZE + 3, 1, Tile::new(TileKind::Wall)));
+        assert_eq!(grid.get_tile(CHUNK_SIZE + 1, 1).unwrap().kind, TileKind::Wall);
+        assert!(grid.get_tile(0, 2).is_none());
+
+        // Only the chunk holding the tile changed
+        assert!(grid.chunk(0, 0).unwrap().iter().all(|tile| tile.kind == TileKind::Floor));
+        assert_eq!(grid.chunk(1, 0).unwrap()[CHUNK_SIZE + 1].kind, TileKind::Wall);
+        assert!(grid.chunk(2, 0).is_none());
     }
-}
\ No newline at end of file
+}
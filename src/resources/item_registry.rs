@@ -0,0 +1,154 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use crate::components::item::{Item, ItemId};
+
+/// Where the item catalogue is loaded from at startup
+pub const ITEM_CATALOGUE_PATH: &str = "assets/items.json";
+
+// Items the game itself refers to; the catalogue must define these
+pub const BOLT: &str = "bolt";
+pub const METAL_DETECTOR: &str = "metal_detector";
+pub const RUST_SLAG: &str = "rust_slag";
+pub const FULLY_EMPTY: &str = "fully_empty";
+
+/// Tag for items the Philosopher's Stone can turn things into
+pub const TAG_TRANSMUTABLE: &str = "transmutable";
+/// Tag for items there is a standing contract for
+pub const TAG_CONTRACT: &str = "contract";
+
+/// One entry of the item catalogue
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ItemDefinition {
+    pub id: ItemId,
+    pub name: String,
+    pub weight: u32,
+    pub value: Option<u32>,
+    pub is_metal: bool,
+    /// Identical units share one inventory/ground entry with a quantity
+    #[serde(default)]
+    pub stackable: bool,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub description: String,
+    /// Image under assets/ shown for a pile whose top item this is
+    #[serde(default)]
+    pub sprite: Option<String>,
+}
+
+impl ItemDefinition {
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    /// A fresh item of this kind
+    pub fn create(&self) -> Item {
+        Item::new(self.id.clone(), self.name.clone(), self.weight, self.value, self.is_metal)
+    }
+}
+
+/// Every item the game knows about, in catalogue order
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ItemRegistry {
+    items: Vec<ItemDefinition>,
+}
+
+impl ItemRegistry {
+    /// Builds a registry, checking ids are unique and the items the game relies on exist
+    pub fn new(items: Vec<ItemDefinition>) -> Result<Self, String> {
+        let mut seen = HashSet::new();
+        for definition in &items {
+            if !seen.insert(definition.id.as_str()) {
+                return Err(format!("Duplicate item id '{}'", definition.id));
+            }
+        }
+
+        for required in [BOLT, METAL_DETECTOR, RUST_SLAG, FULLY_EMPTY] {
+            if !seen.contains(required) {
+                return Err(format!("Item catalogue is missing required item '{}'", required));
+            }
+        }
+
+        Ok(Self { items })
+    }
+
+    pub fn get(&self, id: &str) -> Option<&ItemDefinition> {
+        self.items.iter().find(|definition| definition.id == id)
+    }
+
+    /// A fresh item by id
+    /// Panics on unknown ids, so only use it with ids checked by `new` or taken from the registry
+    pub fn create(&self, id: &str) -> Item {
+        self.get(id)
+            .unwrap_or_else(|| panic!("Unknown item id '{}'", id))
+            .create()
+    }
+
+    /// Fills in the ids of items saved before items had ids, by their display name
+    pub fn resolve_ids<'a>(&self, items: impl IntoIterator<Item = &'a mut Item>) -> Result<(), String> {
+        for item in items.into_iter().filter(|item| item.id.as_str().is_empty()) {
+            let definition = self
+                .items
+                .iter()
+                .find(|definition| definition.name == item.name)
+                .ok_or_else(|| format!("Unknown item '{}'", item.name))?;
+            item.id = definition.id.clone();
+        }
+        Ok(())
+    }
+
+    pub fn with_tag<'a>(&'a self, tag: &'a str) -> impl Iterator<Item = &'a ItemDefinition> {
+        self.items.iter().filter(move |definition| definition.has_tag(tag))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ItemDefinition> {
+        self.items.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn load_from_file(path: &str) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+
+        let catalogue: ItemRegistry = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to deserialize: {}", e))?;
+
+        Self::new(catalogue.items)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catalogue_loads() {
+        let registry = ItemRegistry::load_from_file(ITEM_CATALOGUE_PATH).unwrap();
+
+        let bolt = registry.create(BOLT);
+        assert_eq!(bolt.id, BOLT);
+        assert!(!bolt.name.is_empty());
+        assert!(registry.with_tag(TAG_TRANSMUTABLE).count() > 0);
+    }
+
+    #[test]
+    fn test_catalogue_rejects_duplicates_and_missing_items() {
+        let registry = ItemRegistry::load_from_file(ITEM_CATALOGUE_PATH).unwrap();
+
+        let mut duplicated: Vec<ItemDefinition> = registry.iter().cloned().collect();
+        duplicated.push(registry.get(BOLT).unwrap().clone());
+        assert!(ItemRegistry::new(duplicated).is_err());
+
+        let without_slag = registry.iter().filter(|d| d.id != RUST_SLAG).cloned().collect();
+        assert!(ItemRegistry::new(without_slag).is_err());
+    }
+}
@@ -0,0 +1,592 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use crate::resources::editor_tools::EditorTool;
+
+/// What the player means by a key press, independent of which key it is
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum InputAction {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    MoveUpLeft,
+    MoveUpRight,
+    MoveDownLeft,
+    MoveDownRight,
+    Inspect,
+    Inventory,
+    /// Enters bolt throwing mode; pressed again it puts the bolt away
+    ThrowBolt,
+    MarkMap,
+    MarkDanger,
+    MarkSafe,
+    MarkBolt,
+    /// Starts typing a note on the marker cursor's tile
+    MarkNote,
+    RemoveMarker,
+    /// Jumps the marker cursor to the next marker
+    NextMarker,
+    /// Leaves marking mode and walks the player to the cursor
+    WalkToCursor,
+    /// Toggles keeping the markers for the next run on the map
+    KeepMarkers,
+    SaveRun,
+    MenuUp,
+    MenuDown,
+    AmountLess,
+    AmountMore,
+    Pickup,
+    Drop,
+    /// Picks the selected inventory item to throw
+    Throw,
+    /// Closes a menu or backs out of a mode
+    Cancel,
+    /// Dismisses the enter zone, exit zone and death screens
+    Confirm,
+    Quit,
+    ReplayPause,
+    /// Plays one recorded action while the replay is paused
+    ReplayStep,
+    ReplayFastForward,
+    /// Shows every anomaly during a run, for debugging
+    DebugReveal,
+    /// Switches between Running and Editing
+    ToggleMode,
+    EditorMode,
+    SaveMap,
+    OpenMap,
+    SaveMapAs,
+    NewMap,
+    GenerateZone,
+    ResumeRun,
+    LinkExit,
+    CycleMovement,
+    Keybindings,
+}
+
+/// Where a key press is read; an action only answers to its keys in its own contexts, so the same
+/// key can mean different things in different contexts but not two things in one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputContext {
+    PlayerTurn,
+    Throwing,
+    InspectingItems,
+    ViewingInventory,
+    PlacingMarkers,
+    /// The enter zone, exit zone and death screens
+    Screen,
+    /// Any phase of a run being played back from a replay
+    Replay,
+    Editing,
+}
+
+impl InputContext {
+    pub fn name(self) -> &'static str {
+        match self {
+            InputContext::PlayerTurn => "player turn",
+            InputContext::Throwing => "throwing",
+            InputContext::InspectingItems => "inspect menu",
+            InputContext::ViewingInventory => "inventory",
+            InputContext::PlacingMarkers => "marking mode",
+            InputContext::Screen => "zone screens",
+            InputContext::Replay => "replay",
+            InputContext::Editing => "editor",
+        }
+    }
+}
+
+impl InputAction {
+    /// Every action, in the order the rebinding screen lists them
+    pub const ALL: [InputAction; 46] = [
+        InputAction::MoveUp,
+        InputAction::MoveDown,
+        InputAction::MoveLeft,
+        InputAction::MoveRight,
+        InputAction::MoveUpLeft,
+        InputAction::MoveUpRight,
+        InputAction::MoveDownLeft,
+        InputAction::MoveDownRight,
+        InputAction::Inspect,
+        InputAction::Inventory,
+        InputAction::ThrowBolt,
+        InputAction::MarkMap,
+        InputAction::MarkDanger,
+        InputAction::MarkSafe,
+        InputAction::MarkBolt,
+        InputAction::MarkNote,
+        InputAction::RemoveMarker,
+        InputAction::NextMarker,
+        InputAction::WalkToCursor,
+        InputAction::KeepMarkers,
+        InputAction::SaveRun,
+        InputAction::MenuUp,
+        InputAction::MenuDown,
+        InputAction::AmountLess,
+        InputAction::AmountMore,
+        InputAction::Pickup,
+        InputAction::Drop,
+        InputAction::Throw,
+        InputAction::Cancel,
+        InputAction::Confirm,
+        InputAction::Quit,
+        InputAction::ReplayPause,
+        InputAction::ReplayStep,
+        InputAction::ReplayFastForward,
+        InputAction::DebugReveal,
+        InputAction::ToggleMode,
+        InputAction::EditorMode,
+        InputAction::SaveMap,
+        InputAction::OpenMap,
+        InputAction::SaveMapAs,
+        InputAction::NewMap,
+        InputAction::GenerateZone,
+        InputAction::ResumeRun,
+        InputAction::LinkExit,
+        InputAction::CycleMovement,
+        InputAction::Keybindings,
+    ];
+
+    /// The step a movement action takes
+    pub const STEPS: [(InputAction, (i32, i32)); 8] = [
+        (InputAction::MoveUp, (0, -1)), // Up (negative Y in grid coordinates)
+        (InputAction::MoveDown, (0, 1)), // Down (positive Y in grid coordinates)
+        (InputAction::MoveLeft, (-1, 0)),
+        (InputAction::MoveRight, (1, 0)),
+        (InputAction::MoveUpLeft, (-1, -1)),
+        (InputAction::MoveUpRight, (1, -1)),
+        (InputAction::MoveDownLeft, (-1, 1)),
+        (InputAction::MoveDownRight, (1, 1)),
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            InputAction::MoveUp => "Move up",
+            InputAction::MoveDown => "Move down",
+            InputAction::MoveLeft => "Move left",
+            InputAction::MoveRight => "Move right",
+            InputAction::MoveUpLeft => "Move up-left",
+            InputAction::MoveUpRight => "Move up-right",
+            InputAction::MoveDownLeft => "Move down-left",
+            InputAction::MoveDownRight => "Move down-right",
+            InputAction::Inspect => "Inspect tile",
+            InputAction::Inventory => "Inventory",
+            InputAction::ThrowBolt => "Throw bolt",
+            InputAction::MarkMap => "Mark map",
+            InputAction::MarkDanger => "Danger marker",
+            InputAction::MarkSafe => "Safe marker",
+            InputAction::MarkBolt => "Bolt marker",
+            InputAction::MarkNote => "Write note",
+            InputAction::RemoveMarker => "Remove marker",
+            InputAction::NextMarker => "Next marker",
+            InputAction::WalkToCursor => "Walk to cursor",
+            InputAction::KeepMarkers => "Keep markers",
+            InputAction::SaveRun => "Save run",
+            InputAction::MenuUp => "Menu up",
+            InputAction::MenuDown => "Menu down",
+            InputAction::AmountLess => "Amount -1",
+            InputAction::AmountMore => "Amount +1",
+            InputAction::Pickup => "Pick up",
+            InputAction::Drop => "Drop",
+            InputAction::Throw => "Throw item",
+            InputAction::Cancel => "Cancel / close",
+            InputAction::Confirm => "Confirm",
+            InputAction::Quit => "Quit",
+            InputAction::ReplayPause => "Pause replay",
+            InputAction::ReplayStep => "Step replay",
+            InputAction::ReplayFastForward => "Fast-forward replay",
+            InputAction::DebugReveal => "Debug reveal",
+            InputAction::ToggleMode => "Play / edit",
+            InputAction::EditorMode => "Editor placement mode",
+            InputAction::SaveMap => "Save map",
+            InputAction::OpenMap => "Open map",
+            InputAction::SaveMapAs => "Save map as",
+            InputAction::NewMap => "New map",
+            InputAction::GenerateZone => "Generate zone",
+            InputAction::ResumeRun => "Resume run",
+            InputAction::LinkExit => "Link exit",
+            InputAction::CycleMovement => "Map movement mode",
+            InputAction::Keybindings => "Keybindings",
+        }
+    }
+
+    /// Contexts the action is read in
+    pub fn contexts(self) -> &'static [InputContext] {
+        use InputContext::*;
+        match self {
+            InputAction::MoveUp
+            | InputAction::MoveDown
+            | InputAction::MoveLeft
+            | InputAction::MoveRight
+            | InputAction::MoveUpLeft
+            | InputAction::MoveUpRight
+            | InputAction::MoveDownLeft
+            | InputAction::MoveDownRight => &[PlayerTurn, Throwing, PlacingMarkers],
+            InputAction::ThrowBolt => &[PlayerTurn, Throwing],
+            InputAction::Inspect | InputAction::Inventory | InputAction::SaveRun => &[PlayerTurn],
+            InputAction::MarkMap => &[PlayerTurn, PlacingMarkers],
+            InputAction::MarkDanger
+            | InputAction::MarkSafe
+            | InputAction::MarkBolt
+            | InputAction::MarkNote
+            | InputAction::RemoveMarker
+            | InputAction::NextMarker
+            | InputAction::WalkToCursor
+            | InputAction::KeepMarkers => &[PlacingMarkers],
+            InputAction::MenuUp | InputAction::MenuDown | InputAction::AmountLess | InputAction::AmountMore => {
+                &[InspectingItems, ViewingInventory]
+            }
+            InputAction::Pickup => &[InspectingItems],
+            InputAction::Drop | InputAction::Throw => &[ViewingInventory],
+            InputAction::Cancel => &[Throwing, InspectingItems, ViewingInventory, PlacingMarkers],
+            InputAction::Confirm => &[Screen, Replay],
+            InputAction::Quit => &[PlayerTurn, Screen, Replay, Editing],
+            InputAction::ReplayPause | InputAction::ReplayStep | InputAction::ReplayFastForward => &[Replay],
+            InputAction::DebugReveal => &[PlayerTurn, Throwing, InspectingItems, ViewingInventory, PlacingMarkers, Screen, Replay],
+            InputAction::ToggleMode => &[PlayerTurn, Replay, Editing],
+            InputAction::EditorMode
+            | InputAction::SaveMap
+            | InputAction::OpenMap
+            | InputAction::SaveMapAs
+            | InputAction::NewMap
+            | InputAction::GenerateZone
+            | InputAction::ResumeRun
+            | InputAction::LinkExit
+            | InputAction::CycleMovement
+            | InputAction::Keybindings => &[Editing],
+        }
+    }
+
+    /// Keys the action starts out with
+    pub fn default_keys(self) -> Vec<KeyCode> {
+        use KeyCode::*;
+        match self {
+            InputAction::MoveUp => vec![KeyW, Numpad8, KeyK, ArrowUp],
+            InputAction::MoveDown => vec![KeyS, Numpad2, KeyJ, ArrowDown],
+            InputAction::MoveLeft => vec![KeyA, Numpad4, KeyH, ArrowLeft],
+            InputAction::MoveRight => vec![KeyD, Numpad6, KeyL, ArrowRight],
+            InputAction::MoveUpLeft => vec![Numpad7, KeyY],
+            InputAction::MoveUpRight => vec![Numpad9, KeyU],
+            InputAction::MoveDownLeft => vec![Numpad1, KeyB],
+            InputAction::MoveDownRight => vec![Numpad3, KeyN],
+            InputAction::Inspect => vec![KeyE],
+            InputAction::Inventory => vec![Tab],
+            InputAction::ThrowBolt => vec![KeyQ],
+            InputAction::MarkMap => vec![KeyM],
+            InputAction::MarkDanger => vec![Digit1],
+            InputAction::MarkSafe => vec![Digit2],
+            InputAction::MarkBolt => vec![Digit3],
+            // N and K are vi-keys for moving the cursor
+            InputAction::MarkNote => vec![Enter],
+            InputAction::RemoveMarker => vec![KeyX, Delete],
+            InputAction::NextMarker => vec![Tab],
+            InputAction::WalkToCursor => vec![KeyT],
+            InputAction::KeepMarkers => vec![KeyR],
+            InputAction::SaveRun => vec![F5],
+            InputAction::MenuUp => vec![KeyW],
+            InputAction::MenuDown => vec![KeyS],
+            InputAction::AmountLess => vec![ArrowLeft],
+            InputAction::AmountMore => vec![ArrowRight],
+            InputAction::Pickup => vec![KeyE],
+            InputAction::Drop => vec![KeyD],
+            InputAction::Throw => vec![KeyT],
+            InputAction::Cancel => vec![Escape],
+            InputAction::Confirm => vec![KeyE],
+            InputAction::Quit => vec![Escape],
+            InputAction::ReplayPause => vec![Space],
+            InputAction::ReplayStep => vec![KeyN],
+            InputAction::ReplayFastForward => vec![KeyF],
+            InputAction::DebugReveal => vec![F12],
+            InputAction::ToggleMode => vec![F2],
+            InputAction::EditorMode => vec![Tab],
+            InputAction::SaveMap => vec![F3],
+            InputAction::OpenMap => vec![F4],
+            InputAction::SaveMapAs => vec![F6],
+            InputAction::NewMap => vec![F7],
+            InputAction::GenerateZone => vec![F8],
+            InputAction::ResumeRun => vec![F9],
+            InputAction::LinkExit => vec![KeyK],
+            InputAction::CycleMovement => vec![KeyM],
+            InputAction::Keybindings => vec![F10],
+        }
+    }
+
+    fn shares_context(self, other: InputAction) -> Option<InputContext> {
+        self.contexts().iter().copied().find(|context| other.contexts().contains(context))
+    }
+}
+
+/// Keys that can be bound, by their `KeyCode` names ("KeyW", "Digit1", "F2", "Escape"...)
+const BINDABLE_KEYS: [KeyCode; 88] = {
+    use KeyCode::*;
+    [
+        KeyA, KeyB, KeyC, KeyD, KeyE, KeyF, KeyG, KeyH, KeyI, KeyJ, KeyK, KeyL, KeyM,
+        KeyN, KeyO, KeyP, KeyQ, KeyR, KeyS, KeyT, KeyU, KeyV, KeyW, KeyX, KeyY, KeyZ,
+        Digit0, Digit1, Digit2, Digit3, Digit4, Digit5, Digit6, Digit7, Digit8, Digit9,
+        Numpad0, Numpad1, Numpad2, Numpad3, Numpad4, Numpad5, Numpad6, Numpad7, Numpad8, Numpad9,
+        F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12,
+        ArrowUp, ArrowDown, ArrowLeft, ArrowRight,
+        Tab, Escape, Enter, Space, Backspace, Delete, Insert, Home, End, PageUp, PageDown,
+        Minus, Equal, Comma, Period, Slash, Semicolon, Quote, Backquote, Backslash, BracketLeft, BracketRight,
+        NumpadAdd, NumpadSubtract, NumpadEnter, NumpadDecimal,
+    ]
+};
+
+/// The `KeyCode` name of a key, as written in the keybindings file
+pub fn key_name(key: KeyCode) -> String {
+    format!("{:?}", key)
+}
+
+/// Short name of a key for on-screen hints, e.g. "W", "1", "ESC", "←"
+pub fn key_label(key: KeyCode) -> String {
+    match key {
+        KeyCode::Escape => "ESC".to_string(),
+        KeyCode::ArrowUp => "↑".to_string(),
+        KeyCode::ArrowDown => "↓".to_string(),
+        KeyCode::ArrowLeft => "←".to_string(),
+        KeyCode::ArrowRight => "→".to_string(),
+        _ => key_name(key).trim_start_matches("Key").trim_start_matches("Digit").to_string(),
+    }
+}
+
+/// Reads a key by its `KeyCode` name, or its short name ("W" for KeyW, "1" for Digit1)
+pub fn parse_key(name: &str) -> Result<KeyCode, String> {
+    let name = name.trim();
+    [name.to_string(), format!("Key{}", name), format!("Digit{}", name)]
+        .iter()
+        .find_map(|candidate| BINDABLE_KEYS.iter().find(|key| key_name(**key).eq_ignore_ascii_case(candidate)))
+        .copied()
+        .ok_or_else(|| format!("Unknown key '{}'", name))
+}
+
+/// Keys with a fixed meaning in a context, which no action may take over there
+/// (the editor's tool keys, number keys, brackets and WASD camera pan)
+fn fixed_keys(context: InputContext) -> Vec<KeyCode> {
+    match context {
+        InputContext::Editing => EditorTool::ALL
+            .iter()
+            .map(|tool| tool.key())
+            .chain([
+                KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3, KeyCode::Digit4, KeyCode::Digit5,
+                KeyCode::Digit6, KeyCode::Digit7, KeyCode::Digit8, KeyCode::Digit9,
+                KeyCode::BracketLeft, KeyCode::BracketRight,
+                KeyCode::KeyW, KeyCode::KeyA, KeyCode::KeyS, KeyCode::KeyD,
+            ])
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// One key doing two things in the same context
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyConflict {
+    pub key: KeyCode,
+    pub context: InputContext,
+    pub action: InputAction,
+    /// The other action on the key; None for a key with a fixed meaning in the context
+    pub other: Option<InputAction>,
+}
+
+impl fmt::Display for KeyConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let other = self.other.map_or("a fixed editor key", InputAction::label);
+        write!(
+            f,
+            "{} is bound to both {} and {} in the {}",
+            key_label(self.key),
+            self.action.label(),
+            other,
+            self.context.name()
+        )
+    }
+}
+
+/// The keys each action answers to, loaded from the keybindings file over the defaults
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct Keybindings {
+    bindings: BTreeMap<InputAction, Vec<KeyCode>>,
+}
+
+impl Default for Keybindings {
+    fn default() -> Self {
+        Self {
+            bindings: InputAction::ALL.iter().map(|action| (*action, action.default_keys())).collect(),
+        }
+    }
+}
+
+impl Keybindings {
+    pub fn keys(&self, action: InputAction) -> &[KeyCode] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Whether one of the action's keys went down this frame
+    pub fn just_pressed(&self, keyboard: &ButtonInput<KeyCode>, action: InputAction) -> bool {
+        keyboard.any_just_pressed(self.keys(action).iter().copied())
+    }
+
+    /// The action's keys for on-screen hints, e.g. "W/Numpad8/K"
+    pub fn label(&self, action: InputAction) -> String {
+        let keys: Vec<String> = self.keys(action).iter().map(|key| key_label(*key)).collect();
+        if keys.is_empty() {
+            "(unbound)".to_string()
+        } else {
+            keys.join("/")
+        }
+    }
+
+    /// The conflict binding `key` to `action` would cause, if any
+    pub fn conflict_for(&self, action: InputAction, key: KeyCode) -> Option<KeyConflict> {
+        let fixed = action
+            .contexts()
+            .iter()
+            .find(|context| fixed_keys(**context).contains(&key))
+            .map(|context| KeyConflict { key, context: *context, action, other: None });
+
+        fixed.or_else(|| {
+            self.bindings
+                .iter()
+                .filter(|(other, keys)| **other != action && keys.contains(&key))
+                .find_map(|(other, _)| {
+                    action
+                        .shares_context(*other)
+                        .map(|context| KeyConflict { key, context, action, other: Some(*other) })
+                })
+        })
+    }
+
+    /// Every key that does two things in one context
+    pub fn conflicts(&self) -> Vec<KeyConflict> {
+        let mut conflicts: Vec<KeyConflict> = Vec::new();
+        for (action, keys) in &self.bindings {
+            for key in keys {
+                let Some(conflict) = self.conflict_for(*action, *key) else {
+                    continue;
+                };
+                // Each pair once
+                let reported = conflicts
+                    .iter()
+                    .any(|seen| seen.key == conflict.key && seen.other == Some(conflict.action) && Some(seen.action) == conflict.other);
+                if !reported {
+                    conflicts.push(conflict);
+                }
+            }
+        }
+        conflicts
+    }
+
+    /// Makes `key` the only key of `action`, unless it would do two things in one context
+    pub fn bind(&mut self, action: InputAction, key: KeyCode) -> Result<(), String> {
+        if let Some(conflict) = self.conflict_for(action, key) {
+            return Err(conflict.to_string());
+        }
+        self.bindings.insert(action, vec![key]);
+        Ok(())
+    }
+
+    /// Puts the action back on its default keys
+    pub fn reset(&mut self, action: InputAction) {
+        self.bindings.insert(action, action.default_keys());
+    }
+
+    /// Reads a keybindings file: a JSON object from action to key names, e.g.
+    /// `{"ThrowBolt": ["KeyF"], "MoveUp": ["KeyW", "ArrowUp"]}`; actions it leaves out keep their defaults
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let file: BTreeMap<InputAction, Vec<String>> =
+            serde_json::from_str(json).map_err(|e| format!("Failed to deserialize: {}", e))?;
+
+        let mut keybindings = Self::default();
+        for (action, names) in file {
+            let keys = names
+                .iter()
+                .map(|name| parse_key(name))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| format!("{} for {:?}", e, action))?;
+            keybindings.bindings.insert(action, keys);
+        }
+        Ok(keybindings)
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        let file: BTreeMap<InputAction, Vec<String>> = self
+            .bindings
+            .iter()
+            .map(|(action, keys)| (*action, keys.iter().map(|key| key_name(*key)).collect()))
+            .collect();
+        serde_json::to_string_pretty(&file).map_err(|e| format!("Failed to serialize: {}", e))
+    }
+
+    /// Loads the keybindings file, or the defaults if there isn't one
+    pub fn load_or_default(path: &str) -> Result<Self, String> {
+        if !Path::new(path).exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path).map_err(|e| format!("Failed to read file: {}", e))?;
+        Self::from_json(&content).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    pub fn save_to_file(&self, path: &str) -> Result<(), String> {
+        if let Some(parent) = Path::new(path).parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+        }
+
+        fs::write(path, self.to_json()?)
+            .map_err(|e| format!("Failed to write file: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults_have_no_conflicts() {
+        assert_eq!(Keybindings::default().conflicts(), Vec::new());
+    }
+
+    #[test]
+    fn test_conflicts_are_per_context() {
+        let mut keybindings = Keybindings::default();
+
+        // D moves right on the player's turn and drops in the inventory: different contexts
+        assert!(keybindings.keys(InputAction::MoveRight).contains(&KeyCode::KeyD));
+        assert!(keybindings.keys(InputAction::Drop).contains(&KeyCode::KeyD));
+
+        // Q can't also inspect on the player's turn, and B is the editor's Brush tool
+        let conflict = keybindings.bind(InputAction::Inspect, KeyCode::KeyQ).unwrap_err();
+        assert!(conflict.contains("Throw bolt") && conflict.contains("player turn"), "{}", conflict);
+        assert!(keybindings.bind(InputAction::SaveMap, KeyCode::KeyB).is_err());
+        assert_eq!(keybindings.keys(InputAction::Inspect), &[KeyCode::KeyE]);
+
+        // MarkMap also leaves marking mode, where T walks to the cursor and the move keys move it
+        let conflict = keybindings.conflict_for(InputAction::MarkMap, KeyCode::KeyT).unwrap();
+        assert_eq!((conflict.context, conflict.other), (InputContext::PlacingMarkers, Some(InputAction::WalkToCursor)));
+        assert!(keybindings.conflict_for(InputAction::MarkMap, KeyCode::KeyX).is_some());
+        assert!(keybindings.conflict_for(InputAction::NextMarker, KeyCode::ArrowUp).is_some());
+        assert!(keybindings.conflict_for(InputAction::ReplayStep, KeyCode::Escape).is_some());
+        assert!(keybindings.conflict_for(InputAction::KeepMarkers, KeyCode::KeyQ).is_none());
+
+        keybindings.bind(InputAction::ThrowBolt, KeyCode::KeyF).unwrap();
+        assert_eq!(keybindings.keys(InputAction::ThrowBolt), &[KeyCode::KeyF]);
+        keybindings.reset(InputAction::ThrowBolt);
+        assert_eq!(keybindings, Keybindings::default());
+    }
+
+    #[test]
+    fn test_file_round_trip_and_partial_files() {
+        let keybindings = Keybindings::from_json(r#"{"ThrowBolt": ["F"], "MenuUp": ["KeyW", "ArrowUp"]}"#).unwrap();
+        assert_eq!(keybindings.keys(InputAction::ThrowBolt), &[KeyCode::KeyF]);
+        assert_eq!(keybindings.keys(InputAction::MenuUp), &[KeyCode::KeyW, KeyCode::ArrowUp]);
+        assert_eq!(keybindings.keys(InputAction::Pickup), &[KeyCode::KeyE]);
+        assert_eq!(Keybindings::from_json(&keybindings.to_json().unwrap()).unwrap(), keybindings);
+
+        // A file can bind one key twice; that is reported rather than refused
+        let clashing = Keybindings::from_json(r#"{"Inspect": ["Tab"]}"#).unwrap();
+        assert_eq!(clashing.conflicts().len(), 1);
+        assert!(Keybindings::from_json(r#"{"Inspect": ["NoSuchKey"]}"#).is_err());
+        assert!(Keybindings::from_json(r#"{"Dance": ["KeyE"]}"#).is_err());
+    }
+}
@@ -3,9 +3,13 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::Path;
 use crate::resources::game_grid::{GameGrid, TileKind, EntityType, Tile};
+use crate::resources::item_registry::ItemRegistry;
+use crate::resources::spawn_points::SpawnPoint;
+use crate::resources::zone_links::ZoneLink;
+use crate::sim::movement::MovementMode;
 use crate::components::item::{Item, GroundItems};
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct MapData {
     pub width: usize,
     pub height: usize,
@@ -13,6 +17,15 @@ pub struct MapData {
     pub entities: Vec<PlacedEntity>,
     #[serde(default)] // Backwards compatible - defaults to empty vec if missing
     pub items: Vec<PlacedGroundItems>,
+    /// Items and anomalies rolled from the run seed when a run starts
+    #[serde(default)]
+    pub spawn_points: Vec<PlacedSpawnPoint>,
+    /// Exits that lead on to another map
+    #[serde(default)]
+    pub zone_links: Vec<PlacedZoneLink>,
+    /// Movement mode runs on this map use; None leaves it to the settings
+    #[serde(default)]
+    pub movement: Option<MovementMode>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -22,6 +35,20 @@ pub struct PlacedGroundItems {
     pub items: Vec<Item>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PlacedSpawnPoint {
+    pub x: usize,
+    pub y: usize,
+    pub spawn: SpawnPoint,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PlacedZoneLink {
+    pub x: usize,
+    pub y: usize,
+    pub link: ZoneLink,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Copy)]
 pub enum SerializableTileKind {
     Floor,
@@ -46,7 +73,7 @@ impl From<SerializableTileKind> for TileKind {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SerializableEntityType {
     GravitationalAnomaly,
     PhilosopherStone,
@@ -90,7 +117,7 @@ pub struct PlacedEntity {
 }
 
 impl MapData {
-    // Create MapData from current game state
+    // Create MapData from current game state (without spawn points or zone links)
     pub fn from_game_state(
         grid: &GameGrid,
         entities: &[(EntityType, usize, usize)],
@@ -133,6 +160,9 @@ impl MapData {
             terrain,
             entities,
             items,
+            spawn_points: Vec::new(), // Only the editor has spawn points; it adds them itself
+            zone_links: Vec::new(),
+            movement: None,
         }
     }
 
@@ -152,37 +182,117 @@ impl MapData {
         Ok(())
     }
 
-    // Load from JSON file
-    pub fn load_from_file(path: &str) -> Result<Self, String> {
+    // Load from JSON file, giving items from maps saved before item ids their catalogue ids
+    pub fn load_from_file(path: &str, items: &ItemRegistry) -> Result<Self, String> {
         let content = fs::read_to_string(path)
             .map_err(|e| format!("Failed to read file: {}", e))?;
 
-        let map_data: MapData = serde_json::from_str(&content)
+        let mut map_data: MapData = serde_json::from_str(&content)
             .map_err(|e| format!("Failed to deserialize: {}", e))?;
+        map_data.resolve_item_ids(items)?;
 
         Ok(map_data)
     }
 
+    // Fill in the ids of ground items saved before items had ids
+    pub fn resolve_item_ids(&mut self, items: &ItemRegistry) -> Result<(), String> {
+        items.resolve_ids(self.items.iter_mut().flat_map(|pile| pile.items.iter_mut()))
+    }
+
     // Convert MapData to GameGrid
     pub fn to_game_grid(&self) -> GameGrid {
-        let mut tiles = Vec::with_capacity(self.height);
-        for y in 0..self.height {
-            let mut row = Vec::with_capacity(self.width);
-            for x in 0..self.width {
-                let tile_kind = if y < self.terrain.len() && x < self.terrain[y].len() {
-                    self.terrain[y][x].into()
-                } else {
-                    TileKind::Floor
-                };
-                row.push(Tile::new(tile_kind));
+        let mut grid = GameGrid::new_empty(self.width, self.height);
+        for (y, row) in self.terrain.iter().enumerate().take(self.height) {
+            for (x, kind) in row.iter().enumerate().take(self.width) {
+                grid.set_tile(x, y, Tile::new((*kind).into()));
             }
-            tiles.push(row);
         }
+        grid
+    }
+}
 
-        GameGrid {
-            tiles,
-            width: self.width,
-            height: self.height,
-        }
+/// Path of the map called `name` in `directory`
+/// Names are kept to letters, digits, '-' and '_' so they are safe as file names
+pub fn map_path(directory: &str, name: &str) -> Result<String, String> {
+    if name.is_empty() {
+        return Err("Map name is empty".to_string());
+    }
+    if let Some(c) = name.chars().find(|c| !c.is_ascii_alphanumeric() && *c != '-' && *c != '_') {
+        return Err(format!("Map names can't contain '{}'", c));
+    }
+    Ok(format!("{}/{}.json", directory, name))
+}
+
+/// Name of a map file, e.g. "current" for "assets/maps/current.json"
+pub fn map_name(path: &str) -> String {
+    Path::new(path)
+        .file_stem()
+        .map_or_else(|| path.to_string(), |stem| stem.to_string_lossy().into_owned())
+}
+
+/// Directory the map at `path` is in, where the maps its zone links name are looked up
+pub fn map_directory(path: &str) -> String {
+    match Path::new(path).parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_string_lossy().into_owned(),
+        _ => ".".to_string(),
+    }
+}
+
+/// Paths of every map (.json file) in `directory`, sorted by name
+pub fn list_maps(directory: &str) -> Result<Vec<String>, String> {
+    let entries = fs::read_dir(directory)
+        .map_err(|e| format!("Failed to read directory: {}", e))?;
+
+    let mut paths: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "json"))
+        .map(|path| format!("{}/{}", directory, path.file_name().unwrap_or_default().to_string_lossy()))
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_names_and_paths() {
+        assert_eq!(map_path("assets/maps", "cellar_2").unwrap(), "assets/maps/cellar_2.json");
+        assert!(map_path("assets/maps", "").is_err());
+        assert!(map_path("assets/maps", "../items").is_err());
+        assert_eq!(map_name("assets/maps/cellar_2.json"), "cellar_2");
+        assert_eq!(map_directory("other/dir/a.json"), "other/dir");
+        assert_eq!(map_directory("a.json"), ".");
+
+        let maps = list_maps("assets/maps").unwrap();
+        assert!(maps.contains(&"assets/maps/current.json".to_string()));
+    }
+
+    #[test]
+    fn test_items_saved_before_item_ids_get_their_ids() {
+        use crate::resources::item_registry::ITEM_CATALOGUE_PATH;
+        let items = ItemRegistry::load_from_file(ITEM_CATALOGUE_PATH).unwrap();
+
+        // Items were stored as {name, weight, value, is_metal}
+        let old_map = r#"{
+            "width": 1, "height": 1, "terrain": [["Floor"]], "entities": [],
+            "items": [{"x": 0, "y": 0, "items": [
+                {"name": "Scrap", "weight": 10, "value": 5, "is_metal": true},
+                {"name": "Metal Detector", "weight": 50, "value": null, "is_metal": true}
+            ]}]
+        }"#;
+        let path = std::env::temp_dir().join("picnic_old_item_map.json");
+        let path = path.to_string_lossy().into_owned();
+        fs::write(&path, old_map).unwrap();
+
+        let map = MapData::load_from_file(&path, &items).unwrap();
+        let ids: Vec<&str> = map.items[0].items.iter().map(|item| item.id.as_str()).collect();
+        assert_eq!(ids, ["scrap", "metal_detector"]);
+        assert_eq!(map.items[0].items[0].quantity, 1);
+
+        fs::write(&path, old_map.replace("Scrap", "Mystery Box")).unwrap();
+        assert!(MapData::load_from_file(&path, &items).unwrap_err().contains("Mystery Box"));
     }
 }
@@ -0,0 +1,162 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use crate::components::components::Position;
+
+/// What a player-placed marker says about a tile
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MarkerKind {
+    Danger,
+    Safe,
+    BoltLanded,
+    Note(String),
+}
+
+impl MarkerKind {
+    /// Short text for the markers panel
+    pub fn describe(&self) -> String {
+        match self {
+            MarkerKind::Danger => "Danger".to_string(),
+            MarkerKind::Safe => "Safe".to_string(),
+            MarkerKind::BoltLanded => "Bolt landed here".to_string(),
+            MarkerKind::Note(text) => format!("Note: {}", text),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MapMarker {
+    pub position: Position,
+    pub kind: MarkerKind,
+}
+
+/// Markers the player has put on the map this run (at most one per tile)
+#[derive(Resource, Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MapMarkers {
+    pub markers: Vec<MapMarker>,
+    /// Keep these markers for the next run on the same map
+    pub keep_across_runs: bool,
+    /// Zone the markers above are in
+    #[serde(default)]
+    pub zone: String,
+    /// Markers left behind in the run's other zones, by zone
+    #[serde(default)]
+    pub other_zones: HashMap<String, Vec<MapMarker>>,
+}
+
+impl MapMarkers {
+    pub fn at(&self, pos: Position) -> Option<&MapMarker> {
+        self.markers.iter().find(|marker| marker.position == pos)
+    }
+
+    /// Puts a marker on a tile, replacing any marker already there
+    pub fn place(&mut self, pos: Position, kind: MarkerKind) {
+        self.remove(pos);
+        self.markers.push(MapMarker { position: pos, kind });
+    }
+
+    /// Removes the marker on a tile; returns true if there was one
+    pub fn remove(&mut self, pos: Position) -> bool {
+        let before = self.markers.len();
+        self.markers.retain(|marker| marker.position != pos);
+        self.markers.len() != before
+    }
+
+    /// Puts the markers of this zone away and brings out those of `zone`
+    pub fn switch_zone(&mut self, zone: &str) {
+        let left_behind = std::mem::take(&mut self.markers);
+        if !left_behind.is_empty() {
+            self.other_zones.insert(self.zone.clone(), left_behind);
+        }
+        self.markers = self.other_zones.remove(zone).unwrap_or_default();
+        self.zone = zone.to_string();
+    }
+
+    /// File the markers of a map are kept in between runs, keyed by the map's hash
+    pub fn path_for_map(map_hash: u64) -> String {
+        format!("saves/markers/{:016x}.json", map_hash)
+    }
+
+    /// The markers kept from an earlier run on this map, or none
+    pub fn remembered_for_map(map_hash: u64) -> Self {
+        let path = Self::path_for_map(map_hash);
+        if !Path::new(&path).exists() {
+            return Self::default();
+        }
+
+        Self::load_from_file(&path).unwrap_or_else(|e| {
+            warn!("Failed to load kept markers: {}", e);
+            Self::default()
+        })
+    }
+
+    /// Writes the markers out for the next run on this map, or forgets them if they aren't kept
+    pub fn store_for_map(&self, map_hash: u64) -> Result<(), String> {
+        let path = Self::path_for_map(map_hash);
+        if self.keep_across_runs {
+            self.save_to_file(&path)
+        } else if Path::new(&path).exists() {
+            fs::remove_file(&path).map_err(|e| format!("Failed to remove file: {}", e))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn save_to_file(&self, path: &str) -> Result<(), String> {
+        if let Some(parent) = Path::new(path).parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+        }
+
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize: {}", e))?;
+
+        fs::write(path, json)
+            .map_err(|e| format!("Failed to write file: {}", e))
+    }
+
+    pub fn load_from_file(path: &str) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+
+        serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to deserialize: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_one_marker_per_tile() {
+        let mut markers = MapMarkers::default();
+        let pos = Position { x: 4, y: 2 };
+
+        markers.place(pos, MarkerKind::Danger);
+        markers.place(pos, MarkerKind::Note("stone?".to_string()));
+        assert_eq!(markers.markers.len(), 1);
+        assert_eq!(markers.at(pos).unwrap().kind, MarkerKind::Note("stone?".to_string()));
+
+        assert!(markers.remove(pos));
+        assert!(!markers.remove(pos));
+        assert!(markers.at(pos).is_none());
+    }
+
+    #[test]
+    fn test_markers_stay_in_their_zone() {
+        let mut markers = MapMarkers::default();
+        let pos = Position { x: 1, y: 1 };
+        markers.switch_zone("outskirts");
+        markers.place(pos, MarkerKind::Danger);
+
+        markers.switch_zone("factory");
+        assert!(markers.at(pos).is_none());
+        markers.place(pos, MarkerKind::Safe);
+
+        markers.switch_zone("outskirts");
+        assert_eq!(markers.at(pos).unwrap().kind, MarkerKind::Danger);
+        assert_eq!(markers.other_zones["factory"][0].kind, MarkerKind::Safe);
+    }
+}
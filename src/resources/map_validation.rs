@@ -0,0 +1,345 @@
+use bevy::prelude::Resource;
+use pathfinding::prelude::bfs_reach;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use crate::resources::game_grid::{EntityType, GameGrid, TileKind};
+use crate::resources::item_registry::ItemRegistry;
+use crate::components::components::Position;
+use crate::constants::MAPS_DIRECTORY;
+use crate::resources::map_data::{MapData, map_path};
+use crate::resources::spawn_points::SpawnPoint;
+use crate::sim::zone_sim::{check_entry, is_anomaly};
+
+/// How bad a map problem is: errors make the map unplayable, warnings are worth a look
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IssueSeverity {
+    Error,
+    Warning,
+}
+
+/// One problem found by `validate_map`, with the tile it concerns when there is one
+#[derive(Clone, Debug, PartialEq)]
+pub struct MapIssue {
+    pub severity: IssueSeverity,
+    pub position: Option<(usize, usize)>,
+    pub message: String,
+}
+
+impl MapIssue {
+    fn error(position: Option<(usize, usize)>, message: String) -> Self {
+        Self { severity: IssueSeverity::Error, position, message }
+    }
+
+    fn warning(position: Option<(usize, usize)>, message: String) -> Self {
+        Self { severity: IssueSeverity::Warning, position, message }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == IssueSeverity::Error
+    }
+}
+
+impl fmt::Display for MapIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.severity {
+            IssueSeverity::Error => write!(f, "error: {}", self.message),
+            IssueSeverity::Warning => write!(f, "warning: {}", self.message),
+        }
+    }
+}
+
+/// The issues of the map being edited, kept current by the editor and checked before F2 starts a run
+#[derive(Resource, Default)]
+pub struct MapIssues(pub Vec<MapIssue>);
+
+impl MapIssues {
+    pub fn error_count(&self) -> usize {
+        self.0.iter().filter(|issue| issue.is_error()).count()
+    }
+}
+
+/// Lints a map before it is played: errors first, then warnings
+/// Errors are what would leave a run stuck (no usable PlayerStart, broken terrain, things off the
+/// map, spawn points that can't roll, links to impossible map names); warnings are what makes for a bad run
+/// (no reachable Exit, stacked entities, items in walls, links that lead nowhere)
+/// Linked maps are looked up in MAPS_DIRECTORY
+pub fn validate_map(map: &MapData, items: &ItemRegistry) -> Vec<MapIssue> {
+    let mut issues = Vec::new();
+
+    if map.width == 0 || map.height == 0 {
+        issues.push(MapIssue::error(None, format!("Map is {}x{}, it needs at least one tile", map.width, map.height)));
+        return issues;
+    }
+    if map.terrain.len() != map.height {
+        issues.push(MapIssue::error(None, format!("Terrain has {} rows, expected {}", map.terrain.len(), map.height)));
+    }
+    for (y, row) in map.terrain.iter().enumerate().filter(|(_, row)| row.len() != map.width) {
+        issues.push(MapIssue::error(
+            Some((0, y)),
+            format!("Terrain row {} has {} tiles, expected {}", y, row.len(), map.width),
+        ));
+    }
+
+    let grid = map.to_game_grid();
+    let is_wall = |x: usize, y: usize| grid.get_tile(x, y).is_some_and(|tile| tile.kind == TileKind::Wall);
+    let in_bounds = |x: usize, y: usize| x < map.width && y < map.height;
+
+    let mut per_tile: HashMap<(usize, usize), usize> = HashMap::new();
+    for placed in &map.entities {
+        let entity_type: EntityType = placed.entity_type.into();
+        let (x, y) = (placed.x, placed.y);
+        if !in_bounds(x, y) {
+            issues.push(MapIssue::error(None, format!("{:?} at ({}, {}) is outside the map", entity_type, x, y)));
+        } else if is_wall(x, y) {
+            // The player can't start inside a wall; anything else there is just unreachable
+            let message = format!("{:?} at ({}, {}) is inside a wall", entity_type, x, y);
+            issues.push(match entity_type {
+                EntityType::PlayerStart => MapIssue::error(Some((x, y)), message),
+                _ => MapIssue::warning(Some((x, y)), message),
+            });
+        }
+        *per_tile.entry((x, y)).or_default() += 1;
+    }
+    let mut stacked: Vec<_> = per_tile.into_iter().filter(|(_, count)| *count > 1).collect();
+    stacked.sort();
+    for ((x, y), count) in stacked {
+        let position = in_bounds(x, y).then_some((x, y));
+        issues.push(MapIssue::warning(position, format!("{} entities on one tile at ({}, {})", count, x, y)));
+    }
+
+    let positions_of = |wanted: EntityType| -> Vec<(usize, usize)> {
+        map.entities
+            .iter()
+            .filter(|placed| EntityType::from(placed.entity_type) == wanted)
+            .map(|placed| (placed.x, placed.y))
+            .collect()
+    };
+    let starts = positions_of(EntityType::PlayerStart);
+    let exits = positions_of(EntityType::Exit);
+    match starts.len() {
+        0 => issues.push(MapIssue::error(None, "No PlayerStart marker".to_string())),
+        1 => {}
+        n => issues.push(MapIssue::error(Some(starts[1]), format!("{} PlayerStart markers, expected one", n))),
+    }
+    if exits.is_empty() {
+        issues.push(MapIssue::warning(None, "No Exit: the run can only end in death".to_string()));
+    }
+
+    let mut seen_piles = HashSet::new();
+    for placed in &map.items {
+        let (x, y) = (placed.x, placed.y);
+        if !in_bounds(x, y) {
+            issues.push(MapIssue::error(None, format!("Items at ({}, {}) are outside the map", x, y)));
+        } else if is_wall(x, y) {
+            issues.push(MapIssue::warning(Some((x, y)), format!("Items at ({}, {}) are inside a wall", x, y)));
+        }
+        if !seen_piles.insert((x, y)) {
+            issues.push(MapIssue::warning(Some((x, y)), format!("More than one item pile at ({}, {})", x, y)));
+        }
+        for item in placed.items.iter().filter(|item| items.get(item.id.as_str()).is_none()) {
+            issues.push(MapIssue::warning(Some((x, y)), format!("Unknown item '{}' at ({}, {})", item.id, x, y)));
+        }
+    }
+
+    let mut seen_spawns = HashSet::new();
+    for placed in &map.spawn_points {
+        let (x, y) = (placed.x, placed.y);
+        let label = placed.spawn.label();
+        if !in_bounds(x, y) {
+            issues.push(MapIssue::error(None, format!("{} at ({}, {}) is outside the map", label, x, y)));
+            continue;
+        }
+        if is_wall(x, y) {
+            issues.push(MapIssue::warning(Some((x, y)), format!("{} at ({}, {}) is inside a wall", label, x, y)));
+        }
+        if !seen_spawns.insert((x, y)) {
+            issues.push(MapIssue::warning(Some((x, y)), format!("More than one spawn point at ({}, {})", x, y)));
+        }
+        match &placed.spawn {
+            SpawnPoint::Item { loot, empty_chance } => {
+                if !(0.0..=1.0).contains(empty_chance) {
+                    issues.push(MapIssue::error(
+                        Some((x, y)),
+                        format!("Item spawn at ({}, {}) has an empty chance of {}, expected 0-1", x, y, empty_chance),
+                    ));
+                }
+                for entry in loot.iter().filter(|entry| items.get(entry.item.as_str()).is_none()) {
+                    issues.push(MapIssue::warning(
+                        Some((x, y)),
+                        format!("Unknown item '{}' in the item spawn at ({}, {})", entry.item, x, y),
+                    ));
+                }
+            }
+            SpawnPoint::Anomaly { choices } => {
+                if choices.is_empty() {
+                    issues.push(MapIssue::error(Some((x, y)), format!("Anomaly slot at ({}, {}) has nothing to pick from", x, y)));
+                }
+                for choice in choices.iter().map(|choice| EntityType::from(*choice)).filter(|choice| !is_anomaly(*choice)) {
+                    issues.push(MapIssue::error(
+                        Some((x, y)),
+                        format!("Anomaly slot at ({}, {}) can roll {:?}, which isn't an anomaly", x, y, choice),
+                    ));
+                }
+                if map.entities.iter().any(|entity| (entity.x, entity.y) == (x, y)) {
+                    issues.push(MapIssue::warning(
+                        Some((x, y)),
+                        format!("Anomaly slot at ({}, {}) shares its tile with an entity", x, y),
+                    ));
+                }
+            }
+        }
+    }
+
+    let mut seen_links = HashSet::new();
+    for placed in &map.zone_links {
+        let (x, y) = (placed.x, placed.y);
+        let label = placed.link.label();
+        if !in_bounds(x, y) {
+            issues.push(MapIssue::error(None, format!("{} at ({}, {}) is outside the map", label, x, y)));
+            continue;
+        }
+        if !exits.contains(&(x, y)) {
+            issues.push(MapIssue::warning(Some((x, y)), format!("{} at ({}, {}) isn't on an Exit", label, x, y)));
+        }
+        if !seen_links.insert((x, y)) {
+            issues.push(MapIssue::warning(Some((x, y)), format!("More than one zone link at ({}, {})", x, y)));
+        }
+        let path = match map_path(MAPS_DIRECTORY, &placed.link.map) {
+            Ok(path) => path,
+            Err(e) => {
+                issues.push(MapIssue::error(Some((x, y)), format!("{} at ({}, {}): {}", label, x, y, e)));
+                continue;
+            }
+        };
+        // A link that can't be followed extracts the player instead, so these are only warnings
+        let entry = Position { x: placed.link.entry_x as i32, y: placed.link.entry_y as i32 };
+        match MapData::load_from_file(&path, items) {
+            Ok(target) => {
+                if let Err(e) = check_entry(&target, entry) {
+                    issues.push(MapIssue::warning(Some((x, y)), format!("{} at ({}, {}): {}", label, x, y, e)));
+                }
+            }
+            Err(_) => issues.push(MapIssue::warning(
+                Some((x, y)),
+                format!("{} at ({}, {}) leads to a map that isn't in {}", label, x, y, MAPS_DIRECTORY),
+            )),
+        }
+    }
+
+    // The run can only end well if some exit can be walked to
+    let single_start = match starts.as_slice() {
+        [start] if !exits.is_empty() && in_bounds(start.0, start.1) && !is_wall(start.0, start.1) => Some(*start),
+        _ => None,
+    };
+    if let Some(start) = single_start {
+        let reachable = reachable_from(start, &grid);
+        if !exits.iter().any(|exit| reachable.contains(exit)) {
+            issues.push(MapIssue::warning(
+                Some(exits[0]),
+                format!("No Exit can be reached from the PlayerStart at ({}, {})", start.0, start.1),
+            ));
+        }
+    }
+
+    issues.sort_by_key(|issue| !issue.is_error());
+    issues
+}
+
+// Tiles that can be walked to from `start` without crossing walls
+fn reachable_from(start: (usize, usize), grid: &GameGrid) -> HashSet<(usize, usize)> {
+    let open = |x: usize, y: usize| grid.get_tile(x, y).is_some_and(|tile| tile.kind != TileKind::Wall);
+    bfs_reach(start, |&(x, y)| {
+        [
+            (x.wrapping_sub(1), y),
+            (x + 1, y),
+            (x, y.wrapping_sub(1)),
+            (x, y + 1),
+        ]
+        .into_iter()
+        .filter(|&(nx, ny)| open(nx, ny))
+        .collect::<Vec<_>>()
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::item::Item;
+    use crate::resources::item_registry::ITEM_CATALOGUE_PATH;
+    use crate::resources::map_data::{PlacedGroundItems, PlacedSpawnPoint, PlacedZoneLink, SerializableEntityType, SerializableTileKind};
+    use crate::resources::zone_gen::LootEntry;
+    use crate::resources::zone_links::ZoneLink;
+
+    #[test]
+    fn test_validate_map() {
+        let items = ItemRegistry::load_from_file(ITEM_CATALOGUE_PATH).unwrap();
+        let mut map = MapData::load_from_file("assets/maps/current.json", &items).unwrap();
+        assert_eq!(validate_map(&map, &items), Vec::new());
+
+        // Wall off the exit and drop an item nobody has heard of
+        let exit = map
+            .entities
+            .iter()
+            .find(|placed| EntityType::from(placed.entity_type) == EntityType::Exit)
+            .map(|placed| (placed.x, placed.y))
+            .unwrap();
+        for (x, y) in [(exit.0.wrapping_sub(1), exit.1), (exit.0 + 1, exit.1), (exit.0, exit.1.wrapping_sub(1)), (exit.0, exit.1 + 1)] {
+            if let Some(tile) = map.terrain.get_mut(y).and_then(|row| row.get_mut(x)) {
+                *tile = SerializableTileKind::Wall;
+            }
+        }
+        map.items.push(PlacedGroundItems {
+            x: 0,
+            y: 0,
+            items: vec![Item::new("moon_rock", "Moon Rock", 1, None, false)],
+        });
+
+        let issues = validate_map(&map, &items);
+        let unreachable = issues.iter().find(|issue| issue.message.starts_with("No Exit can be reached")).unwrap();
+        assert_eq!(unreachable.position, Some(exit));
+        assert!(!unreachable.is_error());
+        assert!(issues.iter().any(|issue| issue.message.contains("moon_rock")), "{:?}", issues);
+        assert!(!issues.iter().any(MapIssue::is_error), "{:?}", issues);
+
+        // A spawn point that can roll a PlayerStart is broken, a loot table with unknown items just odd
+        map.spawn_points.push(PlacedSpawnPoint {
+            x: 1,
+            y: 1,
+            spawn: SpawnPoint::Anomaly { choices: vec![SerializableEntityType::PlayerStart] },
+        });
+        map.spawn_points.push(PlacedSpawnPoint {
+            x: 2,
+            y: 1,
+            spawn: SpawnPoint::Item {
+                loot: vec![LootEntry { item: "moon_rock".to_string(), weight: 1, max_quantity: 1 }],
+                empty_chance: 0.5,
+            },
+        });
+        let issues = validate_map(&map, &items);
+        assert_eq!(issues.iter().filter(|issue| issue.is_error()).count(), 1, "{:?}", issues);
+        assert!(issues[0].message.contains("isn't an anomaly"), "{:?}", issues);
+        assert!(issues.iter().any(|issue| issue.message.starts_with("Unknown item 'moon_rock' in the item spawn")));
+        map.spawn_points.clear();
+
+        // Links to a bad map name are broken; links off an Exit or to a missing map just lead nowhere
+        let link = |x: usize, y: usize, map: &str| PlacedZoneLink {
+            x,
+            y,
+            link: ZoneLink { map: map.to_string(), entry_x: 1, entry_y: 1 },
+        };
+        map.zone_links = vec![link(exit.0, exit.1, "../items"), link(0, 0, "no_such_map")];
+        let issues = validate_map(&map, &items);
+        assert_eq!(issues.iter().filter(|issue| issue.is_error()).count(), 1, "{:?}", issues);
+        assert!(issues[0].message.contains("can't contain '.'"), "{:?}", issues);
+        assert!(issues.iter().any(|issue| issue.message.ends_with("(0, 0) isn't on an Exit")), "{:?}", issues);
+        assert!(issues.iter().any(|issue| issue.message.contains("leads to a map that isn't in")), "{:?}", issues);
+        map.zone_links.clear();
+
+        // Without a PlayerStart the map can't be played at all
+        map.entities.retain(|placed| EntityType::from(placed.entity_type) != EntityType::PlayerStart);
+        let issues = validate_map(&map, &items);
+        assert!(issues[0].is_error());
+        assert_eq!(issues[0].message, "No PlayerStart marker");
+    }
+}
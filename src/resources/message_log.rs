@@ -1,11 +1,14 @@
 use bevy::prelude::*;
 use std::collections::VecDeque;
+use crate::sim::action::GameEvent;
 
 /// Stores recent game messages for display in the HUD
 #[derive(Resource)]
 pub struct MessageLog {
     messages: VecDeque<String>,
     max_messages: usize,
+    /// Messages added since the log was last cleared, including ones scrolled out
+    total: usize,
 }
 
 impl Default for MessageLog {
@@ -13,6 +16,7 @@ impl Default for MessageLog {
         Self {
             messages: VecDeque::new(),
             max_messages: 5,
+            total: 0,
         }
     }
 }
@@ -25,6 +29,16 @@ impl MessageLog {
             self.messages.pop_front();
         }
         self.messages.push_back(message.into());
+        self.total += 1;
+    }
+
+    /// Add the player-facing text of every simulation event, in order
+    pub fn log_events(&mut self, events: &[GameEvent]) {
+        for event in events {
+            if let GameEvent::Message(message) = event {
+                self.add_message(message.clone());
+            }
+        }
     }
 
     /// Get an iterator over all messages (oldest to newest)
@@ -35,6 +49,12 @@ impl MessageLog {
     /// Clear all messages
     pub fn clear(&mut self) {
         self.messages.clear();
+        self.total = 0;
+    }
+
+    /// Number of messages added since the log was last cleared, to tell when a new one comes in
+    pub fn total(&self) -> usize {
+        self.total
     }
 
     /// Get the number of messages currently stored
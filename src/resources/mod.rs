@@ -5,6 +5,18 @@ pub mod seed;
 pub mod camera;
 pub mod editor_state;
 pub mod map_data;
+pub mod map_validation;
+pub mod zone_gen;
+pub mod spawn_points;
+pub mod zone_links;
 pub mod turn_state;
 pub mod message_log;
-pub mod contract_system;
\ No newline at end of file
+pub mod contract_system;
+pub mod run_save;
+pub mod debug;
+pub mod map_markers;
+pub mod item_registry;
+pub mod edit_history;
+pub mod editor_tools;
+pub mod keybindings;
+pub mod action_input;
@@ -0,0 +1,233 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use crate::components::{
+    components::Position,
+    inventory::{CarryCapacity, Inventory},
+    item::Item,
+};
+use crate::resources::{
+    contract_system::{Contract, ContractSystem},
+    item_registry::ItemRegistry,
+    map_data::MapData,
+    map_markers::MapMarkers,
+    message_log::MessageLog,
+    seed::RunRng,
+};
+use crate::constants::MAPS_DIRECTORY;
+use crate::sim::{
+    fog::KnownMap,
+    knowledge::KnownAnomalies,
+    movement::{MovementMode, MovementSetting},
+    replay::ActionRecord,
+    zone_sim::{VisitedZone, ZoneSim},
+};
+
+/// An in-progress Zone run, written separately from the map file so it can be resumed later
+/// The embedded map is the zone the player is in, holding the ground items as they are now, after
+/// any anomaly changes; the other zones of the run are kept as the player left them
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RunSave {
+    pub map: MapData,
+    pub player_x: i32,
+    pub player_y: i32,
+    pub inventory: Vec<Item>,
+    /// Turns left inside a gravitational anomaly, if the player is caught in one
+    pub gravity_timer: Option<u32>,
+    pub turn: u32,
+    pub messages: Vec<String>,
+    pub contracts: Vec<Contract>,
+    pub rng: RunRng,
+    /// Hash of the map the run started on, so the replay of a resumed run still matches it
+    pub map_hash: u64,
+    pub actions: Vec<ActionRecord>,
+    /// Explored tiles
+    pub known: KnownMap,
+    pub known_anomalies: KnownAnomalies,
+    pub markers: MapMarkers,
+    /// Name of the map the player is in
+    #[serde(default)]
+    pub zone: String,
+    #[serde(default)]
+    pub visited: HashMap<String, VisitedZone>,
+    /// Movement mode for zones that don't choose their own
+    #[serde(default)]
+    pub movement_setting: MovementMode,
+    /// Directory the run's zone links are looked up in
+    #[serde(default = "default_maps_directory")]
+    pub maps_directory: String,
+}
+
+fn default_maps_directory() -> String {
+    MAPS_DIRECTORY.to_string()
+}
+
+impl RunSave {
+    /// Captures the run's simulation along with the HUD state that lives outside it
+    pub fn capture(
+        sim: &ZoneSim,
+        message_log: &MessageLog,
+        contract_system: &ContractSystem,
+        markers: &MapMarkers,
+    ) -> Self {
+        Self {
+            map: sim.zone_map(),
+            player_x: sim.player.position.x,
+            player_y: sim.player.position.y,
+            inventory: sim.player.inventory.items.clone(),
+            gravity_timer: sim.player.gravity_timer,
+            turn: sim.turn,
+            messages: message_log.get_messages().cloned().collect(),
+            contracts: contract_system.active_contracts.clone(),
+            rng: sim.rng.clone(),
+            map_hash: sim.map_hash,
+            actions: sim.actions.clone(),
+            known: sim.known.clone(),
+            known_anomalies: sim.known_anomalies.clone(),
+            markers: markers.clone(),
+            zone: sim.zone.clone(),
+            visited: sim.visited.clone(),
+            movement_setting: sim.movement_setting.0,
+            maps_directory: sim.maps_directory.clone(),
+        }
+    }
+
+    /// Rebuilds the simulation exactly as it was when saved
+    pub fn to_sim(&self, capacity: CarryCapacity, items: ItemRegistry) -> Result<ZoneSim, String> {
+        let mut sim = ZoneSim::from_map_data(&self.map, items, self.rng.seed())?;
+        sim.capacity = capacity;
+        sim.player.position = Position { x: self.player_x, y: self.player_y };
+        sim.player.inventory = Inventory { items: self.inventory.clone() };
+        sim.player.gravity_timer = self.gravity_timer;
+        sim.turn = self.turn;
+        sim.rng = self.rng.clone();
+        sim.map_hash = self.map_hash;
+        sim.actions = self.actions.clone();
+        sim.known = self.known.clone();
+        sim.known_anomalies = self.known_anomalies.clone();
+        sim.zone = self.zone.clone();
+        sim.visited = self.visited.clone();
+        sim.maps_directory = self.maps_directory.clone();
+        sim.set_movement_setting(MovementSetting(self.movement_setting));
+        Ok(sim)
+    }
+
+    /// Puts the saved messages, contract progress and map markers back
+    pub fn restore_hud(
+        &self,
+        message_log: &mut MessageLog,
+        contract_system: &mut ContractSystem,
+        markers: &mut MapMarkers,
+    ) {
+        message_log.clear();
+        for message in &self.messages {
+            message_log.add_message(message.clone());
+        }
+        contract_system.active_contracts = self.contracts.clone();
+        *markers = self.markers.clone();
+    }
+
+    pub fn save_to_file(&self, path: &str) -> Result<(), String> {
+        if let Some(parent) = Path::new(path).parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+        }
+
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize: {}", e))?;
+
+        fs::write(path, json)
+            .map_err(|e| format!("Failed to write file: {}", e))
+    }
+
+    /// Loads a save, giving items from saves written before item ids their catalogue ids
+    pub fn load_from_file(path: &str, items: &ItemRegistry) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+
+        let mut save: RunSave = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to deserialize: {}", e))?;
+        save.map.resolve_item_ids(items)?;
+        items.resolve_ids(save.inventory.iter_mut())?;
+        for zone in save.visited.values_mut() {
+            zone.map.resolve_item_ids(items)?;
+        }
+        Ok(save)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::item::GroundItems;
+    use crate::resources::game_grid::{EntityType, GameGrid};
+    use crate::resources::item_registry::ITEM_CATALOGUE_PATH;
+    use crate::sim::action::Action;
+
+    fn pos(x: i32, y: i32) -> Position {
+        Position { x, y }
+    }
+
+    fn items() -> ItemRegistry {
+        ItemRegistry::load_from_file(ITEM_CATALOGUE_PATH).unwrap()
+    }
+
+    #[test]
+    fn test_resumed_run_continues_identically() {
+        let entities = vec![
+            (pos(1, 1), EntityType::PlayerStart),
+            (pos(2, 1), EntityType::PhilosopherStone),
+            (pos(4, 1), EntityType::GravitationalAnomaly),
+        ];
+        let mut pile = GroundItems::new();
+        pile.add_item(items().create("scrap"), &items());
+        pile.add_item(items().create("glass_jar"), &items());
+        let mut sim = ZoneSim::new(
+            GameGrid::new_empty(6, 3),
+            entities,
+            vec![(pos(2, 1), pile)],
+            CarryCapacity::default(),
+            items(),
+            11,
+        )
+        .unwrap();
+
+        // Stand on the stone for a few turns, then get caught by the anomaly
+        sim.apply(Action::Move { dx: 1, dy: 0 });
+        sim.apply(Action::CloseUi);
+        sim.apply(Action::Move { dx: 1, dy: 0 });
+        assert!(sim.player.gravity_timer.is_some());
+
+        let mut message_log = MessageLog::default();
+        message_log.add_message("Saved mid-run");
+        let mut contract_system = ContractSystem::from_registry(&items());
+        contract_system.active_contracts[0].completed = true;
+        let mut markers = MapMarkers::default();
+        markers.place(pos(4, 1), crate::resources::map_markers::MarkerKind::Danger);
+
+        let json = serde_json::to_string(&RunSave::capture(&sim, &message_log, &contract_system, &markers)).unwrap();
+        let save: RunSave = serde_json::from_str(&json).unwrap();
+        let mut resumed = save.to_sim(CarryCapacity::default(), items()).unwrap();
+
+        let mut restored_log = MessageLog::default();
+        let mut restored_contracts = ContractSystem::from_registry(&items());
+        let mut restored_markers = MapMarkers::default();
+        save.restore_hud(&mut restored_log, &mut restored_contracts, &mut restored_markers);
+        assert_eq!(restored_log.get_messages().next().map(String::as_str), Some("Saved mid-run"));
+        assert!(restored_contracts.active_contracts[0].completed);
+        assert_eq!(restored_markers, markers);
+
+        assert_eq!(resumed.player.position, sim.player.position);
+        assert_eq!(resumed.player.gravity_timer, sim.player.gravity_timer);
+        assert_eq!(resumed.ground_items, sim.ground_items);
+        assert_eq!(resumed.known, sim.known);
+        assert_eq!(resumed.known_anomalies, sim.known_anomalies);
+
+        // Both copies play out the rest of the run the same way
+        for _ in 0..3 {
+            sim.apply(Action::Move { dx: -1, dy: 0 });
+            resumed.apply(Action::Move { dx: -1, dy: 0 });
+        }
+        assert_eq!(resumed.to_replay(), sim.to_replay());
+    }
+}
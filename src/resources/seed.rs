@@ -1,4 +1,62 @@
 use bevy::prelude::Resource;
-    
-#[derive(Resource, Default)]
-pub struct WorldSeed(pub u32);
\ No newline at end of file
+use rand::{rand_core, RngCore, SeedableRng};
+use rand_pcg::Pcg32;
+use serde::{Deserialize, Serialize};
+
+/// Seed of the current run; every random roll in the run derives from it
+#[derive(Resource, Default, Clone, Copy, Debug)]
+pub struct WorldSeed(pub u64);
+
+/// Seed requested on the command line (`--seed <n>`)
+/// When set, every run uses it instead of rolling a fresh seed
+#[derive(Resource, Default, Clone, Copy, Debug)]
+pub struct SeedOverride(pub Option<u64>);
+
+/// Deterministic random source for one run
+/// Anomaly effects roll through this so the same seed and inputs replay turn for turn
+/// Every value drawn is kept until taken, so replays can record the outcome of each roll
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RunRng {
+    seed: u64,
+    rng: Pcg32,
+    rolls: Vec<u64>,
+}
+
+impl RunRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: Pcg32::seed_from_u64(seed),
+            rolls: Vec::new(),
+        }
+    }
+
+    /// The seed this generator was created from
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Takes the raw values drawn since the last call
+    pub fn take_rolls(&mut self) -> Vec<u64> {
+        std::mem::take(&mut self.rolls)
+    }
+}
+
+impl RngCore for RunRng {
+    fn next_u32(&mut self) -> u32 {
+        let value = self.rng.next_u32();
+        self.rolls.push(value as u64);
+        value
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let value = self.rng.next_u64();
+        self.rolls.push(value);
+        value
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        rand_core::impls::fill_bytes_via_next(self, dst)
+    }
+}
+
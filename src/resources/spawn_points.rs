@@ -0,0 +1,144 @@
+use bevy::prelude::Component;
+use rand::{SeedableRng, Rng, seq::IndexedRandom};
+use rand_pcg::Pcg32;
+use serde::{Deserialize, Serialize};
+use crate::components::{components::Position, item::GroundItems};
+use crate::resources::game_grid::EntityType;
+use crate::resources::item_registry::ItemRegistry;
+use crate::resources::map_data::{PlacedSpawnPoint, SerializableEntityType};
+use crate::resources::zone_gen::{LootEntry, roll_loot};
+
+// Spawn points roll from their own generator so the run's RNG, and the replays recorded from it,
+// don't depend on how many spawn points the map has
+const SPAWN_SEED_SALT: u64 = 0x5EED_5107_0000_0001;
+
+/// A spot on the map whose contents are rolled from the run seed each time a run starts
+#[derive(Component, Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum SpawnPoint {
+    /// One roll of the loot table, or nothing at all with `empty_chance` (0-1)
+    Item { loot: Vec<LootEntry>, empty_chance: f32 },
+    /// One of these entity types, each as likely as the others
+    Anomaly { choices: Vec<SerializableEntityType> },
+}
+
+impl SpawnPoint {
+    /// Short description for the editor and the map issues panel
+    pub fn label(&self) -> String {
+        match self {
+            SpawnPoint::Item { loot, empty_chance } => {
+                let names: Vec<&str> = loot.iter().map(|entry| entry.item.as_str()).collect();
+                format!("Item spawn [{}] ({:.0}% empty)", names.join(", "), empty_chance * 100.0)
+            }
+            SpawnPoint::Anomaly { choices } => {
+                let names: Vec<String> = choices.iter().map(|choice| format!("{:?}", EntityType::from(*choice))).collect();
+                format!("Anomaly slot [{}]", names.join(" / "))
+            }
+        }
+    }
+}
+
+/// Spawn points the editor offers in Spawn mode (keys 1-N); maps can hold any table in their JSON
+pub fn spawn_presets() -> Vec<(&'static str, SpawnPoint)> {
+    let loot = |item: &str, weight, max_quantity| LootEntry { item: item.to_string(), weight, max_quantity };
+    vec![
+        ("Common loot", SpawnPoint::Item {
+            loot: vec![loot("scrap", 6, 3), loot("bolt", 4, 5), loot("glass_jar", 3, 2), loot("battery", 2, 1)],
+            empty_chance: 0.3,
+        }),
+        ("Rare loot", SpawnPoint::Item {
+            loot: vec![loot("battery", 3, 2), loot("glass_jar", 2, 1), loot("fully_empty", 1, 1)],
+            empty_chance: 0.6,
+        }),
+        ("Any anomaly", SpawnPoint::Anomaly {
+            choices: vec![
+                SerializableEntityType::GravitationalAnomaly,
+                SerializableEntityType::PhilosopherStone,
+                SerializableEntityType::RustAnomaly,
+            ],
+        }),
+        ("Gravitational or Rust", SpawnPoint::Anomaly {
+            choices: vec![SerializableEntityType::GravitationalAnomaly, SerializableEntityType::RustAnomaly],
+        }),
+        ("Rust or Philosopher's Stone", SpawnPoint::Anomaly {
+            choices: vec![SerializableEntityType::RustAnomaly, SerializableEntityType::PhilosopherStone],
+        }),
+    ]
+}
+
+/// What the map's spawn points turn into for a run on `seed`: the same seed always rolls the same layout
+/// Points are rolled in map order (top to bottom, left to right), whatever order they come in
+pub fn resolve_spawn_points(
+    spawn_points: &[PlacedSpawnPoint],
+    items: &ItemRegistry,
+    seed: u64,
+) -> (Vec<(Position, EntityType)>, Vec<(Position, GroundItems)>) {
+    let mut ordered: Vec<&PlacedSpawnPoint> = spawn_points.iter().collect();
+    ordered.sort_by_key(|placed| (placed.y, placed.x));
+
+    let mut rng = Pcg32::seed_from_u64(seed ^ SPAWN_SEED_SALT);
+    let mut entities = Vec::new();
+    let mut ground_items = Vec::new();
+    for placed in ordered {
+        let position = Position { x: placed.x as i32, y: placed.y as i32 };
+        match &placed.spawn {
+            SpawnPoint::Item { loot, empty_chance } => {
+                // Always roll both, so one point's table doesn't shift the rolls of the next
+                let empty = rng.random::<f32>() < *empty_chance;
+                let mut pile = GroundItems::new();
+                roll_loot(loot, items, &mut rng, &mut pile);
+                if !empty && !pile.is_empty() {
+                    ground_items.push((position, pile));
+                }
+            }
+            SpawnPoint::Anomaly { choices } => {
+                if let Some(choice) = choices.choose(&mut rng) {
+                    entities.push((position, (*choice).into()));
+                }
+            }
+        }
+    }
+    (entities, ground_items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::item_registry::ITEM_CATALOGUE_PATH;
+
+    fn spawn_at(x: usize, y: usize, spawn: SpawnPoint) -> PlacedSpawnPoint {
+        PlacedSpawnPoint { x, y, spawn }
+    }
+
+    #[test]
+    fn test_spawn_points_roll_from_the_seed() {
+        let items = ItemRegistry::load_from_file(ITEM_CATALOGUE_PATH).unwrap();
+        let presets = spawn_presets();
+        let points: Vec<PlacedSpawnPoint> = (0..20)
+            .map(|i| spawn_at(i % 5, i / 5, presets[i % presets.len()].1.clone()))
+            .collect();
+
+        let first = resolve_spawn_points(&points, &items, 42);
+        let mut reversed = points.clone();
+        reversed.reverse();
+        assert_eq!(resolve_spawn_points(&reversed, &items, 42), first);
+
+        // Over a few seeds, every anomaly slot fills and the layouts differ
+        let layouts: Vec<_> = (0..5).map(|seed| resolve_spawn_points(&points, &items, seed)).collect();
+        for (entities, _) in &layouts {
+            assert_eq!(entities.len(), 12);
+        }
+        assert!(layouts.iter().any(|layout| *layout != layouts[0]));
+    }
+
+    #[test]
+    fn test_item_spawn_can_come_up_empty() {
+        let items = ItemRegistry::load_from_file(ITEM_CATALOGUE_PATH).unwrap();
+        let loot = vec![LootEntry { item: "scrap".to_string(), weight: 1, max_quantity: 1 }];
+        let always_empty = [spawn_at(1, 1, SpawnPoint::Item { loot: loot.clone(), empty_chance: 1.0 })];
+        let never_empty = [spawn_at(1, 1, SpawnPoint::Item { loot, empty_chance: 0.0 })];
+
+        assert!(resolve_spawn_points(&always_empty, &items, 3).1.is_empty());
+        let (_, piles) = resolve_spawn_points(&never_empty, &items, 3);
+        assert_eq!(piles[0].1.items[0].id, "scrap");
+    }
+}
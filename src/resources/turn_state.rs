@@ -8,7 +8,8 @@ pub enum TurnPhase {
     WorldUpdate,     // Processing world effects in sequence
     InspectingItems, // Player is inspecting items on current tile (paused)
     ViewingInventory, // Player is viewing/managing their inventory (paused)
-    ThrowingBolt,    // Player is preparing to throw a bolt (paused, waiting for direction)
+    Throwing,        // Player is aiming a thrown item (paused, waiting for a direction or target tile)
+    PlacingMarkers,  // Player is putting markers and notes on the map (paused)
     EnteringZone,    // Showing contract briefing screen (paused)
     ExitingZone,     // Showing extraction/contract completion screen (paused)
     PlayerDead,      // Showing death screen (paused)
@@ -0,0 +1,443 @@
+use noise::{Fbm, NoiseFn, Perlin};
+use pathfinding::prelude::{bfs_reach, dijkstra_all};
+use rand::{Rng, SeedableRng, seq::{IndexedRandom, SliceRandom}};
+use rand_pcg::Pcg32;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use crate::components::item::GroundItems;
+use crate::constants::{GRID_WIDTH, GRID_HEIGHT, WATER_LEVEL, SCALE, MAX_REACHABILITY_CHECKS, MIN_MAP_SIZE, MAX_MAP_SIZE};
+use crate::resources::game_grid::EntityType;
+use crate::resources::item_registry::ItemRegistry;
+use crate::resources::map_data::{MapData, PlacedEntity, PlacedGroundItems, SerializableTileKind};
+
+/// One line of the loot table: how often an item turns up in a pile, and how many at most
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LootEntry {
+    pub item: String,
+    pub weight: u32,
+    pub max_quantity: u32,
+}
+
+/// Everything the zone generator can be asked for; missing fields in a params file take the defaults
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct ZoneGenParams {
+    pub width: usize,
+    pub height: usize,
+    /// Share of the inner tiles (0-0.9) that become walls, before cut-off pockets are filled in
+    pub wall_density: f32,
+    pub exits: usize,
+    pub gravitational_anomalies: usize,
+    pub philosopher_stones: usize,
+    pub rust_anomalies: usize,
+    pub item_piles: usize,
+    pub loot: Vec<LootEntry>,
+}
+
+impl Default for ZoneGenParams {
+    fn default() -> Self {
+        let loot = |item: &str, weight, max_quantity| LootEntry { item: item.to_string(), weight, max_quantity };
+        Self {
+            width: GRID_WIDTH,
+            height: GRID_HEIGHT,
+            wall_density: WATER_LEVEL,
+            exits: 1,
+            gravitational_anomalies: 2,
+            philosopher_stones: 1,
+            rust_anomalies: 2,
+            item_piles: 6,
+            loot: vec![
+                loot("scrap", 6, 3),
+                loot("bolt", 4, 5),
+                loot("glass_jar", 4, 2),
+                loot("battery", 3, 2),
+                loot("rust_slag", 1, 1),
+                loot("fully_empty", 1, 1),
+            ],
+        }
+    }
+}
+
+impl ZoneGenParams {
+    pub fn load_from_file(path: &str) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+
+        serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to deserialize: {}", e))
+    }
+
+    fn check(&self, items: &ItemRegistry) -> Result<(), String> {
+        for size in [self.width, self.height] {
+            if !(MIN_MAP_SIZE..=MAX_MAP_SIZE).contains(&size) {
+                return Err(format!("Zone size must be {}-{} tiles, got {}x{}", MIN_MAP_SIZE, MAX_MAP_SIZE, self.width, self.height));
+            }
+        }
+        if !(0.0..=0.9).contains(&self.wall_density) {
+            return Err(format!("Wall density must be between 0 and 0.9, got {}", self.wall_density));
+        }
+        if self.exits == 0 {
+            return Err("A zone needs at least one exit".to_string());
+        }
+        if let Some(entry) = self.loot.iter().find(|entry| items.get(&entry.item).is_none()) {
+            return Err(format!("Loot table names unknown item '{}'", entry.item));
+        }
+        if self.item_piles > 0 && self.loot.iter().all(|entry| entry.weight == 0 || entry.max_quantity == 0) {
+            return Err("Item piles need a loot table with at least one weighted entry".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Adds one roll of a loot table to `pile`: an entry picked by weight, 1 to `max_quantity` of it
+/// Entries for items missing from the catalogue never come up; an empty table adds nothing
+pub fn roll_loot(loot: &[LootEntry], items: &ItemRegistry, rng: &mut impl Rng, pile: &mut GroundItems) {
+    let weight = |entry: &LootEntry| {
+        if entry.max_quantity == 0 || items.get(&entry.item).is_none() { 0 } else { entry.weight }
+    };
+    let Ok(entry) = loot.choose_weighted(rng, weight) else {
+        return;
+    };
+    for _ in 0..rng.random_range(1..=entry.max_quantity) {
+        pile.add_item(items.create(&entry.item), items);
+    }
+}
+
+type Tile = (usize, usize);
+
+fn neighbours((x, y): Tile) -> [Tile; 4] {
+    [(x.wrapping_sub(1), y), (x + 1, y), (x, y.wrapping_sub(1)), (x, y + 1)]
+}
+
+/// Generates a zone from a seed: noise terrain, a PlayerStart, exits far from it, anomalies on
+/// narrow ground and piles of loot. The same seed and params always give the same map
+/// Every floor tile is reachable from the PlayerStart, and every exit can be reached without
+/// stepping on an anomaly or next to a gravitational one
+pub fn generate_zone(seed: u64, params: &ZoneGenParams, items: &ItemRegistry) -> Result<MapData, String> {
+    params.check(items)?;
+    let (width, height) = (params.width, params.height);
+    let mut rng = Pcg32::seed_from_u64(seed);
+
+    // Terrain: the lowest-lying share of the noise field becomes wall, and so does the border
+    let noise = Fbm::<Perlin>::new((seed ^ (seed >> 32)) as u32);
+    let mut inner: Vec<(f64, Tile)> = (1..height - 1)
+        .flat_map(|y| (1..width - 1).map(move |x| (x, y)))
+        .map(|(x, y)| (noise.get([x as f64 * SCALE, y as f64 * SCALE]), (x, y)))
+        .collect();
+    inner.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let wall_count = (inner.len() as f32 * params.wall_density) as usize;
+    let mut open = vec![vec![false; width]; height];
+    for (_, (x, y)) in inner.iter().skip(wall_count) {
+        open[*y][*x] = true;
+    }
+    let is_open = |open: &Vec<Vec<bool>>, (x, y): Tile| open.get(y).and_then(|row| row.get(x)).copied().unwrap_or(false);
+
+    // Keep the largest cave and wall off the pockets cut off from it
+    let mut region: Vec<Tile> = Vec::new();
+    let mut seen = HashSet::new();
+    for (_, tile) in &inner {
+        if !is_open(&open, *tile) || seen.contains(tile) {
+            continue;
+        }
+        let cave: Vec<Tile> = bfs_reach(*tile, |t| neighbours(*t).into_iter().filter(|n| is_open(&open, *n)).collect::<Vec<_>>()).collect();
+        seen.extend(cave.iter().copied());
+        if cave.len() > region.len() {
+            region = cave;
+        }
+    }
+    let region_tiles: HashSet<Tile> = region.iter().copied().collect();
+    for (_, tile) in &inner {
+        if !region_tiles.contains(tile) {
+            open[tile.1][tile.0] = false;
+        }
+    }
+
+    let placements = 1 + params.exits + params.gravitational_anomalies + params.philosopher_stones + params.rust_anomalies;
+    if region.len() < placements * 4 {
+        return Err(format!(
+            "Zone is too cramped: {} open tiles for {} markers and anomalies; lower the wall density or make it bigger",
+            region.len(),
+            placements
+        ));
+    }
+    region.sort();
+
+    // PlayerStart anywhere, exits among the tiles farthest from it
+    let start = *region.choose(&mut rng).unwrap_or(&region[0]);
+    let distances = dijkstra_all(&start, |t| {
+        neighbours(*t).into_iter().filter(|n| is_open(&open, *n)).map(|n| (n, 1)).collect::<Vec<_>>()
+    });
+    let distance = |tile: &Tile| distances.get(tile).map_or(0, |(_, cost)| *cost);
+    let mut by_distance: Vec<Tile> = region.iter().copied().filter(|tile| *tile != start).collect();
+    by_distance.shuffle(&mut rng);
+    by_distance.sort_by_key(|tile| std::cmp::Reverse(distance(tile)));
+
+    let spread = |a: &Tile, b: &Tile| a.0.abs_diff(b.0) + a.1.abs_diff(b.1);
+    let mut exits: Vec<Tile> = Vec::new();
+    let far = (by_distance.len() / 4).max(params.exits);
+    for tile in by_distance.iter().take(far) {
+        if exits.len() < params.exits && exits.iter().all(|exit| spread(exit, tile) >= 4) {
+            exits.push(*tile);
+        }
+    }
+    for tile in &by_distance {
+        if exits.len() < params.exits && !exits.contains(tile) {
+            exits.push(*tile);
+        }
+    }
+
+    // Anomalies in the narrowest passages first, as long as every exit stays safely reachable
+    // A tile's passage width is the shorter of its open runs across and down
+    let run = |(x, y): Tile, (dx, dy): (i32, i32)| {
+        (1..)
+            .take_while(|step| is_open(&open, ((x as i32 + dx * step) as usize, (y as i32 + dy * step) as usize)))
+            .count()
+    };
+    let passage_width = |tile: Tile| {
+        let across = run(tile, (-1, 0)) + run(tile, (1, 0)) + 1;
+        let down = run(tile, (0, -1)) + run(tile, (0, 1)) + 1;
+        across.min(down)
+    };
+    let mut candidates: Vec<Tile> = region
+        .iter()
+        .copied()
+        .filter(|tile| !exits.contains(tile) && spread(tile, &start) >= 3)
+        .collect();
+    candidates.shuffle(&mut rng);
+    candidates.sort_by_cached_key(|tile| passage_width(*tile));
+
+    let wanted = [
+        (EntityType::GravitationalAnomaly, params.gravitational_anomalies),
+        (EntityType::PhilosopherStone, params.philosopher_stones),
+        (EntityType::RustAnomaly, params.rust_anomalies),
+    ];
+    let mut anomalies: Vec<(Tile, EntityType)> = Vec::new();
+    let mut safe = open.clone();
+    for (entity_type, count) in wanted {
+        for _ in 0..count {
+            // The narrowest candidates are mostly chokepoints, so they're ruled out against the safe ground
+            // as it stands instead of searching the whole zone again for each of them
+            let walkable = |tile: Tile| is_open(&safe, tile);
+            let chokepoints = chokepoints(start, &exits, width, height, walkable);
+            let mut checks = 0;
+            let placed = candidates.iter().position(|tile| {
+                let apart = anomalies.iter().all(|(other, _)| other.0.abs_diff(tile.0) > 1 || other.1.abs_diff(tile.1) > 1);
+                if !apart {
+                    return false;
+                }
+                let blocked = blocked_tiles(*tile, entity_type);
+                if blocked.iter().any(|blocked| chokepoints.contains(blocked) || exits.contains(blocked)) {
+                    return false;
+                }
+                // One tile that no route depends on, or a patch the ground right around it bypasses
+                if blocked.len() == 1 || detour_around(*tile, &blocked, walkable) {
+                    return true;
+                }
+                if checks == MAX_REACHABILITY_CHECKS {
+                    return false;
+                }
+                checks += 1;
+                let mut trial = anomalies.clone();
+                trial.push((*tile, entity_type));
+                exits_safely_reachable(start, &exits, &trial, &open)
+            });
+            let Some(index) = placed else {
+                return Err(format!("No room left for another {:?} that keeps the exits reachable", entity_type));
+            };
+            let tile = candidates.remove(index);
+            for (x, y) in blocked_tiles(tile, entity_type) {
+                safe[y][x] = false;
+            }
+            anomalies.push((tile, entity_type));
+        }
+    }
+
+    // Loot piles on the free floor
+    let taken: HashSet<Tile> = anomalies.iter().map(|(tile, _)| *tile).chain(exits.iter().copied()).chain([start]).collect();
+    let mut free: Vec<Tile> = region.iter().copied().filter(|tile| !taken.contains(tile)).collect();
+    free.shuffle(&mut rng);
+    let mut piles = Vec::new();
+    for (x, y) in free.into_iter().take(params.item_piles) {
+        let mut pile = GroundItems::new();
+        for _ in 0..rng.random_range(1..=2) {
+            roll_loot(&params.loot, items, &mut rng, &mut pile);
+        }
+        piles.push(PlacedGroundItems { x, y, items: pile.items });
+    }
+    piles.sort_by_key(|pile| (pile.y, pile.x));
+
+    let placed = |(x, y): Tile, entity_type: EntityType| PlacedEntity { entity_type: entity_type.into(), x, y };
+    let entities = [placed(start, EntityType::PlayerStart)]
+        .into_iter()
+        .chain(exits.iter().map(|tile| placed(*tile, EntityType::Exit)))
+        .chain(anomalies.iter().map(|(tile, entity_type)| placed(*tile, *entity_type)))
+        .collect();
+
+    Ok(MapData {
+        width,
+        height,
+        spawn_points: Vec::new(),
+        zone_links: Vec::new(),
+        movement: None,
+        terrain: open
+            .iter()
+            .map(|row| row.iter().map(|open| if *open { SerializableTileKind::Floor } else { SerializableTileKind::Wall }).collect())
+            .collect(),
+        entities,
+        items: piles,
+    })
+}
+
+// Whether every exit can be walked to from `start` without touching an anomaly or a tile a
+// gravitational anomaly would pull from, diagonal tiles included so the map is safe in either movement mode
+fn exits_safely_reachable(start: Tile, exits: &[Tile], anomalies: &[(Tile, EntityType)], open: &[Vec<bool>]) -> bool {
+    let unsafe_tiles = unsafe_tiles(anomalies);
+    if unsafe_tiles.contains(&start) {
+        return false;
+    }
+
+    let walkable = |tile: &Tile| open.get(tile.1).and_then(|row| row.get(tile.0)).copied().unwrap_or(false) && !unsafe_tiles.contains(tile);
+    let reached: HashSet<Tile> = bfs_reach(start, |t| neighbours(*t).into_iter().filter(|n| walkable(n)).collect::<Vec<_>>()).collect();
+    exits.iter().all(|exit| reached.contains(exit))
+}
+
+// Tiles an anomaly makes unsafe to walk: its own, and for a gravitational one every tile around it
+fn blocked_tiles((x, y): Tile, entity_type: EntityType) -> Vec<Tile> {
+    if entity_type != EntityType::GravitationalAnomaly {
+        return vec![(x, y)];
+    }
+    (y.saturating_sub(1)..=y + 1)
+        .flat_map(|ny| (x.saturating_sub(1)..=x + 1).map(move |nx| (nx, ny)))
+        .collect()
+}
+
+fn unsafe_tiles(anomalies: &[(Tile, EntityType)]) -> HashSet<Tile> {
+    anomalies.iter().flat_map(|(tile, entity_type)| blocked_tiles(*tile, *entity_type)).collect()
+}
+
+// Tiles every safe route from `start` to at least one exit goes through (cut vertices of the walkable
+// ground, found with an iterative Tarjan search), so an anomaly on any of them cuts an exit off
+fn chokepoints(start: Tile, exits: &[Tile], width: usize, height: usize, walkable: impl Fn(Tile) -> bool) -> HashSet<Tile> {
+    let index = |(x, y): Tile| y * width + x;
+    let mut order = vec![0u32; width * height]; // 0 for tiles not reached yet
+    let mut low = vec![0u32; width * height];
+    let mut leads_to_exit = vec![false; width * height];
+    let mut found = HashSet::new();
+
+    let mut visited = 1;
+    order[index(start)] = visited;
+    low[index(start)] = visited;
+    leads_to_exit[index(start)] = exits.contains(&start);
+    let mut stack: Vec<(Tile, usize)> = vec![(start, 0)];
+    while let Some(top) = stack.last_mut() {
+        let (tile, next) = *top;
+        if let Some(neighbour) = neighbours(tile).get(next).copied() {
+            top.1 += 1;
+            if !walkable(neighbour) {
+                continue;
+            }
+            if order[index(neighbour)] == 0 {
+                visited += 1;
+                order[index(neighbour)] = visited;
+                low[index(neighbour)] = visited;
+                leads_to_exit[index(neighbour)] = exits.contains(&neighbour);
+                stack.push((neighbour, 0));
+            } else {
+                low[index(tile)] = low[index(tile)].min(order[index(neighbour)]);
+            }
+            continue;
+        }
+
+        stack.pop();
+        let Some(&(parent, _)) = stack.last() else {
+            continue;
+        };
+        low[index(parent)] = low[index(parent)].min(low[index(tile)]);
+        if leads_to_exit[index(tile)] {
+            leads_to_exit[index(parent)] = true;
+            if low[index(tile)] >= order[index(parent)] && parent != start {
+                found.insert(parent);
+            }
+        }
+    }
+    found
+}
+
+// Whether the walkable ground around `blocked` stays connected within a few tiles of `center`, so any
+// route through the patch can step around it instead
+fn detour_around(center: Tile, blocked: &[Tile], walkable: impl Fn(Tile) -> bool) -> bool {
+    let near = |(x, y): Tile, reach: usize| x.abs_diff(center.0) <= reach && y.abs_diff(center.1) <= reach;
+    let free = |tile: Tile| near(tile, 3) && !blocked.contains(&tile) && walkable(tile);
+    let edge: Vec<Tile> = (center.1.saturating_sub(2)..=center.1 + 2)
+        .flat_map(|y| (center.0.saturating_sub(2)..=center.0 + 2).map(move |x| (x, y)))
+        .filter(|tile| free(*tile))
+        .collect();
+    let Some(first) = edge.first() else {
+        return true;
+    };
+    let reached: HashSet<Tile> = bfs_reach(*first, |t| neighbours(*t).into_iter().filter(|n| free(*n)).collect::<Vec<_>>()).collect();
+    edge.iter().all(|tile| reached.contains(tile))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::item_registry::ITEM_CATALOGUE_PATH;
+    use crate::resources::map_validation::validate_map;
+
+    #[test]
+    fn test_generated_zone_is_valid_and_repeatable() {
+        let items = ItemRegistry::load_from_file(ITEM_CATALOGUE_PATH).unwrap();
+        let params = ZoneGenParams { width: 40, height: 30, exits: 2, ..ZoneGenParams::default() };
+
+        for seed in [1, 7, 2024] {
+            let map = generate_zone(seed, &params, &items).unwrap();
+            assert_eq!((map.width, map.height), (40, 30));
+            assert_eq!(validate_map(&map, &items), Vec::new(), "seed {}", seed);
+
+            let count = |wanted: EntityType| map.entities.iter().filter(|placed| EntityType::from(placed.entity_type) == wanted).count();
+            assert_eq!(count(EntityType::Exit), 2);
+            assert_eq!(count(EntityType::GravitationalAnomaly), params.gravitational_anomalies);
+            assert_eq!(map.items.len(), params.item_piles);
+
+            let again = generate_zone(seed, &params, &items).unwrap();
+            assert_eq!(serde_json::to_string(&again).unwrap(), serde_json::to_string(&map).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_generator_rejects_impossible_params() {
+        let items = ItemRegistry::load_from_file(ITEM_CATALOGUE_PATH).unwrap();
+        let crowded = ZoneGenParams { width: 5, height: 5, gravitational_anomalies: 10, ..ZoneGenParams::default() };
+        assert!(generate_zone(1, &crowded, &items).is_err());
+
+        let unknown_loot = ZoneGenParams {
+            loot: vec![LootEntry { item: "moon_rock".to_string(), weight: 1, max_quantity: 1 }],
+            ..ZoneGenParams::default()
+        };
+        assert!(generate_zone(1, &unknown_loot, &items).unwrap_err().contains("moon_rock"));
+    }
+
+    #[test]
+    fn test_largest_zone_generates_quickly() {
+        let items = ItemRegistry::load_from_file(ITEM_CATALOGUE_PATH).unwrap();
+        let params = ZoneGenParams {
+            width: MAX_MAP_SIZE,
+            height: MAX_MAP_SIZE,
+            exits: 4,
+            gravitational_anomalies: 40,
+            philosopher_stones: 20,
+            rust_anomalies: 40,
+            item_piles: 200,
+            ..ZoneGenParams::default()
+        };
+
+        let started = std::time::Instant::now();
+        let map = generate_zone(3, &params, &items).unwrap();
+        let elapsed = started.elapsed();
+        assert_eq!((map.width, map.height), (MAX_MAP_SIZE, MAX_MAP_SIZE));
+        assert_eq!(validate_map(&map, &items), Vec::new());
+        assert!(elapsed.as_secs() < 30, "generating took {:?}", elapsed);
+    }
+}
@@ -0,0 +1,18 @@
+use bevy::prelude::Component;
+use serde::{Deserialize, Serialize};
+
+/// Turns the Exit on its tile into a way to another map: stepping on it takes the player to
+/// (`entry_x`, `entry_y`) in the map called `map` instead of ending the run
+#[derive(Component, Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ZoneLink {
+    pub map: String,
+    pub entry_x: usize,
+    pub entry_y: usize,
+}
+
+impl ZoneLink {
+    /// Short description for the editor and the map issues panel
+    pub fn label(&self) -> String {
+        format!("Link to {} ({}, {})", self.map, self.entry_x, self.entry_y)
+    }
+}
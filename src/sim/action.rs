@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+use crate::components::{components::Position, item::{Item, ItemId}};
+use crate::resources::game_grid::EntityType;
+
+fn one() -> u32 {
+    1
+}
+
+/// Where a thrown item is aimed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ThrowTarget {
+    /// As far as the item carries in one direction
+    Direction { dx: i32, dy: i32 },
+    /// A tile within the item's range, reached along a straight line
+    Tile { x: i32, y: i32 },
+}
+
+/// A single player decision fed into the simulation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Action {
+    /// Step one tile in the given direction
+    Move { dx: i32, dy: i32 },
+    /// Throw a bolt in the given direction
+    ThrowBolt { dx: i32, dy: i32 },
+    /// Throw one unit of the inventory stack at `index`
+    ThrowItem { index: usize, target: ThrowTarget },
+    /// Pick up `quantity` units of the stack at `index` on the player's tile (does not end the turn)
+    Pickup {
+        index: usize,
+        #[serde(default = "one")]
+        quantity: u32,
+    },
+    /// Drop `quantity` units of the inventory stack at `index` onto the player's tile (does not end the turn)
+    Drop {
+        index: usize,
+        #[serde(default = "one")]
+        quantity: u32,
+    },
+    /// Close the inspect or inventory screen, which consumes a turn
+    CloseUi,
+}
+
+/// Why a move was rejected (no turn is consumed)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveBlocked {
+    OutOfBounds,
+    Wall,
+    Overweight,
+}
+
+/// What stopped a thrown item in flight
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThrowHit {
+    OutOfBounds,
+    Wall,
+    Anomaly(EntityType),
+    /// A heavy item flying past a Gravitational Anomaly is dragged onto it
+    Pulled,
+    /// It carried as far as it could, or reached the tile it was thrown at
+    MaxRange,
+}
+
+impl ThrowHit {
+    /// Player-facing description of how the thrown item (e.g. "bolt") came to rest
+    pub fn message(&self, item: &str) -> String {
+        match self {
+            ThrowHit::OutOfBounds => format!("The {} flies out of sight.", item),
+            ThrowHit::Wall => format!("The {} clangs against the wall.", item),
+            ThrowHit::Anomaly(EntityType::GravitationalAnomaly) => {
+                format!("The {} curves sharply and falls to the ground near a gravitational distortion.", item)
+            }
+            ThrowHit::Anomaly(EntityType::PhilosopherStone) => {
+                format!("The {} strikes something shimmering and falls to the ground.", item)
+            }
+            ThrowHit::Anomaly(EntityType::RustAnomaly) => {
+                format!("The {} strikes something and begins to oxidize rapidly.", item)
+            }
+            ThrowHit::Anomaly(_) => format!("The {} strikes something unusual.", item),
+            ThrowHit::Pulled => format!("The {} is dragged out of the air and slams into a gravitational distortion.", item),
+            ThrowHit::MaxRange => format!("The {} falls to the ground harmlessly.", item),
+        }
+    }
+}
+
+/// The traced path of a thrown item
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThrowFlight {
+    /// Tiles the item passes through, in order (excludes the thrower's tile)
+    pub path: Vec<Position>,
+    /// Where the item comes to rest
+    pub landing: Position,
+    pub hit: ThrowHit,
+}
+
+/// Something that happened while applying an action or processing the world
+#[derive(Debug, Clone)]
+pub enum GameEvent {
+    /// Text for the player's message log
+    Message(String),
+    PlayerMoved { from: Position, to: Position },
+    MoveBlocked(MoveBlocked),
+    PulledByGravity { to: Position },
+    CaughtInGravity { turns_left: u32 },
+    GravityTimerTicked { turns_left: u32 },
+    EscapedGravity,
+    ItemTransmuted { from: ItemId, to: ItemId },
+    ItemRusted { item: ItemId, in_inventory: bool },
+    ItemThrown { item: ItemId, flight: ThrowFlight },
+    ItemPickedUp(Item),
+    ItemDropped(Item),
+    PlayerDied,
+    ReachedExit,
+    /// The player went through a linked Exit into the named map
+    EnteredZone(String),
+    TurnEnded(u32),
+}
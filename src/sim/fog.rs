@@ -0,0 +1,142 @@
+use serde::{Deserialize, Serialize};
+use crate::components::components::Position;
+use crate::resources::game_grid::{GameGrid, TileKind};
+
+/// What the player knows about a tile
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TileVisibility {
+    /// Never seen
+    #[default]
+    Unseen,
+    /// Seen before but out of sight now
+    Remembered,
+    /// In the player's line of sight this turn
+    Visible,
+}
+
+/// The player's map knowledge for one run: per-tile visibility
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KnownMap {
+    width: usize,
+    height: usize,
+    tiles: Vec<TileVisibility>,
+}
+
+impl KnownMap {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            tiles: vec![TileVisibility::Unseen; width * height],
+        }
+    }
+
+    fn index(&self, pos: Position) -> Option<usize> {
+        let in_bounds = pos.x >= 0 && pos.y >= 0 && (pos.x as usize) < self.width && (pos.y as usize) < self.height;
+        in_bounds.then(|| pos.y as usize * self.width + pos.x as usize)
+    }
+
+    /// Visibility of a tile; anything off the map is unseen
+    pub fn visibility(&self, pos: Position) -> TileVisibility {
+        self.index(pos).map_or(TileVisibility::Unseen, |i| self.tiles[i])
+    }
+
+    /// Recomputes what the player can see from `origin`
+    /// Tiles that drop out of sight are remembered; walls block line of sight but are themselves seen
+    pub fn update(&mut self, grid: &GameGrid, origin: Position, radius: i32) {
+        for tile in &mut self.tiles {
+            if *tile == TileVisibility::Visible {
+                *tile = TileVisibility::Remembered;
+            }
+        }
+
+        for dy in -radius..=radius {
+            for dx in -radius..=radius {
+                if dx * dx + dy * dy > radius * radius {
+                    continue;
+                }
+
+                let target = Position { x: origin.x + dx, y: origin.y + dy };
+                let Some(i) = self.index(target) else {
+                    continue;
+                };
+
+                if has_line_of_sight(grid, origin, target) {
+                    self.tiles[i] = TileVisibility::Visible;
+                }
+            }
+        }
+    }
+}
+
+/// Checks that no wall stands strictly between two tiles (Bresenham line)
+pub fn has_line_of_sight(grid: &GameGrid, from: Position, to: Position) -> bool {
+    let dx = (to.x - from.x).abs();
+    let dy = -(to.y - from.y).abs();
+    let step_x = if from.x < to.x { 1 } else { -1 };
+    let step_y = if from.y < to.y { 1 } else { -1 };
+
+    let mut error = dx + dy;
+    let (mut x, mut y) = (from.x, from.y);
+
+    loop {
+        if x == to.x && y == to.y {
+            return true;
+        }
+
+        let is_endpoint = x == from.x && y == from.y;
+        let is_wall = grid
+            .get_tile(x as usize, y as usize)
+            .is_some_and(|tile| tile.kind == TileKind::Wall);
+        if !is_endpoint && is_wall {
+            return false;
+        }
+
+        let doubled = 2 * error;
+        if doubled >= dy {
+            error += dy;
+            x += step_x;
+        }
+        if doubled <= dx {
+            error += dx;
+            y += step_y;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::game_grid::Tile;
+
+    fn pos(x: i32, y: i32) -> Position {
+        Position { x, y }
+    }
+
+    #[test]
+    fn test_walls_block_sight_and_seen_tiles_are_remembered() {
+        let mut grid = GameGrid::new_empty(9, 3);
+        grid.set_tile(4, 1, Tile::new(TileKind::Wall));
+
+        let mut known = KnownMap::new(9, 3);
+        known.update(&grid, pos(1, 1), 6);
+
+        assert_eq!(known.visibility(pos(3, 1)), TileVisibility::Visible);
+        assert_eq!(known.visibility(pos(4, 1)), TileVisibility::Visible); // the wall itself
+        assert_eq!(known.visibility(pos(6, 1)), TileVisibility::Unseen); // behind the wall
+
+        known.update(&grid, pos(7, 1), 1);
+        assert_eq!(known.visibility(pos(1, 1)), TileVisibility::Remembered);
+        assert_eq!(known.visibility(pos(7, 1)), TileVisibility::Visible);
+    }
+
+    #[test]
+    fn test_sight_is_limited_by_radius() {
+        let grid = GameGrid::new_empty(12, 1);
+        let mut known = KnownMap::new(12, 1);
+        known.update(&grid, pos(0, 0), 3);
+
+        assert_eq!(known.visibility(pos(3, 0)), TileVisibility::Visible);
+        assert_eq!(known.visibility(pos(4, 0)), TileVisibility::Unseen);
+    }
+}
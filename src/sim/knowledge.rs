@@ -0,0 +1,105 @@
+use serde::{Deserialize, Serialize};
+use crate::components::components::Position;
+use crate::resources::game_grid::EntityType;
+
+/// What the player has learned about an anomaly, as opposed to what it really is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AnomalyClue {
+    /// Sensed (e.g. by the Metal Detector) but never observed acting
+    Unknown,
+    /// Bent a bolt or pulled the player
+    Gravitational,
+    /// Flashed at a bolt or transformed items
+    Shimmering,
+    /// Corroded a bolt or rusted metal
+    Corrosive,
+}
+
+impl AnomalyClue {
+    /// The clue an anomaly gives away when it is seen acting
+    pub fn observed(entity_type: EntityType) -> Self {
+        match entity_type {
+            EntityType::GravitationalAnomaly => AnomalyClue::Gravitational,
+            EntityType::PhilosopherStone => AnomalyClue::Shimmering,
+            EntityType::RustAnomaly => AnomalyClue::Corrosive,
+            _ => AnomalyClue::Unknown,
+        }
+    }
+
+    /// Player-facing marker text
+    pub fn label(&self) -> &'static str {
+        match self {
+            AnomalyClue::Unknown => "something strange",
+            AnomalyClue::Gravitational => "something gravitational",
+            AnomalyClue::Shimmering => "shimmering",
+            AnomalyClue::Corrosive => "corrosive",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct KnownAnomaly {
+    pub position: Position,
+    pub clue: AnomalyClue,
+}
+
+/// Anomalies the player has discovered this run; starts empty
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KnownAnomalies {
+    anomalies: Vec<KnownAnomaly>,
+}
+
+impl KnownAnomalies {
+    pub fn clue_at(&self, pos: Position) -> Option<AnomalyClue> {
+        self.anomalies
+            .iter()
+            .find(|known| known.position == pos)
+            .map(|known| known.clue)
+    }
+
+    /// Records a clue about the anomaly on a tile
+    /// A specific clue replaces a vague one, never the other way around
+    /// Returns true if the player learned something new
+    pub fn learn(&mut self, pos: Position, clue: AnomalyClue) -> bool {
+        match self.anomalies.iter_mut().find(|known| known.position == pos) {
+            Some(known) if known.clue == AnomalyClue::Unknown && clue != AnomalyClue::Unknown => {
+                known.clue = clue;
+                true
+            }
+            Some(_) => false,
+            None => {
+                self.anomalies.push(KnownAnomaly { position: pos, clue });
+                true
+            }
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &KnownAnomaly> {
+        self.anomalies.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.anomalies.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.anomalies.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_specific_clues_replace_vague_ones() {
+        let mut known = KnownAnomalies::default();
+        let pos = Position { x: 3, y: 4 };
+
+        assert!(known.learn(pos, AnomalyClue::Unknown));
+        assert!(known.learn(pos, AnomalyClue::Corrosive));
+        assert!(!known.learn(pos, AnomalyClue::Unknown));
+        assert_eq!(known.clue_at(pos), Some(AnomalyClue::Corrosive));
+        assert_eq!(known.len(), 1);
+    }
+}
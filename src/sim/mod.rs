@@ -0,0 +1,7 @@
+pub mod action;
+pub mod fog;
+pub mod knowledge;
+pub mod movement;
+pub mod zone_sim;
+pub mod replay;
+pub mod travel;
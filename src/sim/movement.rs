@@ -0,0 +1,109 @@
+use bevy::prelude::Resource;
+use serde::{Deserialize, Serialize};
+use crate::components::components::Position;
+
+/// The four orthogonal steps
+pub const CARDINAL_STEPS: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+/// The four orthogonal steps followed by the four diagonal ones
+pub const ALL_STEPS: [(i32, i32); 8] = [(1, 0), (-1, 0), (0, 1), (0, -1), (1, 1), (1, -1), (-1, 1), (-1, -1)];
+
+/// Which way the player (and their bolts) may go, chosen per map or in the settings
+/// The mode also sets how the Zone measures distance, so "adjacent" and "in range" mean the same
+/// thing for gravitational pull, the gravitational timer and the Metal Detector as they do for a step
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MovementMode {
+    /// 4 directions; distance is Manhattan
+    #[default]
+    Cardinal,
+    /// 8 directions; distance is Chebyshev, so a diagonal step costs the same as a straight one
+    Diagonal,
+}
+
+impl MovementMode {
+    /// Parses a settings value ("cardinal"/"4" or "diagonal"/"8")
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.to_ascii_lowercase().as_str() {
+            "cardinal" | "4" => Ok(MovementMode::Cardinal),
+            "diagonal" | "8" => Ok(MovementMode::Diagonal),
+            _ => Err(format!("Unknown movement mode '{}': expected cardinal or diagonal", value)),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MovementMode::Cardinal => "cardinal",
+            MovementMode::Diagonal => "diagonal",
+        }
+    }
+
+    /// Every single step this mode allows
+    pub fn steps(self) -> &'static [(i32, i32)] {
+        match self {
+            MovementMode::Cardinal => &CARDINAL_STEPS,
+            MovementMode::Diagonal => &ALL_STEPS,
+        }
+    }
+
+    /// Whether (dx, dy) is one step this mode allows
+    pub fn allows(self, dx: i32, dy: i32) -> bool {
+        self.steps().contains(&(dx, dy))
+    }
+
+    /// Distance between two tiles in steps, ignoring walls
+    pub fn distance(self, a: Position, b: Position) -> i32 {
+        let (dx, dy) = ((a.x - b.x).abs(), (a.y - b.y).abs());
+        match self {
+            MovementMode::Cardinal => dx + dy,
+            MovementMode::Diagonal => dx.max(dy),
+        }
+    }
+}
+
+/// Movement mode from the settings (`--movement <mode>`), for maps that don't choose their own
+#[derive(Resource, Default, Clone, Copy, Debug)]
+pub struct MovementSetting(pub MovementMode);
+
+impl MovementSetting {
+    /// The mode a run on `map` uses: the map's own choice, or else this setting
+    pub fn for_map(self, map: Option<MovementMode>) -> MovementMode {
+        map.unwrap_or(self.0)
+    }
+}
+
+/// The steps of a straight line from `from` to `to` (Bresenham), in the steps `mode` allows
+/// In Cardinal mode each diagonal step of the line is split into a horizontal and a vertical one,
+/// so the number of steps is always `mode.distance(from, to)`
+pub fn line_steps(from: Position, to: Position, mode: MovementMode) -> Vec<(i32, i32)> {
+    let (dx, dy) = (to.x - from.x, to.y - from.y);
+    let (ax, ay) = (dx.abs(), dy.abs());
+    let mut err = ax - ay;
+    let mut at = (0, 0);
+    let mut steps = Vec::new();
+    while at != (dx, dy) {
+        let mut step = (0, 0);
+        let doubled = 2 * err;
+        if doubled > -ay {
+            err -= ay;
+            step.0 = dx.signum();
+        }
+        if doubled < ax {
+            err += ax;
+            step.1 = dy.signum();
+        }
+        at = (at.0 + step.0, at.1 + step.1);
+        if mode == MovementMode::Cardinal && step.0 != 0 && step.1 != 0 {
+            steps.push((step.0, 0));
+            steps.push((0, step.1));
+        } else {
+            steps.push(step);
+        }
+    }
+    steps
+}
+
+/// Whether a diagonal step from `from` squeezes past a wall corner
+/// A diagonal step needs both tiles it passes between to be open; straight steps never cut corners
+pub fn cuts_corner(from: Position, dx: i32, dy: i32, is_wall: impl Fn(i32, i32) -> bool) -> bool {
+    dx != 0 && dy != 0 && (is_wall(from.x + dx, from.y) || is_wall(from.x, from.y + dy))
+}
@@ -0,0 +1,246 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use crate::components::item::Item;
+use crate::resources::item_registry::ItemRegistry;
+use crate::sim::action::Action;
+use crate::sim::movement::{MovementMode, MovementSetting};
+use crate::sim::zone_sim::{RunOutcome, ZoneSim};
+
+/// One recorded player action and the random rolls its resolution consumed
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ActionRecord {
+    /// Turn the action was taken on
+    pub turn: u32,
+    pub action: Action,
+    /// Raw values drawn from the run's RNG while resolving the action and its world update
+    pub rolls: Vec<u64>,
+}
+
+/// Where a run stood when its replay was written
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ReplaySummary {
+    pub turn: u32,
+    pub inventory: Vec<Item>,
+    pub outcome: Option<RunOutcome>,
+}
+
+impl ReplaySummary {
+    pub fn of(sim: &ZoneSim) -> Self {
+        Self {
+            turn: sim.turn,
+            inventory: sim.player.inventory.items.clone(),
+            outcome: sim.outcome,
+        }
+    }
+}
+
+/// A whole run: seed, map fingerprint, every action in order and the final state
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Replay {
+    pub seed: u64,
+    pub map_hash: u64,
+    /// Movement mode the run was played in, in zones that don't choose their own
+    #[serde(default)]
+    pub movement: MovementMode,
+    pub actions: Vec<ActionRecord>,
+    pub summary: ReplaySummary,
+}
+
+impl Replay {
+    /// Checks that a fresh simulation matches the map and seed this replay was recorded on
+    pub fn check_start(&self, sim: &ZoneSim) -> Result<(), String> {
+        if sim.map_hash != self.map_hash {
+            return Err(format!(
+                "Replay was recorded on a different map (hash {:016x}, current map {:016x})",
+                self.map_hash, sim.map_hash
+            ));
+        }
+        if sim.rng.seed() != self.seed {
+            return Err(format!("Replay needs seed {}, run was started with {}", self.seed, sim.rng.seed()));
+        }
+        if sim.movement_setting.0 != self.movement {
+            return Err(format!(
+                "Replay was recorded with {} movement, run was started with {}",
+                self.movement.name(),
+                sim.movement_setting.0.name()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Compares the rolls of the most recently applied action against the recording
+    pub fn check_step(&self, sim: &ZoneSim, index: usize) -> Result<(), String> {
+        let (Some(expected), Some(actual)) = (self.actions.get(index), sim.actions.get(index)) else {
+            return Err(format!("Replay has no action {}", index));
+        };
+
+        if expected.action != actual.action || expected.turn != actual.turn {
+            return Err(format!(
+                "Replay diverged at action {}: expected {:?} on turn {}, got {:?} on turn {}",
+                index, expected.action, expected.turn, actual.action, actual.turn
+            ));
+        }
+        if expected.rolls != actual.rolls {
+            return Err(format!(
+                "Replay diverged on turn {}: random rolls differ ({} recorded, {} drawn)",
+                actual.turn,
+                expected.rolls.len(),
+                actual.rolls.len()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Compares the simulation's final state against the recording
+    pub fn check_summary(&self, sim: &ZoneSim) -> Result<(), String> {
+        let actual = ReplaySummary::of(sim);
+        if actual.turn != self.summary.turn {
+            return Err(format!("Replay ended on turn {}, recording ended on turn {}", actual.turn, self.summary.turn));
+        }
+        if actual.outcome != self.summary.outcome {
+            return Err(format!(
+                "Replay ended with {:?}, recording ended with {:?}",
+                actual.outcome, self.summary.outcome
+            ));
+        }
+        if actual.inventory != self.summary.inventory {
+            return Err("Replay ended with a different inventory than the recording".to_string());
+        }
+        Ok(())
+    }
+
+    /// Feeds every recorded action through the simulation, checking each turn against the recording
+    /// The simulation is switched to the movement setting the run was recorded with
+    pub fn play(&self, sim: &mut ZoneSim) -> Result<(), String> {
+        sim.set_movement_setting(MovementSetting(self.movement));
+        self.check_start(sim)?;
+
+        for (index, record) in self.actions.iter().enumerate() {
+            if sim.is_finished() {
+                return Err(format!("Run ended on turn {} with actions still to replay", sim.turn));
+            }
+            sim.apply(record.action);
+            self.check_step(sim, index)?;
+        }
+
+        self.check_summary(sim)
+    }
+
+    pub fn save_to_file(&self, path: &str) -> Result<(), String> {
+        if let Some(parent) = Path::new(path).parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+        }
+
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize: {}", e))?;
+
+        fs::write(path, json)
+            .map_err(|e| format!("Failed to write file: {}", e))
+    }
+
+    /// Loads a replay, giving items from replays written before item ids their catalogue ids
+    pub fn load_from_file(path: &str, items: &ItemRegistry) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+
+        let mut replay: Replay = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to deserialize: {}", e))?;
+        items.resolve_ids(replay.summary.inventory.iter_mut())?;
+        Ok(replay)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{components::Position, inventory::CarryCapacity, item::GroundItems};
+    use crate::resources::game_grid::{EntityType, GameGrid, Tile, TileKind};
+    use crate::resources::item_registry::{ItemRegistry, ITEM_CATALOGUE_PATH};
+
+    fn pos(x: i32, y: i32) -> Position {
+        Position { x, y }
+    }
+
+    fn items() -> ItemRegistry {
+        ItemRegistry::load_from_file(ITEM_CATALOGUE_PATH).unwrap()
+    }
+
+    fn zone(seed: u64) -> ZoneSim {
+        zone_on(GameGrid::new_empty(7, 3), seed)
+    }
+
+    fn zone_on(grid: GameGrid, seed: u64) -> ZoneSim {
+        let entities = vec![
+            (pos(1, 1), EntityType::PlayerStart),
+            (pos(2, 1), EntityType::PhilosopherStone),
+            (pos(3, 1), EntityType::RustAnomaly),
+            (pos(5, 1), EntityType::GravitationalAnomaly),
+        ];
+        let mut pile = GroundItems::new();
+        pile.add_item(items().create("scrap"), &items());
+        pile.add_item(items().create("battery"), &items());
+        ZoneSim::new(grid, entities, vec![(pos(2, 1), pile)], CarryCapacity::default(), items(), seed)
+            .unwrap()
+    }
+
+    fn record_run(seed: u64) -> Replay {
+        let mut sim = zone(seed);
+        let script = [
+            Action::Move { dx: 1, dy: 0 },
+            Action::CloseUi,
+            Action::Pickup { index: 0, quantity: 1 },
+            Action::CloseUi,
+            Action::Move { dx: 1, dy: 0 },
+            Action::CloseUi,
+            Action::ThrowBolt { dx: 0, dy: 1 },
+            Action::Move { dx: 1, dy: 0 },
+        ];
+        for action in script {
+            sim.apply(action);
+        }
+        while !sim.is_finished() {
+            sim.apply(Action::CloseUi);
+        }
+        sim.to_replay()
+    }
+
+    #[test]
+    fn test_replay_reproduces_run() {
+        let replay = record_run(99);
+        assert!(replay.actions.iter().any(|record| !record.rolls.is_empty()));
+        assert_eq!(replay.summary.outcome, Some(RunOutcome::Dead));
+
+        let mut sim = zone(99);
+        replay.play(&mut sim).unwrap();
+        assert_eq!(sim.to_replay(), replay);
+    }
+
+    #[test]
+    fn test_replay_survives_json_round_trip() {
+        let replay = record_run(5);
+        let json = serde_json::to_string(&replay).unwrap();
+        let loaded: Replay = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, replay);
+    }
+
+    #[test]
+    fn test_replay_rejects_other_map() {
+        let replay = record_run(5);
+        let mut grid = GameGrid::new_empty(7, 3);
+        grid.set_tile(0, 0, Tile::new(TileKind::Wall));
+
+        let mut sim = zone_on(grid, 5);
+        assert!(replay.play(&mut sim).unwrap_err().contains("different map"));
+    }
+
+    #[test]
+    fn test_replay_detects_diverging_rolls() {
+        let mut replay = record_run(5);
+        let record = replay.actions.iter_mut().find(|record| !record.rolls.is_empty()).unwrap();
+        record.rolls[0] ^= 1;
+
+        let mut sim = zone(5);
+        assert!(replay.play(&mut sim).unwrap_err().contains("diverged"));
+    }
+}
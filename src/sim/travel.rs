@@ -0,0 +1,140 @@
+use pathfinding::prelude::astar;
+use crate::components::components::Position;
+use crate::constants::METAL_DETECTOR_RANGE;
+use crate::resources::game_grid::EntityType;
+use crate::sim::fog::TileVisibility;
+use crate::sim::movement::cuts_corner;
+use crate::sim::zone_sim::ZoneSim;
+
+/// What auto-travel keeps an eye on between steps (see `ZoneSim::travel_watch`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TravelWatch {
+    caught: bool,
+    items_in_sight: usize,
+    metal_detected: bool,
+    known_anomalies: usize,
+}
+
+impl TravelWatch {
+    /// Whether anything happened since this watch that should stop auto-travel: being caught by
+    /// gravity, a new item pile coming into sight, the metal detector going off, an anomaly discovered
+    pub fn interrupted_by(&self, now: &TravelWatch) -> bool {
+        (now.caught && !self.caught)
+            || now.items_in_sight > self.items_in_sight
+            || (now.metal_detected && !self.metal_detected)
+            || now.known_anomalies > self.known_anomalies
+    }
+}
+
+impl ZoneSim {
+    /// Cheapest walk from the player to `destination` by `Tile::move_cost`, in the steps the movement
+    /// mode allows (never cutting wall corners), over tiles the player has seen and around walls, the
+    /// anomalies they know of and Exits other than the destination
+    /// The steps leave out the player's own tile; None if there is no such walk
+    pub fn find_path(&self, destination: Position) -> Option<Vec<Position>> {
+        let walkable = |pos: &Position| {
+            self.known.visibility(*pos) != TileVisibility::Unseen
+                && !self.is_wall(pos.x, pos.y)
+                && self.known_anomalies.clue_at(*pos).is_none()
+                && (*pos == destination || !self.has_entity_at(*pos, EntityType::Exit))
+        };
+        if !walkable(&destination) {
+            return None;
+        }
+
+        let (mut path, _) = astar(
+            &self.player.position,
+            |pos| {
+                self.movement
+                    .steps()
+                    .iter()
+                    .filter(|(dx, dy)| !cuts_corner(*pos, *dx, *dy, |x, y| self.is_wall(x, y)))
+                    .map(|(dx, dy)| Position { x: pos.x + dx, y: pos.y + dy })
+                    .filter(walkable)
+                    .filter_map(|next| {
+                        let tile = self.grid.get_tile(next.x as usize, next.y as usize)?;
+                        Some((next, tile.move_cost))
+                    })
+                    .collect::<Vec<_>>()
+            },
+            |pos| self.movement.distance(*pos, destination),
+            |pos| *pos == destination,
+        )?;
+        path.remove(0);
+        Some(path)
+    }
+
+    /// Whether a carried Metal Detector picks up metal items lying within its range
+    pub fn metal_detected(&self) -> bool {
+        let player_pos = self.player.position;
+        self.player.inventory.has_metal_detector()
+            && self.ground_items.iter().any(|(pos, pile)| {
+                self.movement.distance(*pos, player_pos) <= METAL_DETECTOR_RANGE && pile.items.iter().any(|item| item.is_metal)
+            })
+    }
+
+    /// The player's situation as far as auto-travel cares, to compare after each step
+    pub fn travel_watch(&self) -> TravelWatch {
+        TravelWatch {
+            caught: self.player.gravity_timer.is_some(),
+            items_in_sight: self
+                .ground_items
+                .keys()
+                .filter(|pos| self.known.visibility(**pos) == TileVisibility::Visible)
+                .count(),
+            metal_detected: self.metal_detected(),
+            known_anomalies: self.known_anomalies.len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{inventory::CarryCapacity, item::GroundItems};
+    use crate::resources::game_grid::{GameGrid, Tile, TileKind};
+    use crate::resources::item_registry::{ItemRegistry, ITEM_CATALOGUE_PATH};
+    use crate::sim::action::Action;
+
+    fn pos(x: i32, y: i32) -> Position {
+        Position { x, y }
+    }
+
+    #[test]
+    fn test_path_goes_around_walls_and_known_anomalies() {
+        let items = ItemRegistry::load_from_file(ITEM_CATALOGUE_PATH).unwrap();
+        let mut grid = GameGrid::new_empty(7, 7);
+        for y in 0..5 {
+            grid.set_tile(3, y, Tile::new(TileKind::Wall));
+        }
+        let entities = vec![(pos(1, 1), EntityType::PlayerStart), (pos(2, 5), EntityType::RustAnomaly)];
+        let mut sim = ZoneSim::new(grid, entities, Vec::new(), CarryCapacity::default(), items, 42).unwrap();
+
+        // Nothing past the wall has been seen yet
+        assert!(sim.find_path(pos(5, 1)).is_none());
+
+        // Once it has, the walk goes down and round the end of the wall
+        let grid = sim.grid.clone();
+        sim.known.update(&grid, pos(5, 5), 6);
+        let path = sim.find_path(pos(5, 1)).unwrap();
+        assert_eq!(path.len(), 12);
+        assert_eq!(path.last(), Some(&pos(5, 1)));
+        assert!(path.iter().all(|step| step.x != 3 || step.y >= 5));
+        assert!(sim.find_path(pos(3, 0)).is_none());
+
+        // A known anomaly is walked around
+        sim.apply(Action::Move { dx: 1, dy: 0 });
+        sim.apply(Action::ThrowBolt { dx: 0, dy: 1 });
+        assert!(sim.known_anomalies.clue_at(pos(2, 5)).is_some());
+        let path = sim.find_path(pos(2, 6)).unwrap();
+        assert!(!path.contains(&pos(2, 5)));
+        assert_eq!(path.len(), 7);
+
+        // An item pile coming into view interrupts
+        let watch = sim.travel_watch();
+        sim.ground_items.insert(pos(2, 2), GroundItems::new());
+        sim.refresh_fov();
+        assert!(watch.interrupted_by(&sim.travel_watch()));
+        assert!(!sim.travel_watch().interrupted_by(&sim.travel_watch()));
+    }
+}
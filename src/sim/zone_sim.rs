@@ -0,0 +1,1421 @@
+use bevy::prelude::*;
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use crate::components::{
+    components::Position,
+    inventory::{Inventory, CarryCapacity},
+    item::{GroundItems, Item, ItemId},
+};
+use crate::resources::{
+    game_grid::{GameGrid, TileKind, EntityType},
+    item_registry::{ItemRegistry, BOLT, FULLY_EMPTY, METAL_DETECTOR, RUST_SLAG, TAG_TRANSMUTABLE},
+    map_data::{MapData, PlacedZoneLink, map_path},
+    seed::RunRng,
+    spawn_points::resolve_spawn_points,
+    zone_links::ZoneLink,
+};
+use crate::sim::action::{Action, ThrowFlight, ThrowHit, ThrowTarget, GameEvent, MoveBlocked};
+use crate::sim::fog::KnownMap;
+use crate::sim::knowledge::{AnomalyClue, KnownAnomalies};
+use crate::sim::movement::{MovementMode, MovementSetting, cuts_corner, line_steps};
+use crate::sim::replay::{ActionRecord, Replay, ReplaySummary};
+use crate::constants::{
+    GRAVITY_TIMER_TURNS, HEAVY_THROW_WEIGHT, MAPS_DIRECTORY, MAX_THROW_RANGE, METAL_DETECTOR_RANGE, SIGHT_RADIUS,
+    THROW_STRENGTH,
+};
+
+/// The player's side of the simulation
+#[derive(Debug, Clone)]
+pub struct PlayerState {
+    pub position: Position,
+    pub inventory: Inventory,
+    /// Turns left before being crushed; `Some` while caught in a gravitational anomaly
+    pub gravity_timer: Option<u32>,
+}
+
+/// How a run ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RunOutcome {
+    Dead,
+    Extracted,
+}
+
+/// A zone the player has left, kept as they left it for when they come back
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VisitedZone {
+    pub map: MapData,
+    pub known: KnownMap,
+    pub known_anomalies: KnownAnomalies,
+}
+
+/// Headless simulation of one Zone run
+/// Owns the terrain, placed entities, ground items and the player, and applies the game rules
+/// The terrain, entities, ground items and map knowledge are those of the zone the player is in;
+/// zones they have left wait in `visited`
+/// During Running mode this is the authoritative game state; the ECS mirrors it for rendering
+#[derive(Resource, Clone)]
+pub struct ZoneSim {
+    pub grid: GameGrid,
+    pub entities: Vec<(Position, EntityType)>,
+    pub ground_items: HashMap<Position, GroundItems>,
+    pub player: PlayerState,
+    pub capacity: CarryCapacity,
+    pub turn: u32,
+    pub outcome: Option<RunOutcome>,
+    /// Every random roll of the run goes through this, so a seed replays turn for turn
+    pub rng: RunRng,
+    /// Fingerprint of the starting map, so a replay can tell it is being played on the right one
+    pub map_hash: u64,
+    /// Every action performed so far, with the rolls each one consumed
+    pub actions: Vec<ActionRecord>,
+    /// Tiles the player has seen so far
+    pub known: KnownMap,
+    /// Anomalies the player has discovered, and what they learned about each
+    pub known_anomalies: KnownAnomalies,
+    /// Item catalogue the run creates items from
+    pub items: ItemRegistry,
+    /// Name of the map the player is in ("" for a map that was never saved)
+    pub zone: String,
+    /// Exits of this zone that lead on to other maps
+    pub links: Vec<PlacedZoneLink>,
+    /// Zones the player has left, by map name
+    pub visited: HashMap<String, VisitedZone>,
+    /// Where linked maps are loaded from
+    pub maps_directory: String,
+    /// Directions the player may move and throw in, and the distance the Zone measures by
+    /// The zone's own mode, or else `movement_setting`; it changes with the zone
+    pub movement: MovementMode,
+    /// The mode the zone the player is in chose for itself, if any
+    pub map_movement: Option<MovementMode>,
+    /// Mode for zones that don't choose their own
+    pub movement_setting: MovementSetting,
+}
+
+impl ZoneSim {
+    /// Builds a simulation from map contents, placing the player on the PlayerStart marker
+    pub fn new(
+        grid: GameGrid,
+        entities: Vec<(Position, EntityType)>,
+        ground_items: Vec<(Position, GroundItems)>,
+        capacity: CarryCapacity,
+        items: ItemRegistry,
+        seed: u64,
+    ) -> Result<Self, String> {
+        let start = entities
+            .iter()
+            .find(|(_, entity_type)| matches!(entity_type, EntityType::PlayerStart))
+            .map(|(pos, _)| *pos)
+            .ok_or_else(|| "No PlayerStart marker found in the map!".to_string())?;
+
+        let entities = in_map_order(entities);
+        let merged = merge_piles(ground_items, &items);
+
+        let map_hash = map_hash(&grid, &entities, &merged);
+        let known = KnownMap::new(grid.width, grid.height);
+
+        let mut sim = Self {
+            grid,
+            entities,
+            ground_items: merged,
+            player: PlayerState {
+                position: start,
+                inventory: starting_inventory(&items),
+                gravity_timer: None,
+            },
+            capacity,
+            turn: 0,
+            outcome: None,
+            rng: RunRng::new(seed),
+            map_hash,
+            actions: Vec::new(),
+            known,
+            known_anomalies: KnownAnomalies::default(),
+            items,
+            zone: String::new(),
+            links: Vec::new(),
+            visited: HashMap::new(),
+            maps_directory: MAPS_DIRECTORY.to_string(),
+            movement: MovementMode::default(),
+            map_movement: None,
+            movement_setting: MovementSetting::default(),
+        };
+        sim.refresh_fov();
+        Ok(sim)
+    }
+
+    /// Builds a simulation straight from a saved map, rolling its spawn points from `seed`
+    pub fn from_map_data(map_data: &MapData, items: ItemRegistry, seed: u64) -> Result<Self, String> {
+        let (entities, ground_items) = map_contents(map_data, &items, seed);
+        let mut sim = Self::new(map_data.to_game_grid(), entities, ground_items, CarryCapacity::default(), items, seed)?;
+        sim.links = map_data.zone_links.clone();
+        sim.set_map_movement(map_data.movement);
+        Ok(sim)
+    }
+
+    /// The zone the player is in as map data, with its zone links
+    pub fn zone_map(&self) -> MapData {
+        let entities: Vec<(EntityType, usize, usize)> = self
+            .entities
+            .iter()
+            .map(|(pos, entity_type)| (*entity_type, pos.x as usize, pos.y as usize))
+            .collect();
+        let ground_items: Vec<_> = self
+            .ground_items
+            .iter()
+            .map(|(pos, items)| (items.clone(), pos.x as usize, pos.y as usize))
+            .collect();
+
+        let mut map = MapData::from_game_state(&self.grid, &entities, &ground_items);
+        map.zone_links = self.links.clone();
+        map.movement = self.map_movement;
+        map
+    }
+
+    /// Switches the movement mode, re-sensing with the detector's range measured the new way
+    fn set_movement(&mut self, movement: MovementMode) {
+        self.movement = movement;
+        self.refresh_fov();
+    }
+
+    /// Moves the way the zone's map chose, or by the movement setting if it didn't
+    pub fn set_map_movement(&mut self, map_movement: Option<MovementMode>) {
+        self.map_movement = map_movement;
+        self.set_movement(self.movement_setting.for_map(map_movement));
+    }
+
+    /// Changes the mode for zones that don't choose their own, the one the player is in included
+    pub fn set_movement_setting(&mut self, setting: MovementSetting) {
+        self.movement_setting = setting;
+        self.set_map_movement(self.map_movement);
+    }
+
+    // --- Queries ---
+
+    /// Returns the anomaly (if any) occupying the given tile
+    pub fn anomaly_at(&self, pos: Position) -> Option<EntityType> {
+        self.entities
+            .iter()
+            .find(|(entity_pos, entity_type)| *entity_pos == pos && is_anomaly(*entity_type))
+            .map(|(_, entity_type)| *entity_type)
+    }
+
+    /// Checks whether an entity of the given type sits on the tile
+    pub fn has_entity_at(&self, pos: Position, entity_type: EntityType) -> bool {
+        self.entities
+            .iter()
+            .any(|(entity_pos, placed_type)| *entity_pos == pos && *placed_type == entity_type)
+    }
+
+    /// Where the Exit on the given tile leads, if it leads anywhere but out of the Zone
+    pub fn link_at(&self, pos: Position) -> Option<&ZoneLink> {
+        self.links
+            .iter()
+            .find(|placed| placed.x as i32 == pos.x && placed.y as i32 == pos.y)
+            .map(|placed| &placed.link)
+    }
+
+    pub fn ground_items_at(&self, pos: Position) -> Option<&GroundItems> {
+        self.ground_items.get(&pos)
+    }
+
+    pub fn in_bounds(&self, x: i32, y: i32) -> bool {
+        x >= 0 && y >= 0 && x < self.grid.width as i32 && y < self.grid.height as i32
+    }
+
+    pub fn is_wall(&self, x: i32, y: i32) -> bool {
+        self.in_bounds(x, y)
+            && self
+                .grid
+                .get_tile(x as usize, y as usize)
+                .is_some_and(|tile| tile.kind == TileKind::Wall)
+    }
+
+    /// Carry limit for the player's current situation
+    pub fn max_capacity(&self) -> u32 {
+        if self.player.gravity_timer.is_some() {
+            self.capacity.in_gravity
+        } else {
+            self.capacity.normal
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.outcome.is_some()
+    }
+
+    /// Everything needed to replay the run so far
+    pub fn to_replay(&self) -> Replay {
+        let mut actions = self.actions.clone();
+        let mut rng = self.rng.clone();
+        if let Some(last) = actions.last_mut() {
+            last.rolls.extend(rng.take_rolls());
+        }
+
+        Replay {
+            seed: self.rng.seed(),
+            map_hash: self.map_hash,
+            movement: self.movement_setting.0,
+            actions,
+            summary: ReplaySummary::of(self),
+        }
+    }
+
+    /// Recomputes line of sight from the player
+    /// A carried Metal Detector also senses anomalies within its range, without telling what they are
+    pub fn refresh_fov(&mut self) {
+        let player_pos = self.player.position;
+        self.known.update(&self.grid, player_pos, SIGHT_RADIUS);
+
+        if !self.player.inventory.has_metal_detector() {
+            return;
+        }
+        for (pos, entity_type) in &self.entities {
+            let distance = self.movement.distance(*pos, player_pos);
+            if is_anomaly(*entity_type) && distance <= METAL_DETECTOR_RANGE {
+                self.known_anomalies.learn(*pos, AnomalyClue::Unknown);
+            }
+        }
+    }
+
+    /// Files the rolls drawn since the last action under that action
+    fn record_rolls(&mut self) {
+        let rolls = self.rng.take_rolls();
+        if let Some(last) = self.actions.last_mut() {
+            last.rolls.extend(rolls);
+        }
+    }
+
+    // --- Turn driver ---
+
+    /// Applies one player action and, if it consumed the turn, processes the world update
+    pub fn apply(&mut self, action: Action) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+        if self.is_finished() {
+            return events;
+        }
+
+        if self.perform(action, &mut events) {
+            self.run_world_update(&mut events);
+            if self.outcome.is_none() {
+                self.check_exit(&mut events);
+            }
+        }
+
+        events
+    }
+
+    /// Resolves the player's part of an action
+    /// Returns true if the action consumed the turn (the world update should run next)
+    pub fn perform(&mut self, action: Action, events: &mut Vec<GameEvent>) -> bool {
+        self.record_rolls();
+        self.actions.push(ActionRecord { turn: self.turn, action, rolls: Vec::new() });
+
+        let consumed = match action {
+            Action::Move { dx, dy } => self.move_player(dx, dy, events),
+            Action::ThrowBolt { dx, dy } => self.throw_bolt(dx, dy, events),
+            Action::ThrowItem { index, target } => self.throw_item(index, target, events),
+            Action::Pickup { index, quantity } => {
+                self.pickup(index, quantity, events);
+                false
+            }
+            Action::Drop { index, quantity } => {
+                self.drop_item(index, quantity, events);
+                false
+            }
+            Action::CloseUi => true,
+        };
+
+        self.refresh_fov();
+        consumed
+    }
+
+    /// Runs every WorldUpdate step in order
+    pub fn run_world_update(&mut self, events: &mut Vec<GameEvent>) {
+        self.gravitational_pull(events);
+        self.philosopher_stone(events);
+        self.rust_anomaly(events);
+        self.gravitational_timer(events);
+        self.death_check(events);
+        self.advance_turn(events);
+    }
+
+    // --- Player actions ---
+
+    fn move_player(&mut self, dx: i32, dy: i32, events: &mut Vec<GameEvent>) -> bool {
+        if !self.movement.allows(dx, dy) {
+            return false;
+        }
+
+        // Check if player is over carry capacity
+        let current_weight = self.player.inventory.total_weight();
+        let max_capacity = self.max_capacity();
+        if current_weight > max_capacity {
+            events.push(GameEvent::Message("You're carrying too much weight to move!".to_string()));
+            events.push(GameEvent::MoveBlocked(MoveBlocked::Overweight));
+            info!("Movement blocked: over carry capacity ({}/{})", current_weight, max_capacity);
+            return false;
+        }
+
+        let from = self.player.position;
+        let new_x = from.x + dx;
+        let new_y = from.y + dy;
+
+        if !self.in_bounds(new_x, new_y) {
+            events.push(GameEvent::MoveBlocked(MoveBlocked::OutOfBounds));
+            return false;
+        }
+
+        if self.is_wall(new_x, new_y) || cuts_corner(from, dx, dy, |x, y| self.is_wall(x, y)) {
+            events.push(GameEvent::MoveBlocked(MoveBlocked::Wall));
+            return false;
+        }
+
+        let to = Position { x: new_x, y: new_y };
+        self.player.position = to;
+        events.push(GameEvent::PlayerMoved { from, to });
+        info!("Player moved to ({}, {})", new_x, new_y);
+        true
+    }
+
+    /// Traces where `item` thrown from the player's tile at `target` would land, without changing anything
+    /// Errs if it can't be thrown there: a direction the movement mode doesn't allow, or a tile out of range
+    pub fn trace_throw(&self, item: &Item, target: ThrowTarget) -> Result<ThrowFlight, String> {
+        let range = throw_range(item);
+        let steps = match target {
+            ThrowTarget::Direction { dx, dy } => {
+                if !self.movement.allows(dx, dy) {
+                    return Err("You can't throw that way.".to_string());
+                }
+                vec![(dx, dy); range as usize]
+            }
+            ThrowTarget::Tile { x, y } => {
+                let to = Position { x, y };
+                let distance = self.movement.distance(self.player.position, to);
+                if distance == 0 {
+                    return Err("Pick a tile away from you to throw at.".to_string());
+                }
+                if distance > range as i32 {
+                    return Err(format!("The {} won't carry that far ({} tiles at most).", item.name.to_lowercase(), range));
+                }
+                line_steps(self.player.position, to, self.movement)
+            }
+        };
+        Ok(self.trace_steps(item, &steps))
+    }
+
+    /// Flies `item` along `steps` from the player's tile until it hits something or runs out of steps
+    fn trace_steps(&self, item: &Item, steps: &[(i32, i32)]) -> ThrowFlight {
+        let mut pos = self.player.position;
+        let mut path = Vec::new();
+
+        for (dx, dy) in steps.iter().copied() {
+            let next = Position { x: pos.x + dx, y: pos.y + dy };
+
+            if !self.in_bounds(next.x, next.y) {
+                return ThrowFlight { path, landing: pos, hit: ThrowHit::OutOfBounds };
+            }
+
+            if self.is_wall(next.x, next.y) || cuts_corner(pos, dx, dy, |x, y| self.is_wall(x, y)) {
+                return ThrowFlight { path, landing: pos, hit: ThrowHit::Wall };
+            }
+
+            if let Some(anomaly) = self.anomaly_at(next) {
+                path.push(next);
+                return ThrowFlight { path, landing: next, hit: ThrowHit::Anomaly(anomaly) };
+            }
+
+            pos = next;
+            path.push(pos);
+
+            // Heavy items don't fly past gravity: they're dragged onto an anomaly one step away,
+            // but not around a wall corner
+            if item.weight >= HEAVY_THROW_WEIGHT {
+                let pulled_to = self
+                    .entities
+                    .iter()
+                    .find(|(anomaly_pos, entity_type)| {
+                        *entity_type == EntityType::GravitationalAnomaly
+                            && self.movement.distance(pos, *anomaly_pos) == 1
+                            && !cuts_corner(pos, anomaly_pos.x - pos.x, anomaly_pos.y - pos.y, |x, y| self.is_wall(x, y))
+                    })
+                    .map(|(anomaly_pos, _)| *anomaly_pos);
+                if let Some(anomaly_pos) = pulled_to {
+                    path.push(anomaly_pos);
+                    return ThrowFlight { path, landing: anomaly_pos, hit: ThrowHit::Pulled };
+                }
+            }
+        }
+
+        ThrowFlight { path, landing: pos, hit: ThrowHit::MaxRange }
+    }
+
+    fn throw_bolt(&mut self, dx: i32, dy: i32, events: &mut Vec<GameEvent>) -> bool {
+        if !self.movement.allows(dx, dy) {
+            return false;
+        }
+
+        let Some(bolt_index) = self.player.inventory.find(BOLT) else {
+            events.push(GameEvent::Message("You don't have any bolts to throw!".to_string()));
+            return false;
+        };
+        self.throw_item(bolt_index, ThrowTarget::Direction { dx, dy }, events)
+    }
+
+    fn throw_item(&mut self, index: usize, target: ThrowTarget, events: &mut Vec<GameEvent>) -> bool {
+        let Some(stack) = self.player.inventory.items.get(index) else {
+            return false;
+        };
+        let flight = match self.trace_throw(stack, target) {
+            Ok(flight) => flight,
+            Err(e) => {
+                events.push(GameEvent::Message(e));
+                return false;
+            }
+        };
+
+        let Some(item) = self.player.inventory.remove_item(index, 1) else {
+            return false;
+        };
+        let id = item.id.clone();
+        events.push(GameEvent::Message(flight.hit.message(&item.name.to_lowercase())));
+        match flight.hit {
+            ThrowHit::Anomaly(anomaly) => {
+                self.known_anomalies.learn(flight.landing, AnomalyClue::observed(anomaly));
+            }
+            ThrowHit::Pulled => {
+                self.known_anomalies.learn(flight.landing, AnomalyClue::Gravitational);
+            }
+            _ => {}
+        }
+
+        let landed = self.land_thrown_item(item, flight.hit, events);
+        self.ground_items.entry(flight.landing).or_default().add_item(landed, &self.items);
+        info!("Thrown {} finalized at position ({}, {})", id, flight.landing.x, flight.landing.y);
+        events.push(GameEvent::ItemThrown { item: id, flight });
+        true
+    }
+
+    /// What a thrown item becomes where it lands: the Rust turns metal into Rust Slag and the
+    /// Philosopher's Stone transmutes anything of value, as they do to items on the ground
+    fn land_thrown_item(&mut self, item: Item, hit: ThrowHit, events: &mut Vec<GameEvent>) -> Item {
+        let name = item.name.to_lowercase();
+        match hit {
+            ThrowHit::Anomaly(EntityType::RustAnomaly) if item.is_metal => {
+                events.push(GameEvent::Message(format!("The {} rusts into a glob the moment it lands.", name)));
+                info!("Rust anomaly: Rusted thrown item {} → Rust Slag", item.name);
+                events.push(GameEvent::ItemRusted { item: item.id, in_inventory: false });
+                self.items.create(RUST_SLAG)
+            }
+            ThrowHit::Anomaly(EntityType::PhilosopherStone) if item.value.is_some() => {
+                let new_item = transmute(&self.items, &mut self.rng, &item);
+                events.push(GameEvent::Message(format!(
+                    "The {} shimmers with impossible light as it lands and becomes {}.",
+                    name, new_item.name
+                )));
+                info!("Philosopher's Stone: Transformed thrown {} → {}", item.name, new_item.name);
+                events.push(GameEvent::ItemTransmuted { from: item.id, to: new_item.id.clone() });
+                new_item
+            }
+            _ => item,
+        }
+    }
+
+    fn pickup(&mut self, index: usize, quantity: u32, events: &mut Vec<GameEvent>) {
+        let pos = self.player.position;
+        let Some(ground_items) = self.ground_items.get_mut(&pos) else {
+            return;
+        };
+
+        let Some(item) = ground_items.remove_item(index, quantity) else {
+            return;
+        };
+
+        if ground_items.is_empty() {
+            self.ground_items.remove(&pos);
+        }
+
+        events.push(GameEvent::Message(format!("Picked up: {}", item.label())));
+        info!("Picked up: {} (weight: {})", item.label(), item.total_weight());
+        self.player.inventory.add_item(item.clone(), &self.items);
+        events.push(GameEvent::ItemPickedUp(item));
+    }
+
+    fn drop_item(&mut self, index: usize, quantity: u32, events: &mut Vec<GameEvent>) {
+        let Some(item) = self.player.inventory.remove_item(index, quantity) else {
+            return;
+        };
+
+        let pos = self.player.position;
+        self.ground_items.entry(pos).or_default().add_item(item.clone(), &self.items);
+        info!("Dropped {} at ({}, {})", item.label(), pos.x, pos.y);
+        events.push(GameEvent::ItemDropped(item));
+    }
+
+    // --- World update steps ---
+
+    /// Pulls the player onto a Gravitational Anomaly one step away
+    /// Only pulls players who don't have a timer (fresh captures, not escaping players)
+    pub fn gravitational_pull(&mut self, events: &mut Vec<GameEvent>) {
+        // Don't pull if player already has a timer (they're trying to escape)
+        if self.player.gravity_timer.is_some() {
+            return;
+        }
+
+        let player_pos = self.player.position;
+        let Some(anomaly_pos) = self
+            .entities
+            .iter()
+            .filter(|(_, entity_type)| matches!(entity_type, EntityType::GravitationalAnomaly))
+            .map(|(pos, _)| *pos)
+            .find(|anomaly_pos| self.movement.distance(player_pos, *anomaly_pos) == 1)
+        else {
+            return;
+        };
+
+        // Pull player onto the anomaly, one step away (diagonally in Diagonal mode)
+        self.player.position = anomaly_pos;
+        self.known_anomalies.learn(anomaly_pos, AnomalyClue::Gravitational);
+
+        events.push(GameEvent::Message("Gravitational anomaly pulls you in!".to_string()));
+        events.push(GameEvent::PulledByGravity { to: anomaly_pos });
+        info!("Gravitational anomaly pulled player to ({}, {})", anomaly_pos.x, anomaly_pos.y);
+
+        self.player.gravity_timer = Some(GRAVITY_TIMER_TURNS);
+        events.push(GameEvent::Message(format!(
+            "Immense pressure... {} turns to escape!",
+            GRAVITY_TIMER_TURNS
+        )));
+        events.push(GameEvent::CaughtInGravity { turns_left: GRAVITY_TIMER_TURNS });
+        warn!("Player entered gravitational anomaly! {} turns to escape or die!", GRAVITY_TIMER_TURNS);
+    }
+
+    /// Philosopher's Stone effect while the player stands on it
+    /// Transforms one valued ground item into an equal/lesser value item (5% chance for Fully Empty)
+    /// Shows mysterious flavor text for non-valued items (no transformation)
+    pub fn philosopher_stone(&mut self, events: &mut Vec<GameEvent>) {
+        let player_pos = self.player.position;
+        if !self.has_entity_at(player_pos, EntityType::PhilosopherStone) {
+            return;
+        }
+
+        let Some(ground_items) = self.ground_items.get_mut(&player_pos) else {
+            return;
+        };
+
+        // Whatever happens next, the player sees the stone at work
+        self.known_anomalies.learn(player_pos, AnomalyClue::Shimmering);
+        let rng = &mut self.rng;
+
+        // Collect indices of valued items
+        let valued_indices: Vec<usize> = ground_items
+            .items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| item.value.is_some())
+            .map(|(idx, _)| idx)
+            .collect();
+
+        if valued_indices.is_empty() {
+            let messages = [
+                "The anomaly pulses with strange energy, but the items remain unchanged.",
+                "Reality shifts around you, but nothing happens.",
+                "Strange forces swirl, then dissipate.",
+                "You sense the anomaly trying to reshape what lies before you, but it cannot.",
+            ];
+            let msg = messages.choose(rng).unwrap();
+            events.push(GameEvent::Message(msg.to_string()));
+            info!("Philosopher's Stone: Non-valued items, no transformation");
+            return;
+        }
+
+        let selected_idx = *valued_indices.choose(rng).unwrap();
+        let original_item = &ground_items.items[selected_idx];
+        let original_name = original_item.name.clone();
+        let original_id = original_item.id.clone();
+        let new_item = transmute(&self.items, rng, original_item);
+
+        // Only one unit of a stack is transformed
+        ground_items.remove_item(selected_idx, 1);
+        ground_items.add_item(new_item.clone(), &self.items);
+
+        let message = if new_item.id == FULLY_EMPTY {
+            format!(
+                "The fabric of reality tears. A Fully Empty materializes where {} once was.",
+                original_name
+            )
+        } else {
+            let transformations = [
+                format!("The {} shimmers with impossible light and becomes {}.", original_name, new_item.name),
+                format!("Reality fractures. The {} transforms into {}.", original_name, new_item.name),
+                format!("The anomaly pulses. Where {} lay, now rests {}.", original_name, new_item.name),
+            ];
+            transformations.choose(rng).unwrap().clone()
+        };
+
+        events.push(GameEvent::Message(message));
+        info!("Philosopher's Stone: Transformed {} → {}", original_name, new_item.name);
+        events.push(GameEvent::ItemTransmuted { from: original_id, to: new_item.id });
+    }
+
+    /// The Rust effect while the player stands on it
+    /// Rusts one metal item from the ground OR the player's inventory
+    /// Ground items: clear descriptive message
+    /// Inventory items: vague sensory message (player doesn't know what rusted until they check)
+    pub fn rust_anomaly(&mut self, events: &mut Vec<GameEvent>) {
+        let player_pos = self.player.position;
+        if !self.has_entity_at(player_pos, EntityType::RustAnomaly) {
+            return;
+        }
+
+        enum MetalSource {
+            Ground(usize),    // item_index
+            Inventory(usize), // item_index
+        }
+
+        let mut metal_items: Vec<(MetalSource, ItemId, String)> = Vec::new();
+
+        if let Some(ground_items) = self.ground_items.get(&player_pos) {
+            for (idx, item) in ground_items.items.iter().enumerate() {
+                if item.is_metal {
+                    metal_items.push((MetalSource::Ground(idx), item.id.clone(), item.name.clone()));
+                }
+            }
+        }
+
+        for (idx, item) in self.player.inventory.items.iter().enumerate() {
+            if item.is_metal {
+                metal_items.push((MetalSource::Inventory(idx), item.id.clone(), item.name.clone()));
+            }
+        }
+
+        let Some((source, item_id, item_name)) = metal_items.choose(&mut self.rng) else {
+            return; // No metal items to rust
+        };
+        self.known_anomalies.learn(player_pos, AnomalyClue::Corrosive);
+
+        match source {
+            MetalSource::Ground(item_idx) => {
+                if let Some(ground_items) = self.ground_items.get_mut(&player_pos) {
+                    ground_items.remove_item(*item_idx, 1);
+                    ground_items.add_item(self.items.create(RUST_SLAG), &self.items);
+                }
+
+                events.push(GameEvent::Message(format!(
+                    "The {} on the ground begins to rust rapidly before your very eyes. In an instant, it melts into a rusty glob.",
+                    item_name
+                )));
+                info!("Rust anomaly: Rusted ground item {} → Rust Slag", item_name);
+                events.push(GameEvent::ItemRusted { item: item_id.clone(), in_inventory: false });
+            }
+            MetalSource::Inventory(item_idx) => {
+                self.player.inventory.remove_item(*item_idx, 1);
+                self.player.inventory.add_item(self.items.create(RUST_SLAG), &self.items);
+
+                let messages = [
+                    "The acrid smell of oxidation surrounds you.",
+                    "You sense something shifting in your pack.",
+                    "A metallic tang fills the air.",
+                    "The scent of rust and iron overwhelms you.",
+                    "Something heavy settles differently at your side.",
+                ];
+                let msg = messages.choose(&mut self.rng).unwrap();
+                events.push(GameEvent::Message(msg.to_string()));
+                info!("Rust anomaly: Rusted inventory item {} → Rust Slag", item_name);
+                events.push(GameEvent::ItemRusted { item: item_id.clone(), in_inventory: true });
+            }
+        }
+    }
+
+    /// Decrements the gravitational timer while the player is on or adjacent to an anomaly,
+    /// and clears it once they escape to a safe distance
+    pub fn gravitational_timer(&mut self, events: &mut Vec<GameEvent>) {
+        let Some(turns_left) = self.player.gravity_timer else {
+            return;
+        };
+
+        let player_pos = self.player.position;
+        let within_range = self.entities.iter().any(|(anomaly_pos, entity_type)| {
+            if !matches!(entity_type, EntityType::GravitationalAnomaly) {
+                return false;
+            }
+
+            // Within range: on anomaly OR one step away
+            self.movement.distance(player_pos, *anomaly_pos) <= 1
+        });
+
+        if within_range {
+            let turns_left = turns_left.saturating_sub(1);
+            self.player.gravity_timer = Some(turns_left);
+            events.push(GameEvent::Message(format!("Crushing pressure! {} turns left!", turns_left)));
+            events.push(GameEvent::GravityTimerTicked { turns_left });
+            warn!("Gravitational anomaly! {} turns remaining!", turns_left);
+        } else {
+            self.player.gravity_timer = None;
+            events.push(GameEvent::Message("You break free from the anomaly!".to_string()));
+            events.push(GameEvent::EscapedGravity);
+            info!("Player escaped gravitational anomaly!");
+        }
+    }
+
+    /// Ends the run if the gravitational timer has run out
+    pub fn death_check(&mut self, events: &mut Vec<GameEvent>) {
+        if self.player.gravity_timer == Some(0) {
+            self.outcome = Some(RunOutcome::Dead);
+            events.push(GameEvent::Message("You are crushed to death!".to_string()));
+            events.push(GameEvent::PlayerDied);
+            error!("DEATH: Player was crushed by gravitational anomaly!");
+        }
+    }
+
+    /// Increments the turn counter
+    pub fn advance_turn(&mut self, events: &mut Vec<GameEvent>) {
+        self.record_rolls();
+        self.refresh_fov();
+        self.turn += 1;
+        events.push(GameEvent::TurnEnded(self.turn));
+        info!("Turn {}", self.turn);
+    }
+
+    /// Ends the run as an extraction if the player stands on an Exit, or takes them on to another
+    /// zone if the Exit is linked to one (an Exit whose link can't be followed extracts instead)
+    /// Returns true if the run ended on an exit
+    pub fn check_exit(&mut self, events: &mut Vec<GameEvent>) -> bool {
+        if !self.has_entity_at(self.player.position, EntityType::Exit) {
+            return false;
+        }
+
+        if let Some(link) = self.link_at(self.player.position).cloned() {
+            match self.travel(&link, events) {
+                Ok(()) => return false,
+                Err(e) => {
+                    warn!("Zone link at ({}, {}) is broken: {}", self.player.position.x, self.player.position.y, e);
+                    events.push(GameEvent::Message("The way on is impassable; you head out of the Zone instead.".to_string()));
+                }
+            }
+        }
+
+        if self.outcome.is_none() {
+            self.outcome = Some(RunOutcome::Extracted);
+            events.push(GameEvent::ReachedExit);
+            info!("Player reached exit at ({}, {})", self.player.position.x, self.player.position.y);
+        }
+        true
+    }
+
+    /// Takes the player through a linked Exit onto the link's entry tile
+    /// The zone they leave is kept as it is; the one they enter is picked up where they left it, or
+    /// loaded fresh with its spawn points rolled for this run. The player, turn and RNG carry on
+    fn travel(&mut self, link: &ZoneLink, events: &mut Vec<GameEvent>) -> Result<(), String> {
+        let entry = Position { x: link.entry_x as i32, y: link.entry_y as i32 };
+
+        // A link within the zone just moves the player
+        if link.map == self.zone {
+            check_entry(&self.zone_map(), entry)?;
+            self.player.position = entry;
+            self.refresh_fov();
+            return Ok(());
+        }
+
+        let arriving = match self.visited.get(&link.map) {
+            Some(zone) => zone.clone(),
+            None => {
+                let path = map_path(&self.maps_directory, &link.map)?;
+                let map = MapData::load_from_file(&path, &self.items).map_err(|e| format!("{}: {}", path, e))?;
+                let known = KnownMap::new(map.width, map.height);
+                VisitedZone { map, known, known_anomalies: KnownAnomalies::default() }
+            }
+        };
+        check_entry(&arriving.map, entry)?;
+        self.visited.remove(&link.map);
+
+        let leaving = VisitedZone {
+            map: self.zone_map(),
+            known: std::mem::replace(&mut self.known, arriving.known),
+            known_anomalies: std::mem::replace(&mut self.known_anomalies, arriving.known_anomalies),
+        };
+        let left = std::mem::replace(&mut self.zone, link.map.clone());
+        self.visited.insert(left, leaving);
+
+        let (entities, ground_items) = map_contents(&arriving.map, &self.items, zone_seed(self.rng.seed(), &link.map));
+        self.grid = arriving.map.to_game_grid();
+        self.entities = in_map_order(entities);
+        self.ground_items = merge_piles(ground_items, &self.items);
+        self.links = arriving.map.zone_links;
+        self.player.position = entry;
+        // Re-senses from the entry tile, measuring the arriving zone's way
+        self.set_map_movement(arriving.map.movement);
+
+        events.push(GameEvent::Message(format!("You make your way into {}.", link.map)));
+        events.push(GameEvent::EnteredZone(link.map.clone()));
+        info!("Player entered zone {} at ({}, {})", link.map, entry.x, entry.y);
+        Ok(())
+    }
+}
+
+/// The entities and ground items of a map, with its spawn points rolled from `seed`
+fn map_contents(
+    map_data: &MapData,
+    items: &ItemRegistry,
+    seed: u64,
+) -> (Vec<(Position, EntityType)>, Vec<(Position, GroundItems)>) {
+    let (rolled_entities, rolled_items) = resolve_spawn_points(&map_data.spawn_points, items, seed);
+    let entities = map_data
+        .entities
+        .iter()
+        .map(|placed| {
+            (
+                Position { x: placed.x as i32, y: placed.y as i32 },
+                placed.entity_type.into(),
+            )
+        })
+        .chain(rolled_entities)
+        .collect();
+
+    let ground_items = map_data
+        .items
+        .iter()
+        .map(|placed| {
+            (
+                Position { x: placed.x as i32, y: placed.y as i32 },
+                GroundItems { items: placed.items.clone() },
+            )
+        })
+        .chain(rolled_items)
+        .collect();
+
+    (entities, ground_items)
+}
+
+/// Keeps entities in map order rather than spawn order so runs resolve identically
+/// whether the map came from the editor or straight from a file
+fn in_map_order(mut entities: Vec<(Position, EntityType)>) -> Vec<(Position, EntityType)> {
+    entities.sort_by_key(|(pos, _)| (pos.y, pos.x));
+    entities
+}
+
+/// Merges duplicate tiles so every position has at most one item list
+fn merge_piles(ground_items: Vec<(Position, GroundItems)>, items: &ItemRegistry) -> HashMap<Position, GroundItems> {
+    let mut merged: HashMap<Position, GroundItems> = HashMap::new();
+    for (pos, pile_items) in ground_items {
+        if pile_items.is_empty() {
+            continue;
+        }
+        let pile = merged.entry(pos).or_default();
+        for item in pile_items.items {
+            pile.add_item(item, items);
+        }
+    }
+    merged
+}
+
+/// A zone link's entry tile must be open floor without an Exit, or the player would arrive
+/// inside a wall or be sent straight on again
+pub fn check_entry(map: &MapData, entry: Position) -> Result<(), String> {
+    let (x, y) = (entry.x as usize, entry.y as usize);
+    match map.to_game_grid().get_tile(x, y) {
+        None => return Err(format!("entry ({}, {}) is outside the map", x, y)),
+        Some(tile) if tile.kind == TileKind::Wall => return Err(format!("entry ({}, {}) is inside a wall", x, y)),
+        Some(_) => {}
+    }
+    let on_exit = map
+        .entities
+        .iter()
+        .any(|placed| (placed.x, placed.y) == (x, y) && EntityType::from(placed.entity_type) == EntityType::Exit);
+    if on_exit {
+        return Err(format!("entry ({}, {}) is on an Exit", x, y));
+    }
+    Ok(())
+}
+
+/// Seed a linked zone's spawn points roll from: the run seed mixed with the map's name, so each
+/// zone rolls its own layout and the same run always rolls the same one
+fn zone_seed(seed: u64, zone: &str) -> u64 {
+    const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+    zone.bytes().fold(seed, |hash, byte| (hash ^ byte as u64).wrapping_mul(FNV_PRIME))
+}
+
+/// Starting loadout for a fresh stalker (10 Bolts + Metal Detector)
+pub fn starting_inventory(items: &ItemRegistry) -> Inventory {
+    let mut inventory = Inventory::new();
+    for _ in 0..10 {
+        inventory.add_item(items.create(BOLT), items);
+    }
+    inventory.add_item(items.create(METAL_DETECTOR), items);
+    inventory
+}
+
+/// Order-independent FNV-1a fingerprint of a map's terrain, entities and ground items
+pub fn map_hash(
+    grid: &GameGrid,
+    entities: &[(Position, EntityType)],
+    ground_items: &HashMap<Position, GroundItems>,
+) -> u64 {
+    const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let mut hash = FNV_OFFSET;
+    let mut feed = |bytes: &[u8]| {
+        for byte in bytes {
+            hash ^= *byte as u64;
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+    };
+
+    feed(&(grid.width as u64).to_le_bytes());
+    feed(&(grid.height as u64).to_le_bytes());
+    for y in 0..grid.height {
+        for x in 0..grid.width {
+            let is_wall = grid.get_tile(x, y).is_some_and(|tile| tile.kind == TileKind::Wall);
+            feed(&[is_wall as u8]);
+        }
+    }
+
+    let mut placed: Vec<String> = entities
+        .iter()
+        .map(|(pos, entity_type)| format!("{},{}:{:?}", pos.x, pos.y, entity_type))
+        .collect();
+    placed.sort();
+
+    let mut piles: Vec<String> = ground_items
+        .iter()
+        .map(|(pos, items)| format!("{},{}:{:?}", pos.x, pos.y, items.items))
+        .collect();
+    piles.sort();
+
+    for line in placed.iter().chain(piles.iter()) {
+        feed(line.as_bytes());
+        feed(&[0]);
+    }
+
+    hash
+}
+
+/// Tiles an item carries when thrown: lighter items go further, up to `MAX_THROW_RANGE`
+pub fn throw_range(item: &Item) -> u32 {
+    (THROW_STRENGTH / item.weight.max(1)).clamp(1, MAX_THROW_RANGE)
+}
+
+/// What one unit of a valued item becomes in the Philosopher's Stone: a transmutable item of equal
+/// or lesser value, or (5% chance) a Fully Empty
+fn transmute(items: &ItemRegistry, rng: &mut RunRng, original: &Item) -> Item {
+    if rng.random_bool(0.05) {
+        return items.create(FULLY_EMPTY);
+    }
+
+    // Get transmutable items with value <= original value
+    let original_value = original.value.unwrap_or(0);
+    let eligible: Vec<_> = items
+        .with_tag(TAG_TRANSMUTABLE)
+        .filter(|definition| definition.value.is_some_and(|v| v <= original_value))
+        .collect();
+
+    match eligible.choose(rng) {
+        Some(definition) => definition.create(),
+        None => Item { quantity: 1, ..original.clone() },
+    }
+}
+
+pub fn is_anomaly(entity_type: EntityType) -> bool {
+    matches!(
+        entity_type,
+        EntityType::GravitationalAnomaly | EntityType::PhilosopherStone | EntityType::RustAnomaly
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::item_registry::ITEM_CATALOGUE_PATH;
+
+    fn pos(x: i32, y: i32) -> Position {
+        Position { x, y }
+    }
+
+    fn items() -> ItemRegistry {
+        ItemRegistry::load_from_file(ITEM_CATALOGUE_PATH).unwrap()
+    }
+
+    fn trace_bolt(sim: &ZoneSim, dx: i32, dy: i32) -> ThrowFlight {
+        sim.trace_throw(&items().create(BOLT), ThrowTarget::Direction { dx, dy }).unwrap()
+    }
+
+    fn thrown(events: &[GameEvent]) -> ThrowFlight {
+        events
+            .iter()
+            .find_map(|e| match e {
+                GameEvent::ItemThrown { flight, .. } => Some(flight.clone()),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    fn sim_with(entities: Vec<(Position, EntityType)>) -> ZoneSim {
+        let mut all = vec![(pos(2, 2), EntityType::PlayerStart)];
+        all.extend(entities);
+        ZoneSim::new(GameGrid::new_empty(7, 7), all, Vec::new(), CarryCapacity::default(), items(), 42).unwrap()
+    }
+
+    #[test]
+    fn test_requires_player_start() {
+        let result = ZoneSim::new(GameGrid::new_empty(5, 5), Vec::new(), Vec::new(), CarryCapacity::default(), items(), 42);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_move_advances_turn_and_walls_block() {
+        let mut sim = sim_with(Vec::new());
+        sim.grid.set_tile(3, 2, crate::resources::game_grid::Tile::new(TileKind::Wall));
+
+        let events = sim.apply(Action::Move { dx: 1, dy: 0 });
+        assert!(events.iter().any(|e| matches!(e, GameEvent::MoveBlocked(MoveBlocked::Wall))));
+        assert_eq!(sim.turn, 0);
+
+        sim.apply(Action::Move { dx: 0, dy: 1 });
+        assert_eq!(sim.player.position, pos(2, 3));
+        assert_eq!(sim.turn, 1);
+    }
+
+    #[test]
+    fn test_diagonal_moves_need_diagonal_mode_and_open_corners() {
+        let mut sim = sim_with(vec![(pos(2, 5), EntityType::GravitationalAnomaly), (pos(5, 5), EntityType::RustAnomaly)]);
+        sim.grid.set_tile(3, 2, crate::resources::game_grid::Tile::new(TileKind::Wall));
+
+        // Cardinal mode has no diagonal steps or throws
+        assert!(sim.apply(Action::Move { dx: -1, dy: 1 }).is_empty());
+        assert!(sim.apply(Action::ThrowBolt { dx: 1, dy: 1 }).is_empty());
+        assert_eq!(sim.player.position, pos(2, 2));
+
+        sim.set_movement_setting(MovementSetting(MovementMode::Diagonal));
+        assert_eq!(sim.to_replay().movement, MovementMode::Diagonal);
+
+        // A diagonal step can't squeeze past a wall corner, and neither can a bolt
+        let events = sim.apply(Action::Move { dx: 1, dy: 1 });
+        assert!(events.iter().any(|e| matches!(e, GameEvent::MoveBlocked(MoveBlocked::Wall))));
+        assert_eq!(trace_bolt(&sim, 1, 1).hit, ThrowHit::Wall);
+        sim.apply(Action::Move { dx: -1, dy: 1 });
+        assert_eq!((sim.player.position, sim.turn), (pos(1, 3), 1));
+
+        // Bolts fly diagonally
+        let flight = trace_bolt(&sim, 1, 1);
+        assert_eq!(flight.path, vec![pos(2, 4), pos(3, 5), pos(4, 6)]);
+
+        // A gravitational anomaly diagonally next to the player is one step away, and pulls
+        sim.apply(Action::Move { dx: 0, dy: 1 });
+        assert_eq!(sim.player.position, pos(2, 5));
+        assert!(sim.player.gravity_timer.is_some());
+    }
+
+    #[test]
+    fn test_gravity_pulls_and_crushes() {
+        let mut sim = sim_with(vec![(pos(4, 2), EntityType::GravitationalAnomaly)]);
+
+        // Step next to the anomaly, then get pulled onto it
+        sim.apply(Action::Move { dx: 1, dy: 0 });
+        assert_eq!(sim.player.position, pos(4, 2));
+        assert_eq!(sim.player.gravity_timer, Some(GRAVITY_TIMER_TURNS - 1));
+
+        // Waiting in place runs the timer out
+        while !sim.is_finished() {
+            sim.apply(Action::CloseUi);
+        }
+        assert_eq!(sim.outcome, Some(RunOutcome::Dead));
+    }
+
+    #[test]
+    fn test_bolt_stops_on_anomaly_and_lands_there() {
+        let mut sim = sim_with(vec![(pos(2, 5), EntityType::RustAnomaly)]);
+        let bolts_before = sim.player.inventory.count_of(BOLT);
+
+        let flight = thrown(&sim.apply(Action::ThrowBolt { dx: 0, dy: 1 }));
+
+        assert_eq!(flight.hit, ThrowHit::Anomaly(EntityType::RustAnomaly));
+        assert_eq!(flight.landing, pos(2, 5));
+        assert_eq!(sim.player.inventory.count_of(BOLT), bolts_before - 1);
+        assert_eq!(sim.ground_items_at(pos(2, 5)).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_throw_range_depends_on_weight() {
+        let items = items();
+        assert_eq!(throw_range(&items.create(BOLT)), MAX_THROW_RANGE);
+        assert_eq!(throw_range(&items.create("glass_jar")), 3);
+        assert_eq!(throw_range(&items.create("scrap")), 1);
+        assert_eq!(throw_range(&items.create(METAL_DETECTOR)), 1);
+
+        // Thrown at a tile, an item follows a straight line made of the steps the mode allows
+        assert_eq!(line_steps(pos(0, 0), pos(2, 1), MovementMode::Diagonal), vec![(1, 0), (1, 1)]);
+        assert_eq!(line_steps(pos(0, 0), pos(2, 1), MovementMode::Cardinal), vec![(1, 0), (1, 0), (0, 1)]);
+
+        let mut sim = sim_with(Vec::new());
+        sim.player.inventory.add_item(items.create("glass_jar"), &items);
+        let jar = sim.player.inventory.find("glass_jar").unwrap();
+
+        // Too far: nothing is thrown and the turn isn't used
+        sim.apply(Action::ThrowItem { index: jar, target: ThrowTarget::Tile { x: 2, y: 6 } });
+        assert_eq!((sim.turn, sim.player.inventory.count_of("glass_jar")), (0, 1));
+
+        let flight = thrown(&sim.apply(Action::ThrowItem { index: jar, target: ThrowTarget::Tile { x: 3, y: 4 } }));
+        assert_eq!((flight.landing, flight.hit), (pos(3, 4), ThrowHit::MaxRange));
+        assert_eq!(sim.ground_items_at(pos(3, 4)).unwrap().items[0].id.as_str(), "glass_jar");
+        assert_eq!(sim.turn, 1);
+    }
+
+    #[test]
+    fn test_thrown_items_meet_anomalies_where_they_land() {
+        let mut sim = sim_with(vec![
+            (pos(2, 3), EntityType::RustAnomaly),
+            (pos(1, 1), EntityType::GravitationalAnomaly),
+            (pos(4, 2), EntityType::PhilosopherStone),
+        ]);
+        sim.player.inventory.add_item(sim.items.create("scrap"), &sim.items);
+        sim.player.inventory.add_item(sim.items.create("scrap"), &sim.items);
+        sim.player.inventory.add_item(sim.items.create("glass_jar"), &sim.items);
+
+        // Scrap rusts on the Rust
+        let scrap = sim.player.inventory.find("scrap").unwrap();
+        let events = sim.apply(Action::ThrowItem { index: scrap, target: ThrowTarget::Direction { dx: 0, dy: 1 } });
+        assert!(events.iter().any(|e| matches!(e, GameEvent::ItemRusted { in_inventory: false, .. })));
+        assert_eq!(sim.ground_items_at(pos(2, 3)).unwrap().items[0].id.as_str(), RUST_SLAG);
+
+        // Heavy scrap flying past gravity is dragged in; a bolt flies on
+        let scrap = sim.player.inventory.find("scrap").unwrap();
+        let flight = thrown(&sim.apply(Action::ThrowItem { index: scrap, target: ThrowTarget::Direction { dx: -1, dy: 0 } }));
+        assert_eq!((flight.landing, flight.hit), (pos(1, 1), ThrowHit::Pulled));
+        assert_eq!(sim.ground_items_at(pos(1, 1)).unwrap().items[0].id.as_str(), "scrap");
+        assert_eq!(thrown(&sim.apply(Action::ThrowBolt { dx: -1, dy: 0 })).landing, pos(0, 2));
+
+        // Valued items are transmuted by the Philosopher's Stone
+        let jar = sim.player.inventory.find("glass_jar").unwrap();
+        let events = sim.apply(Action::ThrowItem { index: jar, target: ThrowTarget::Tile { x: 4, y: 2 } });
+        assert!(events.iter().any(|e| matches!(e, GameEvent::ItemTransmuted { .. })));
+        assert_eq!(sim.ground_items_at(pos(4, 2)).unwrap().count(), 1);
+        assert_eq!(sim.player.inventory.count_of("glass_jar"), 0);
+
+        // Diagonally, gravity doesn't drag heavy items around a wall corner
+        let mut cornered = sim_with(vec![(pos(3, 4), EntityType::GravitationalAnomaly)]);
+        cornered.set_movement_setting(MovementSetting(MovementMode::Diagonal));
+        let scrap = items().create("scrap");
+        let flight = cornered.trace_throw(&scrap, ThrowTarget::Direction { dx: 0, dy: 1 }).unwrap();
+        assert_eq!((flight.landing, flight.hit), (pos(3, 4), ThrowHit::Pulled));
+        cornered.grid.set_tile(2, 4, crate::resources::game_grid::Tile::new(TileKind::Wall));
+        let flight = cornered.trace_throw(&scrap, ThrowTarget::Direction { dx: 0, dy: 1 }).unwrap();
+        assert_eq!((flight.landing, flight.hit), (pos(2, 3), ThrowHit::MaxRange));
+    }
+
+    #[test]
+    fn test_renamed_items_keep_working() {
+        let renamed = items()
+            .iter()
+            .cloned()
+            .map(|mut definition| {
+                definition.name = format!("Renamed {}", definition.name);
+                definition
+            })
+            .collect();
+        let entities = vec![(pos(2, 2), EntityType::PlayerStart)];
+        let mut sim = ZoneSim::new(
+            GameGrid::new_empty(5, 5),
+            entities,
+            Vec::new(),
+            CarryCapacity::default(),
+            ItemRegistry::new(renamed).unwrap(),
+            42,
+        )
+        .unwrap();
+
+        assert!(sim.player.inventory.has_metal_detector());
+        let events = sim.apply(Action::ThrowBolt { dx: 1, dy: 0 });
+        assert!(events.iter().any(|e| matches!(e, GameEvent::ItemThrown { .. })));
+    }
+
+    #[test]
+    fn test_pickup_and_drop_do_not_end_turn() {
+        let mut sim = sim_with(Vec::new());
+        let start = sim.player.position;
+
+        sim.apply(Action::Drop { index: 0, quantity: 1 });
+        assert_eq!(sim.ground_items_at(start).unwrap().count(), 1);
+
+        sim.apply(Action::Pickup { index: 0, quantity: 1 });
+        assert!(sim.ground_items_at(start).is_none());
+        assert_eq!(sim.turn, 0);
+    }
+
+    #[test]
+    fn test_dropping_and_taking_part_of_a_stack() {
+        let mut sim = sim_with(Vec::new());
+        let start = sim.player.position;
+        let bolts = sim.player.inventory.find(BOLT).unwrap();
+        let weight_before = sim.player.inventory.total_weight();
+
+        sim.apply(Action::Drop { index: bolts, quantity: 4 });
+        assert_eq!(sim.player.inventory.count_of(BOLT), 6);
+        assert_eq!(sim.ground_items_at(start).unwrap().items[0].quantity, 4);
+        assert_eq!(sim.player.inventory.total_weight(), weight_before - 4);
+
+        sim.apply(Action::Pickup { index: 0, quantity: 3 });
+        assert_eq!(sim.player.inventory.count_of(BOLT), 9);
+        assert_eq!(sim.ground_items_at(start).unwrap().items[0].quantity, 1);
+        assert_eq!(sim.player.inventory.count(), 2); // the bolts went back onto their stack
+    }
+
+    #[test]
+    fn test_same_seed_replays_anomaly_rolls() {
+        let run = |seed: u64| {
+            let entities = vec![
+                (pos(2, 2), EntityType::PlayerStart),
+                (pos(2, 2), EntityType::PhilosopherStone),
+            ];
+            let mut pile = GroundItems::new();
+            for _ in 0..3 {
+                pile.add_item(items().create("scrap"), &items());
+            }
+            let mut sim = ZoneSim::new(
+                GameGrid::new_empty(5, 5),
+                entities,
+                vec![(pos(2, 2), pile)],
+                CarryCapacity::default(),
+                items(),
+                seed,
+            )
+            .unwrap();
+
+            let mut messages = Vec::new();
+            for _ in 0..10 {
+                for event in sim.apply(Action::CloseUi) {
+                    if let GameEvent::Message(message) = event {
+                        messages.push(message);
+                    }
+                }
+            }
+            (messages, sim.ground_items_at(pos(2, 2)).cloned())
+        };
+
+        assert_eq!(run(7), run(7));
+    }
+
+    #[test]
+    fn test_anomalies_stay_hidden_until_revealed() {
+        let mut sim = sim_with(vec![
+            (pos(2, 6), EntityType::RustAnomaly),
+            (pos(5, 2), EntityType::GravitationalAnomaly),
+        ]);
+        assert_eq!(sim.known.visibility(pos(2, 6)), crate::sim::fog::TileVisibility::Visible);
+        assert!(sim.known_anomalies.is_empty());
+
+        // A bolt tells what kind of anomaly it hit
+        sim.apply(Action::ThrowBolt { dx: 0, dy: 1 });
+        assert_eq!(sim.known_anomalies.clue_at(pos(2, 6)), Some(AnomalyClue::Corrosive));
+
+        // The detector only senses that something is there
+        sim.apply(Action::Move { dx: 1, dy: 0 });
+        assert_eq!(sim.known_anomalies.clue_at(pos(5, 2)), Some(AnomalyClue::Unknown));
+
+        // Being pulled in settles it
+        sim.apply(Action::Move { dx: 1, dy: 0 });
+        assert_eq!(sim.known_anomalies.clue_at(pos(5, 2)), Some(AnomalyClue::Gravitational));
+    }
+
+    #[test]
+    fn test_reaching_exit_extracts() {
+        let mut sim = sim_with(vec![(pos(2, 3), EntityType::Exit)]);
+        let events = sim.apply(Action::Move { dx: 0, dy: 1 });
+        assert!(events.iter().any(|e| matches!(e, GameEvent::ReachedExit)));
+        assert_eq!(sim.outcome, Some(RunOutcome::Extracted));
+    }
+
+    #[test]
+    fn test_each_zone_moves_its_own_way() {
+        let directory = std::env::temp_dir().join("picnic_zone_movement_test");
+        let directory = directory.to_string_lossy().into_owned();
+        let link = |x: usize, y: usize, map: &str, entry_x: usize, entry_y: usize| PlacedZoneLink {
+            x,
+            y,
+            link: ZoneLink { map: map.to_string(), entry_x, entry_y },
+        };
+
+        // A Cardinal zone leads to a Diagonal one, which leads on to one that leaves it to the setting
+        let mut first = MapData::from_game_state(
+            &GameGrid::new_empty(5, 5),
+            &[(EntityType::PlayerStart, 2, 2), (EntityType::Exit, 2, 3)],
+            &[],
+        );
+        first.movement = Some(MovementMode::Cardinal);
+        first.zone_links = vec![link(2, 3, "diagonal", 1, 1)];
+        let mut diagonal = MapData::from_game_state(&GameGrid::new_empty(5, 5), &[(EntityType::Exit, 2, 2)], &[]);
+        diagonal.movement = Some(MovementMode::Diagonal);
+        diagonal.zone_links = vec![link(2, 2, "unset", 1, 1)];
+        let unset = MapData::from_game_state(&GameGrid::new_empty(5, 5), &[], &[]);
+        diagonal.save_to_file(&map_path(&directory, "diagonal").unwrap()).unwrap();
+        unset.save_to_file(&map_path(&directory, "unset").unwrap()).unwrap();
+
+        let mut sim = ZoneSim::from_map_data(&first, items(), 42).unwrap();
+        sim.zone = "first".to_string();
+        sim.maps_directory = directory;
+        assert_eq!(sim.movement, MovementMode::Cardinal);
+        assert!(!sim.apply(Action::Move { dx: 1, dy: 1 }).iter().any(|e| matches!(e, GameEvent::PlayerMoved { .. })));
+
+        sim.apply(Action::Move { dx: 0, dy: 1 });
+        assert_eq!((sim.zone.as_str(), sim.movement), ("diagonal", MovementMode::Diagonal));
+
+        // The zone left behind keeps the mode its author chose
+        assert_eq!(sim.visited["first"].map.movement, Some(MovementMode::Cardinal));
+
+        sim.apply(Action::Move { dx: 1, dy: 1 });
+        assert_eq!((sim.zone.as_str(), sim.movement), ("unset", MovementMode::Cardinal));
+        assert_eq!(sim.visited["diagonal"].map.movement, Some(MovementMode::Diagonal));
+        assert_eq!(sim.zone_map().movement, None);
+    }
+
+    #[test]
+    fn test_linked_exits_travel_between_zones() {
+        let directory = std::env::temp_dir().join("picnic_zone_links_test");
+        let directory = directory.to_string_lossy().into_owned();
+        let link = |x: usize, y: usize, map: &str, entry_x: usize, entry_y: usize| PlacedZoneLink {
+            x,
+            y,
+            link: ZoneLink { map: map.to_string(), entry_x, entry_y },
+        };
+
+        // "first" leads down to "second", whose exit leads back up; "first" also has a way to nowhere
+        let mut first = MapData::from_game_state(
+            &GameGrid::new_empty(7, 7),
+            &[(EntityType::PlayerStart, 2, 2), (EntityType::Exit, 2, 4), (EntityType::Exit, 4, 2)],
+            &[],
+        );
+        first.zone_links = vec![link(2, 4, "second", 1, 1), link(4, 2, "missing", 1, 1)];
+        let mut second = MapData::from_game_state(&GameGrid::new_empty(5, 5), &[(EntityType::Exit, 3, 1)], &[]);
+        second.zone_links = vec![link(3, 1, "first", 2, 3)];
+        second.save_to_file(&map_path(&directory, "second").unwrap()).unwrap();
+
+        let mut sim = ZoneSim::from_map_data(&first, items(), 42).unwrap();
+        sim.zone = "first".to_string();
+        sim.maps_directory = directory;
+
+        // Leave a bolt behind, then take the linked exit
+        let bolts = sim.player.inventory.find(BOLT).unwrap();
+        sim.apply(Action::Drop { index: bolts, quantity: 1 });
+        let carried = sim.player.inventory.count_of(BOLT);
+        sim.apply(Action::Move { dx: 0, dy: 1 });
+        let events = sim.apply(Action::Move { dx: 0, dy: 1 });
+        assert!(events.iter().any(|e| matches!(e, GameEvent::EnteredZone(zone) if zone == "second")));
+        assert_eq!(sim.outcome, None);
+        assert_eq!((sim.zone.as_str(), sim.player.position, sim.turn), ("second", pos(1, 1), 2));
+        assert_eq!((sim.grid.width, sim.grid.height), (5, 5));
+        assert_eq!(sim.player.inventory.count_of(BOLT), carried);
+
+        // Coming back finds the zone as it was left
+        sim.apply(Action::Move { dx: 1, dy: 0 });
+        sim.apply(Action::Move { dx: 1, dy: 0 });
+        assert_eq!((sim.zone.as_str(), sim.player.position, sim.turn), ("first", pos(2, 3), 4));
+        assert_eq!(sim.ground_items_at(pos(2, 2)).unwrap().items[0].id.as_str(), BOLT);
+        assert!(sim.visited.contains_key("second"));
+
+        // A link to a map that isn't there ends the run like any exit
+        sim.player.position = pos(3, 2);
+        let events = sim.apply(Action::Move { dx: 1, dy: 0 });
+        assert!(events.iter().any(|e| matches!(e, GameEvent::ReachedExit)));
+        assert_eq!(sim.outcome, Some(RunOutcome::Extracted));
+    }
+}
@@ -0,0 +1,107 @@
+use bevy::prelude::*;
+use crate::components::components::Position;
+use crate::resources::{
+    game_grid::EntityType,
+    game_state::GameState,
+    debug::DebugReveal,
+    message_log::MessageLog,
+    keybindings::{InputAction, Keybindings},
+};
+use crate::sim::{fog::TileVisibility, zone_sim::{is_anomaly, ZoneSim}};
+use crate::systems::fog::visibility_for;
+use crate::systems::rendering::{clue_color, get_entity_color};
+use crate::constants::TILE_SIZE;
+
+/// Text label floating over a discovered anomaly, saying what the player learned about it
+#[derive(Component)]
+pub struct AnomalyLabel;
+
+/// The DebugReveal key (F12) during a run: toggle the debug reveal
+pub fn toggle_debug_reveal_system(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    mut debug_reveal: ResMut<DebugReveal>,
+    mut message_log: ResMut<MessageLog>,
+) {
+    if keybindings.just_pressed(&keyboard, InputAction::DebugReveal) {
+        debug_reveal.0 = !debug_reveal.0;
+        message_log.add_message(if debug_reveal.0 {
+            "[Debug] Revealing the whole Zone."
+        } else {
+            "[Debug] Reveal off."
+        });
+    }
+}
+
+/// Shows only the anomalies the player has discovered, colored and labelled by what they learned
+/// With the debug reveal on, every anomaly shows in its true editor color and name
+pub fn update_anomaly_markers_system(
+    mut commands: Commands,
+    sim: Res<ZoneSim>,
+    debug_reveal: Res<DebugReveal>,
+    mut anomaly_query: Query<(Entity, &Position, &EntityType, &mut Sprite, &mut Visibility, Option<&Children>)>,
+    mut label_query: Query<&mut Text2d, With<AnomalyLabel>>,
+) {
+    for (entity, pos, entity_type, mut sprite, mut visibility, children) in anomaly_query.iter_mut() {
+        if !is_anomaly(*entity_type) {
+            continue;
+        }
+
+        let clue = sim.known_anomalies.clue_at(*pos);
+        let seen = sim.known.visibility(*pos) != TileVisibility::Unseen;
+
+        let (shown, color, label) = if debug_reveal.0 {
+            (true, get_entity_color(*entity_type, &GameState::Editing), format!("{:?}", entity_type))
+        } else if let Some(clue) = clue {
+            (seen, clue_color(clue), clue.label().to_string())
+        } else {
+            (false, sprite.color, String::new())
+        };
+
+        visibility.set_if_neq(visibility_for(shown));
+        if sprite.color != color {
+            sprite.color = color;
+        }
+
+        // Labels are children, so they share the anomaly's visibility
+        let existing_label = children
+            .into_iter()
+            .flatten()
+            .find(|child| label_query.contains(**child));
+        match existing_label {
+            Some(label_entity) => {
+                let Ok(mut text) = label_query.get_mut(*label_entity) else {
+                    continue;
+                };
+                if text.0 != label {
+                    text.0 = label;
+                }
+            }
+            None if !label.is_empty() => {
+                commands.entity(entity).with_child((
+                    Text2d::new(label),
+                    TextFont {
+                        font_size: 10.0,
+                        ..default()
+                    },
+                    TextColor(Color::WHITE),
+                    Transform::from_xyz(0.0, TILE_SIZE * 0.55, 1.0),
+                    AnomalyLabel,
+                ));
+            }
+            None => {}
+        }
+    }
+}
+
+/// Removes anomaly labels and the debug reveal when leaving Running mode
+pub fn despawn_anomaly_labels_system(
+    mut commands: Commands,
+    label_query: Query<Entity, With<AnomalyLabel>>,
+    mut debug_reveal: ResMut<DebugReveal>,
+) {
+    for entity in label_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    debug_reveal.0 = false;
+}
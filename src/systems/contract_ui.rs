@@ -1,15 +1,45 @@
 use bevy::prelude::*;
 use crate::components::{
-    components::{Player, Position},
+    components::Player,
     inventory::Inventory,
 };
 use crate::resources::{
     turn_state::TurnPhase,
-    contract_system::{ContractSystem, ContractStatus},
+    contract_system::ContractSystem,
     game_state::GameState,
     turn_state::TurnCounter,
     message_log::MessageLog,
+    seed::{WorldSeed, SeedOverride},
+    keybindings::InputAction,
+    action_input::ActionInput,
+    editor_state::EditorMapFile,
+    zone_links::ZoneLink,
 };
+use crate::sim::zone_sim::ZoneSim;
+use crate::systems::editor::unnamed_linked_map;
+use crate::systems::setup::next_run_seed;
+use crate::systems::run_save::PendingResume;
+
+// ============================================================================
+// PROMPTS
+// ============================================================================
+
+/// A "<Confirm key> - what it does" prompt on one of these screens; holds what it does
+#[derive(Component)]
+pub struct ConfirmPrompt(pub &'static str);
+
+/// Keeps the Confirm prompts on the device in use ("E - Exit the Zone" or "A - Exit the Zone")
+pub fn update_confirm_prompts_system(
+    input: ActionInput,
+    mut prompt_query: Query<(&ConfirmPrompt, &mut Text)>,
+) {
+    for (prompt, mut text) in prompt_query.iter_mut() {
+        let current = format!("{} - {}", input.label(InputAction::Confirm), prompt.0);
+        if text.0 != current {
+            text.0 = current;
+        }
+    }
+}
 
 // ============================================================================
 // ENTER THE ZONE SCREEN
@@ -23,6 +53,7 @@ pub struct EnterZoneUiRoot;
 pub fn spawn_enter_zone_ui_system(
     mut commands: Commands,
     contract_system: Res<ContractSystem>,
+    input: ActionInput,
     existing_ui: Query<Entity, With<EnterZoneUiRoot>>,
 ) {
     // Don't spawn if UI already exists
@@ -110,12 +141,13 @@ pub fn spawn_enter_zone_ui_system(
 
                     // Help text
                     parent.spawn((
-                        Text::new("E - Accept and Enter the Zone"),
+                        Text::new(format!("{} - Accept and Enter the Zone", input.label(InputAction::Confirm))),
                         TextFont {
                             font_size: 16.0,
                             ..default()
                         },
                         TextColor(Color::srgb(0.6, 0.9, 0.6)),
+                        ConfirmPrompt("Accept and Enter the Zone"),
                     ));
                 });
         });
@@ -127,16 +159,16 @@ pub fn despawn_enter_zone_ui_system(
     ui_query: Query<Entity, With<EnterZoneUiRoot>>,
 ) {
     for entity in ui_query.iter() {
-        commands.entity(entity).despawn_recursive();
+        commands.entity(entity).despawn();
     }
 }
 
-/// Handles E key to close Enter Zone UI and start game
+/// Handles the Confirm key (E, or A on a gamepad) to close Enter Zone UI and start game
 pub fn close_enter_zone_ui_system(
-    keyboard: Res<ButtonInput<KeyCode>>,
+    input: ActionInput,
     mut next_phase: ResMut<NextState<TurnPhase>>,
 ) {
-    if keyboard.just_pressed(KeyCode::KeyE) {
+    if input.just_pressed(InputAction::Confirm) {
         next_phase.set(TurnPhase::PlayerTurn);
     }
 }
@@ -155,6 +187,8 @@ pub fn spawn_exit_zone_ui_system(
     mut contract_system: ResMut<ContractSystem>,
     player_query: Query<&Inventory, With<Player>>,
     existing_ui: Query<Entity, With<ExitZoneUiRoot>>,
+    world_seed: Res<WorldSeed>,
+    input: ActionInput,
 ) {
     // Don't spawn if UI already exists
     if existing_ui.iter().next().is_some() {
@@ -268,14 +302,25 @@ pub fn spawn_exit_zone_ui_system(
                         BackgroundColor(Color::srgb(0.5, 0.5, 0.5)),
                     ));
 
+                    // Seed, so the run can be reproduced
+                    parent.spawn((
+                        Text::new(format!("Seed: {}", world_seed.0)),
+                        TextFont {
+                            font_size: 16.0,
+                            ..default()
+                        },
+                        TextColor(Color::srgb(0.6, 0.6, 0.6)),
+                    ));
+
                     // Help text
                     parent.spawn((
-                        Text::new("E - Exit the Zone"),
+                        Text::new(format!("{} - Exit the Zone", input.label(InputAction::Confirm))),
                         TextFont {
                             font_size: 16.0,
                             ..default()
                         },
                         TextColor(Color::srgb(0.6, 0.9, 0.6)),
+                        ConfirmPrompt("Exit the Zone"),
                     ));
                 });
         });
@@ -287,16 +332,16 @@ pub fn despawn_exit_zone_ui_system(
     ui_query: Query<Entity, With<ExitZoneUiRoot>>,
 ) {
     for entity in ui_query.iter() {
-        commands.entity(entity).despawn_recursive();
+        commands.entity(entity).despawn();
     }
 }
 
-/// Handles E key to exit zone and reset game
+/// Handles the Confirm key (E, or A on a gamepad) to exit zone and reset game
 pub fn close_exit_zone_ui_system(
-    keyboard: Res<ButtonInput<KeyCode>>,
+    input: ActionInput,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
-    if keyboard.just_pressed(KeyCode::KeyE) {
+    if input.just_pressed(InputAction::Confirm) {
         // Transition to Editing which will trigger reset and then back to Running
         next_state.set(GameState::Editing);
     }
@@ -314,6 +359,8 @@ pub struct DeathUiRoot;
 pub fn spawn_death_ui_system(
     mut commands: Commands,
     existing_ui: Query<Entity, With<DeathUiRoot>>,
+    world_seed: Res<WorldSeed>,
+    input: ActionInput,
 ) {
     // Don't spawn if UI already exists
     if existing_ui.iter().next().is_some() {
@@ -376,6 +423,16 @@ pub fn spawn_death_ui_system(
                         },
                     ));
 
+                    // Seed, so the run can be reproduced
+                    parent.spawn((
+                        Text::new(format!("Seed: {}", world_seed.0)),
+                        TextFont {
+                            font_size: 16.0,
+                            ..default()
+                        },
+                        TextColor(Color::srgb(0.6, 0.6, 0.6)),
+                    ));
+
                     // Separator
                     parent.spawn((
                         Node {
@@ -389,12 +446,13 @@ pub fn spawn_death_ui_system(
 
                     // Help text
                     parent.spawn((
-                        Text::new("E - Restart with a new Stalker"),
+                        Text::new(format!("{} - Restart with a new Stalker", input.label(InputAction::Confirm))),
                         TextFont {
                             font_size: 16.0,
                             ..default()
                         },
                         TextColor(Color::srgb(0.6, 0.9, 0.6)),
+                        ConfirmPrompt("Restart with a new Stalker"),
                     ));
                 });
         });
@@ -406,16 +464,16 @@ pub fn despawn_death_ui_system(
     ui_query: Query<Entity, With<DeathUiRoot>>,
 ) {
     for entity in ui_query.iter() {
-        commands.entity(entity).despawn_recursive();
+        commands.entity(entity).despawn();
     }
 }
 
-/// Handles E key to restart after death
+/// Handles the Confirm key (E, or A on a gamepad) to restart after death
 pub fn close_death_ui_system(
-    keyboard: Res<ButtonInput<KeyCode>>,
+    input: ActionInput,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
-    if keyboard.just_pressed(KeyCode::KeyE) {
+    if input.just_pressed(InputAction::Confirm) {
         // Transition to Editing which will trigger reset and then back to Running
         next_state.set(GameState::Editing);
     }
@@ -426,26 +484,17 @@ pub fn close_death_ui_system(
 // ============================================================================
 
 /// Detects when player steps on an exit tile and transitions to ExitingZone phase
+/// (a linked exit takes the player on to the next zone instead)
 pub fn detect_exit_system(
-    player_query: Query<&Position, With<Player>>,
-    exit_query: Query<(&Position, &crate::resources::game_grid::EntityType)>,
+    mut sim: ResMut<ZoneSim>,
     mut next_phase: ResMut<NextState<TurnPhase>>,
+    mut message_log: ResMut<MessageLog>,
 ) {
-    let Ok(player_pos) = player_query.single() else {
-        return;
-    };
-
-    // Check if player is on an exit tile
-    for (exit_pos, entity_type) in exit_query.iter() {
-        if matches!(entity_type, crate::resources::game_grid::EntityType::Exit)
-            && player_pos.x == exit_pos.x
-            && player_pos.y == exit_pos.y
-        {
-            next_phase.set(TurnPhase::ExitingZone);
-            info!("Player reached exit at ({}, {})", exit_pos.x, exit_pos.y);
-            return;
-        }
+    let mut events = Vec::new();
+    if sim.check_exit(&mut events) {
+        next_phase.set(TurnPhase::ExitingZone);
     }
+    message_log.log_events(&events);
 }
 
 // ============================================================================
@@ -465,32 +514,53 @@ pub fn prepare_restart_system(
     mut contract_system: ResMut<ContractSystem>,
     mut turn_counter: ResMut<TurnCounter>,
     mut message_log: ResMut<MessageLog>,
+    mut world_seed: ResMut<WorldSeed>,
+    seed_override: Res<SeedOverride>,
 ) {
     // Reset game state
     contract_system.reset();
     turn_counter.0 = 0;
     message_log.clear();
 
+    // Each run gets its own seed unless one was fixed on the command line
+    world_seed.0 = next_run_seed(&seed_override);
+
     // Set flag to restart
     auto_restart.should_restart = true;
     info!("Game state reset, preparing to restart");
 }
 
 /// System that runs in Editing mode and auto-restarts if flag is set
+/// A resumed run's zone is an unsaved map, so with zone links it stays in the editor until it's saved
 pub fn auto_restart_system(
     mut auto_restart: ResMut<AutoRestartFlag>,
     mut next_state: ResMut<NextState<GameState>>,
+    mut map_file: ResMut<EditorMapFile>,
+    link_query: Query<(), With<ZoneLink>>,
 ) {
     if auto_restart.should_restart {
         auto_restart.should_restart = false;
+        if let Some(problem) = unnamed_linked_map(&map_file, !link_query.is_empty()) {
+            map_file.status = Some(problem);
+            return;
+        }
         next_state.set(GameState::Running);
         info!("Auto-restarting game");
     }
 }
 
 /// Modifies the player spawn system to set EnteringZone phase instead of PlayerTurn
+/// A resumed run skips the briefing and goes straight back to the player's turn
 pub fn set_entering_zone_phase_system(
+    mut commands: Commands,
     mut next_phase: ResMut<NextState<TurnPhase>>,
+    resume: Option<Res<PendingResume>>,
 ) {
+    if resume.is_some() {
+        commands.remove_resource::<PendingResume>();
+        next_phase.set(TurnPhase::PlayerTurn);
+        return;
+    }
+
     next_phase.set(TurnPhase::EnteringZone);
 }
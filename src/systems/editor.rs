@@ -1,15 +1,34 @@
 use bevy::prelude::*;
+use bevy::input::{keyboard::{Key, KeyboardInput}, ButtonState};
 use crate::resources::{
     game_state::GameState,
-    editor_state::{EditorState, EditorMode, EditorCursor},
-    game_grid::{GameGrid, TileKind, EntityType, ItemType, Tile},
-    map_data::MapData,
+    editor_state::{EditorState, EditorMode, EditorCursor, AuthoredMap, EditorMapFile, MapDialog, MapSettings, StartupMap},
+    game_grid::{GameGrid, TileKind, EntityType, Tile},
+    item_registry::ItemRegistry,
+    map_data::{MapData, PlacedSpawnPoint, PlacedZoneLink, map_path, map_name, map_directory, list_maps},
+    edit_history::{EditHistory, EditChange},
+    editor_tools::{EditorTool, ToolDrag, MapRegion, bounds, flood_fill, rectangle, shape_tiles},
+    map_validation::{MapIssues, MapIssue, IssueSeverity, validate_map},
+    camera::CameraPosition,
+    seed::SeedOverride,
+    zone_gen::{ZoneGenParams, generate_zone},
+    spawn_points::{SpawnPoint, spawn_presets},
+    zone_links::ZoneLink,
+    keybindings::{InputAction, Keybindings},
 };
-use crate::components::components::Position;
-use crate::constants::TILE_SIZE;
-use crate::systems::rendering::{grid_to_world, spawn_placed_entity};
+use std::collections::HashMap;
+use crate::components::{components::Position, item::{GroundItems, Item}};
+use crate::constants::{
+    TILE_SIZE, MAPS_DIRECTORY, MAP_NAME_MAX_LENGTH, MIN_MAP_SIZE, MAX_MAP_SIZE, MAX_ISSUE_ROWS,
+    KEYBINDINGS_PATH, KEYBINDING_ROWS,
+};
+use crate::systems::rendering::{grid_to_world, spawn_placed_entity, spawn_spawn_point_marker, spawn_zone_link_marker};
+use crate::systems::setup::next_run_seed;
+use crate::sim::{movement::{MovementMode, MovementSetting}, zone_sim::ZoneSim};
+use crate::systems::replay::ReplayPlayback;
+use crate::systems::run_save::PendingResume;
 
-const MAP_FILE_PATH: &str = "assets/maps/current.json";
+pub const MAP_FILE_PATH: &str = "assets/maps/current.json";
 
 // Marker component for cursor highlight sprite
 #[derive(Component)]
@@ -28,41 +47,91 @@ pub struct EditorSelectionText;
 #[derive(Component)]
 pub struct EditorCursorText;
 
-// Toggle between Running and Editing states with F2
+#[derive(Component)]
+pub struct EditorFileText;
+
+#[derive(Component)]
+pub struct EditorStatusText;
+
+#[derive(Component)]
+pub struct EditorToolText;
+
+// Marker component for the tiles a tool is about to change
+#[derive(Component)]
+pub struct ToolPreview;
+
+// Marker component for the map issues panel, and one per listed issue (index into MapIssues)
+#[derive(Component)]
+pub struct MapIssuesPanel;
+
+#[derive(Component)]
+pub struct MapIssueRow(pub usize);
+
+// Marker components for the map dialog
+#[derive(Component)]
+pub struct MapDialogPanel;
+
+#[derive(Component)]
+pub struct MapDialogText;
+
+// Toggle between Running and Editing states with the ToggleMode key (F2)
+// A map with errors in the issues panel can't be played, nor can an unsaved map with zone links
 pub fn editor_toggle_system(
     keyboard: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
     current_state: Res<State<GameState>>,
     mut next_state: ResMut<NextState<GameState>>,
+    issues: Res<MapIssues>,
+    mut map_file: ResMut<EditorMapFile>,
+    link_query: Query<(), With<ZoneLink>>,
 ) {
-    if keyboard.just_pressed(KeyCode::F2) {
+    if keybindings.just_pressed(&keyboard, InputAction::ToggleMode) {
         match current_state.get() {
             GameState::Running => {
                 next_state.set(GameState::Editing);
             }
             GameState::Editing => {
+                let errors = issues.error_count();
+                if errors > 0 {
+                    map_file.status = Some(format!("Can't play: the map has {} errors (see MAP ISSUES)", errors));
+                    return;
+                }
+                if let Some(problem) = unnamed_linked_map(&map_file, !link_query.is_empty()) {
+                    map_file.status = Some(problem);
+                    return;
+                }
                 next_state.set(GameState::Running);
             }
         }
     }
 }
 
-// Switch between Terrain, Entity, and Item placement modes with Tab
+// Zone links come back to a map by its name, so the run keeps the start zone's changes under that name;
+// a map that was never saved has none, and a link back would load the file instead of the zone as it was left
+pub fn unnamed_linked_map(map_file: &EditorMapFile, has_links: bool) -> Option<String> {
+    (map_file.path.is_none() && has_links).then(|| "Can't play: save the map first, its zone links need a name to lead back to".to_string())
+}
+
+// Switch between Terrain, Entity, Item and Spawn placement modes with the EditorMode key (Tab)
 pub fn editor_mode_toggle_system(
     keyboard: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
     mut editor_state: ResMut<EditorState>,
 ) {
-    if keyboard.just_pressed(KeyCode::Tab) {
+    if keybindings.just_pressed(&keyboard, InputAction::EditorMode) {
         editor_state.mode = match editor_state.mode {
             EditorMode::Terrain => EditorMode::Entity,
             EditorMode::Entity => EditorMode::Item,
-            EditorMode::Item => EditorMode::Terrain,
+            EditorMode::Item => EditorMode::Spawn,
+            EditorMode::Spawn => EditorMode::Terrain,
         };
     }
 }
 
-// Select terrain, entity, or item type with number keys (mode-dependent)
+// Select terrain, entity, item or spawn point type with number keys (mode-dependent)
 pub fn editor_selection_system(
     keyboard: Res<ButtonInput<KeyCode>>,
+    items: Res<ItemRegistry>,
     mut editor_state: ResMut<EditorState>,
 ) {
     match editor_state.mode {
@@ -89,20 +158,27 @@ pub fn editor_selection_system(
             }
         }
         EditorMode::Item => {
-            if keyboard.just_pressed(KeyCode::Digit1) {
-                editor_state.selected_item = ItemType::FullyEmpty;
-            } else if keyboard.just_pressed(KeyCode::Digit2) {
-                editor_state.selected_item = ItemType::Scrap;
-            } else if keyboard.just_pressed(KeyCode::Digit3) {
-                editor_state.selected_item = ItemType::GlassJar;
-            } else if keyboard.just_pressed(KeyCode::Digit4) {
-                editor_state.selected_item = ItemType::Battery;
-            } else if keyboard.just_pressed(KeyCode::Digit5) {
-                editor_state.selected_item = ItemType::Bolt;
-            } else if keyboard.just_pressed(KeyCode::Digit6) {
-                editor_state.selected_item = ItemType::MetalDetector;
-            } else if keyboard.just_pressed(KeyCode::Digit7) {
-                editor_state.selected_item = ItemType::RustSlag;
+            // 1-9 pick from the start of the catalogue, [ and ] step through all of it
+            let digits = [
+                KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3,
+                KeyCode::Digit4, KeyCode::Digit5, KeyCode::Digit6,
+                KeyCode::Digit7, KeyCode::Digit8, KeyCode::Digit9,
+            ];
+            if let Some(index) = digits.iter().position(|key| keyboard.just_pressed(*key)) {
+                if index < items.len() {
+                    editor_state.selected_item = index;
+                }
+            } else if keyboard.just_pressed(KeyCode::BracketRight) {
+                editor_state.selected_item = (editor_state.selected_item + 1) % items.len();
+            } else if keyboard.just_pressed(KeyCode::BracketLeft) {
+                editor_state.selected_item = (editor_state.selected_item + items.len() - 1) % items.len();
+            }
+        }
+        EditorMode::Spawn => {
+            let digits = [KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3, KeyCode::Digit4, KeyCode::Digit5];
+            let pressed = digits.iter().position(|key| keyboard.just_pressed(*key));
+            if let Some(index) = pressed.filter(|index| *index < spawn_presets().len()) {
+                editor_state.selected_spawn = index;
             }
         }
     }
@@ -114,11 +190,18 @@ pub fn editor_mouse_position_system(
     camera_query: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
     windows: Query<&Window>,
     grid: Res<GameGrid>,
+    panel_query: Query<&Interaction, Or<(With<MapIssuesPanel>, With<MapIssueRow>)>>,
 ) {
     let Ok(window) = windows.single() else {
         return;
     };
 
+    // The issues panel sits over the map; clicks on it aren't meant for the tiles below
+    if panel_query.iter().any(|interaction| *interaction != Interaction::None) {
+        cursor.grid_position = None;
+        return;
+    }
+
     let Some(cursor_screen_pos) = window.cursor_position() else {
         cursor.grid_position = None;
         return;
@@ -181,203 +264,698 @@ pub fn editor_cursor_highlight_system(
     }
 }
 
-// Save/Load map with F3/F4 hotkeys
-pub fn editor_save_load_system(
-    keyboard: Res<ButtonInput<KeyCode>>,
-    grid: Res<GameGrid>,
-    entity_query: Query<(Entity, &EntityType, &Position)>,
-    ground_items_query: Query<(Entity, &crate::components::item::GroundItems, &Position), Without<EntityType>>,
-    mut commands: Commands,
-) {
-    use crate::components::item::GroundItems;
+// Collect the grid, entities, ground items and map settings being edited
+fn current_map_data(
+    grid: &GameGrid,
+    entity_query: &Query<(Entity, &EntityType, &Position)>,
+    ground_items_query: &Query<(Entity, &GroundItems, &Position), Without<EntityType>>,
+    spawn_query: &Query<(Entity, &SpawnPoint, &Position)>,
+    link_query: &Query<(Entity, &ZoneLink, &Position)>,
+    settings: &MapSettings,
+) -> MapData {
+    let entities: Vec<(EntityType, usize, usize)> = entity_query
+        .iter()
+        .map(|(_, entity_type, pos)| (*entity_type, pos.x as usize, pos.y as usize))
+        .collect();
 
-    // F3: Save current map
-    if keyboard.just_pressed(KeyCode::F3) {
-        // Collect all placed entities
-        let entities: Vec<(EntityType, usize, usize)> = entity_query
-            .iter()
-            .map(|(_, entity_type, pos)| (*entity_type, pos.x as usize, pos.y as usize))
-            .collect();
+    let ground_items: Vec<(GroundItems, usize, usize)> = ground_items_query
+        .iter()
+        .map(|(_, items, pos)| (items.clone(), pos.x as usize, pos.y as usize))
+        .collect();
 
-        // Collect all ground items
-        let ground_items: Vec<(GroundItems, usize, usize)> = ground_items_query
-            .iter()
-            .map(|(_, items, pos)| (items.clone(), pos.x as usize, pos.y as usize))
-            .collect();
+    let mut map_data = MapData::from_game_state(grid, &entities, &ground_items);
+    map_data.spawn_points = spawn_points(spawn_query.iter().map(|(_, spawn, pos)| (spawn, pos)));
+    map_data.zone_links = zone_links(link_query.iter().map(|(_, link, pos)| (link, pos)));
+    map_data.movement = settings.movement;
+    map_data
+}
+
+// Spawn point markers as map data
+fn spawn_points<'a>(markers: impl Iterator<Item = (&'a SpawnPoint, &'a Position)>) -> Vec<PlacedSpawnPoint> {
+    markers
+        .map(|(spawn, pos)| PlacedSpawnPoint { x: pos.x as usize, y: pos.y as usize, spawn: spawn.clone() })
+        .collect()
+}
+
+// Zone link markers as map data
+fn zone_links<'a>(markers: impl Iterator<Item = (&'a ZoneLink, &'a Position)>) -> Vec<PlacedZoneLink> {
+    markers
+        .map(|(link, pos)| PlacedZoneLink { x: pos.x as usize, y: pos.y as usize, link: link.clone() })
+        .collect()
+}
 
-        let map_data = MapData::from_game_state(&grid, &entities, &ground_items);
+// Despawn every placed entity, ground item pile, spawn point and zone link
+pub fn clear_map(
+    commands: &mut Commands,
+    entity_query: &Query<(Entity, &EntityType, &Position)>,
+    ground_items_query: &Query<(Entity, &GroundItems, &Position), Without<EntityType>>,
+    spawn_query: &Query<(Entity, &SpawnPoint, &Position)>,
+    link_query: &Query<(Entity, &ZoneLink, &Position)>,
+) {
+    for (entity, _, _) in entity_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    for (entity, _, _) in ground_items_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    for (entity, _, _) in spawn_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    for (entity, _, _) in link_query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
 
-        match map_data.save_to_file(MAP_FILE_PATH) {
-            Ok(_) => info!("Map saved to {}", MAP_FILE_PATH),
-            Err(e) => error!("Failed to save map: {}", e),
+// Write the map to `path`, reporting the outcome in the editor HUD
+fn save_map(map_data: &MapData, path: String, map_file: &mut EditorMapFile) {
+    match map_data.save_to_file(&path) {
+        Ok(_) => {
+            info!("Map saved to {}", path);
+            map_file.status = Some(format!("Saved {}", path));
+            map_file.path = Some(path);
+            map_file.unsaved_changes = false;
+        }
+        Err(e) => {
+            error!("Failed to save map: {}", e);
+            map_file.status = Some(format!("Failed to save {}: {}", path, e));
         }
     }
+}
 
-    // F4: Load map
-    if keyboard.just_pressed(KeyCode::F4) {
-        match MapData::load_from_file(MAP_FILE_PATH) {
-            Ok(map_data) => {
-                info!("Map loaded from {}", MAP_FILE_PATH);
+// Replace the edited map with the one at `path`, reporting the outcome in the editor HUD
+fn load_map(
+    commands: &mut Commands,
+    path: String,
+    items: &ItemRegistry,
+    entity_query: &Query<(Entity, &EntityType, &Position)>,
+    ground_items_query: &Query<(Entity, &GroundItems, &Position), Without<EntityType>>,
+    spawn_query: &Query<(Entity, &SpawnPoint, &Position)>,
+    link_query: &Query<(Entity, &ZoneLink, &Position)>,
+    map_file: &mut EditorMapFile,
+    history: &mut EditHistory,
+) {
+    match MapData::load_from_file(&path, items) {
+        Ok(map_data) => {
+            clear_map(commands, entity_query, ground_items_query, spawn_query, link_query);
+            spawn_map(commands, &map_data);
+            history.clear();
 
-                // Despawn all existing entities
-                for (entity, _, _) in entity_query.iter() {
-                    commands.entity(entity).despawn();
-                }
+            info!("Loaded {}x{} map from {} with {} entities and {} item locations",
+                map_data.width, map_data.height, path, map_data.entities.len(), map_data.items.len());
+            map_file.status = Some(format!("Loaded {} ({}x{})", path, map_data.width, map_data.height));
+            map_file.path = Some(path);
+            map_file.unsaved_changes = false;
+        }
+        Err(e) => {
+            error!("Failed to load map: {}", e);
+            map_file.status = Some(format!("Failed to load {}: {}", path, e));
+        }
+    }
+}
 
-                // Despawn all existing ground items
-                for (entity, _, _) in ground_items_query.iter() {
-                    commands.entity(entity).despawn();
-                }
+// A width or height typed into the new map dialog
+fn parse_map_size(text: &str) -> Result<usize, String> {
+    text.parse::<usize>()
+        .ok()
+        .filter(|size| (MIN_MAP_SIZE..=MAX_MAP_SIZE).contains(size))
+        .ok_or_else(|| format!("Map width and height must be {}-{}", MIN_MAP_SIZE, MAX_MAP_SIZE))
+}
 
-                // Replace the grid (this will trigger tile sprite reload)
-                let new_grid = map_data.to_game_grid();
-                let grid_width = new_grid.width;
-                let grid_height = new_grid.height;
-                commands.insert_resource(new_grid);
-
-                // Spawn entities from loaded map
-                for placed_entity in &map_data.entities {
-                    spawn_placed_entity(
-                        &mut commands,
-                        placed_entity.entity_type.into(),
-                        placed_entity.x,
-                        placed_entity.y,
-                        grid_width,
-                        grid_height,
-                    );
-                }
+// Labels of the generate dialog's fields, in the order MapDialog::Generate keeps their text
+const GENERATE_FIELDS: [&str; 9] = [
+    "Seed", "Width", "Height", "Walls %", "Exits",
+    "Gravitational anomalies", "Philosopher's Stones", "Rust anomalies", "Item piles",
+];
 
-                // Spawn ground items from loaded map
-                for placed_items in &map_data.items {
-                    let mut ground_items = GroundItems::new();
-                    for item in &placed_items.items {
-                        ground_items.add_item(item.clone());
-                    }
+// Starting text of the generate dialog: a fresh seed, the current size and the generator defaults
+fn generate_fields(seed: u64, width: usize, height: usize) -> Vec<String> {
+    let params = ZoneGenParams { width, height, ..ZoneGenParams::default() };
+    [
+        seed,
+        params.width as u64,
+        params.height as u64,
+        (params.wall_density * 100.0).round() as u64,
+        params.exits as u64,
+        params.gravitational_anomalies as u64,
+        params.philosopher_stones as u64,
+        params.rust_anomalies as u64,
+        params.item_piles as u64,
+    ]
+    .iter()
+    .map(u64::to_string)
+    .collect()
+}
 
-                    commands.spawn((
-                        Position {
-                            x: placed_items.x as i32,
-                            y: placed_items.y as i32,
-                        },
-                        ground_items,
-                    ));
-                }
+// The seed and generator settings typed into the generate dialog; the loot table is the default one
+fn parse_generate_fields(fields: &[String]) -> Result<(u64, ZoneGenParams), String> {
+    let number = |index: usize| {
+        fields[index]
+            .parse::<usize>()
+            .map_err(|_| format!("{} must be a whole number", GENERATE_FIELDS[index]))
+    };
+    let seed = fields[0].parse::<u64>().map_err(|_| "Seed must be a whole number".to_string())?;
+    let params = ZoneGenParams {
+        width: number(1)?,
+        height: number(2)?,
+        wall_density: number(3)? as f32 / 100.0,
+        exits: number(4)?,
+        gravitational_anomalies: number(5)?,
+        philosopher_stones: number(6)?,
+        rust_anomalies: number(7)?,
+        item_piles: number(8)?,
+        ..ZoneGenParams::default()
+    };
+    Ok((seed, params))
+}
+
+// Labels of the link dialog's fields, in the order MapDialog::LinkExit keeps their text
+const LINK_FIELDS: [&str; 3] = ["Map", "Entry x", "Entry y"];
+
+// The link typed into the link dialog; None if the map name was left empty
+fn parse_link_fields(fields: &[String]) -> Result<Option<ZoneLink>, String> {
+    let map = fields[0].trim();
+    if map.is_empty() {
+        return Ok(None);
+    }
+    map_path(MAPS_DIRECTORY, map)?;
+    let number = |index: usize| {
+        fields[index]
+            .parse::<usize>()
+            .map_err(|_| format!("{} must be a whole number", LINK_FIELDS[index]))
+    };
+    Ok(Some(ZoneLink {
+        map: map.to_string(),
+        entry_x: number(1)?,
+        entry_y: number(2)?,
+    }))
+}
+
+// The LinkExit key (K) over an Exit opens the link dialog for it, filled in with the Exit's current link
+pub fn editor_link_exit_system(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    cursor: Res<EditorCursor>,
+    entity_query: Query<(&EntityType, &Position)>,
+    link_query: Query<(&ZoneLink, &Position)>,
+    mut map_file: ResMut<EditorMapFile>,
+    mut commands: Commands,
+) {
+    if !keybindings.just_pressed(&keyboard, InputAction::LinkExit) {
+        return;
+    }
+    let exit = cursor.grid_position.filter(|(x, y)| {
+        entity_query
+            .iter()
+            .any(|(entity_type, pos)| *entity_type == EntityType::Exit && pos.x == *x as i32 && pos.y == *y as i32)
+    });
+    let Some((x, y)) = exit else {
+        map_file.status = Some(format!(
+            "{} links an Exit to another map: put the cursor on an Exit",
+            keybindings.label(InputAction::LinkExit)
+        ));
+        return;
+    };
+
+    let fields = link_query
+        .iter()
+        .find(|(_, pos)| pos.x == x as i32 && pos.y == y as i32)
+        .map(|(link, _)| vec![link.map.clone(), link.entry_x.to_string(), link.entry_y.to_string()])
+        .unwrap_or_else(|| vec![String::new(); LINK_FIELDS.len()]);
+    commands.insert_resource(MapDialog::LinkExit { x, y, fields, selected: 0 });
+}
+
+// The CycleMovement key (M) cycles the map's movement mode: left to the settings, cardinal (4 directions),
+// diagonal (8 directions)
+pub fn editor_movement_mode_system(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    settings: Res<MapSettings>,
+    mut history: ResMut<EditHistory>,
+    mut map_file: ResMut<EditorMapFile>,
+    mut commands: Commands,
+) {
+    if !keybindings.just_pressed(&keyboard, InputAction::CycleMovement) {
+        return;
+    }
+    let before = settings.movement;
+    let after = match before {
+        None => Some(MovementMode::Cardinal),
+        Some(MovementMode::Cardinal) => Some(MovementMode::Diagonal),
+        Some(MovementMode::Diagonal) => None,
+    };
+
+    // One undo step, like any other edit
+    history.begin_stroke();
+    history.record(EditChange::Movement { before, after });
+    history.end_stroke();
+    commands.insert_resource(MapSettings { movement: after });
+    map_file.unsaved_changes = true;
+    map_file.status = Some(match after {
+        Some(movement) => format!("Movement: {} on this map", movement.name()),
+        None => "Movement: as set in the settings".to_string(),
+    });
+}
 
-                info!("Loaded {}x{} map with {} entities and {} item locations",
-                    map_data.width, map_data.height, map_data.entities.len(), map_data.items.len());
+// Map file hotkeys: F3 saves (asking for a name if the map has none), F4 opens the map browser,
+// F6 saves under a new name, F7 starts a new empty map, F8 generates a zone into this map,
+// F10 opens the keybindings screen (each is an action, so these are the default keys)
+pub fn editor_save_load_system(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    seed_override: Res<SeedOverride>,
+    grid: Res<GameGrid>,
+    entity_query: Query<(Entity, &EntityType, &Position)>,
+    ground_items_query: Query<(Entity, &GroundItems, &Position), Without<EntityType>>,
+    spawn_query: Query<(Entity, &SpawnPoint, &Position)>,
+    link_query: Query<(Entity, &ZoneLink, &Position)>,
+    settings: Res<MapSettings>,
+    mut map_file: ResMut<EditorMapFile>,
+    mut commands: Commands,
+) {
+    if keybindings.just_pressed(&keyboard, InputAction::SaveMap) {
+        match map_file.path.clone() {
+            Some(path) => {
+                let map_data = current_map_data(&grid, &entity_query, &ground_items_query, &spawn_query, &link_query, &settings);
+                save_map(&map_data, path, &mut map_file);
+            }
+            None => commands.insert_resource(MapDialog::SaveAs { name: String::new() }),
+        }
+    } else if keybindings.just_pressed(&keyboard, InputAction::OpenMap) {
+        match list_maps(MAPS_DIRECTORY) {
+            Ok(maps) => {
+                // Start on the map being edited
+                let selected = map_file
+                    .path
+                    .as_ref()
+                    .and_then(|path| maps.iter().position(|map| map == path))
+                    .unwrap_or(0);
+                commands.insert_resource(MapDialog::Open { maps, selected });
+            }
+            Err(e) => {
+                error!("Failed to list maps: {}", e);
+                map_file.status = Some(format!("Failed to list maps in {}: {}", MAPS_DIRECTORY, e));
             }
-            Err(e) => error!("Failed to load map: {}", e),
         }
+    } else if keybindings.just_pressed(&keyboard, InputAction::SaveMapAs) {
+        let name = map_file.path.as_deref().map(map_name).unwrap_or_default();
+        commands.insert_resource(MapDialog::SaveAs { name });
+    } else if keybindings.just_pressed(&keyboard, InputAction::NewMap) {
+        commands.insert_resource(MapDialog::NewMap {
+            width: grid.width.to_string(),
+            height: grid.height.to_string(),
+            editing_height: false,
+        });
+    } else if keybindings.just_pressed(&keyboard, InputAction::GenerateZone) {
+        commands.insert_resource(MapDialog::Generate {
+            fields: generate_fields(next_run_seed(&seed_override), grid.width, grid.height),
+            selected: 0,
+        });
+    } else if keybindings.just_pressed(&keyboard, InputAction::Keybindings) {
+        commands.insert_resource(MapDialog::Keybindings { selected: 0, capturing: false, status: None });
     }
 }
 
-// Place terrain, entities, or items with mouse clicks
-pub fn editor_placement_system(
+// Save the keybindings file after `action` was rebound, saying what it is bound to now
+fn save_keybindings(keybindings: &Keybindings, action: InputAction) -> String {
+    let bound = format!("{}: {}", action.label(), keybindings.label(action));
+    match keybindings.save_to_file(KEYBINDINGS_PATH) {
+        Ok(()) => bound,
+        Err(e) => {
+            error!("Failed to save keybindings: {}", e);
+            format!("{} (not saved: {})", bound, e)
+        }
+    }
+}
+
+// Bind the key just pressed to an action on the keybindings screen
+// A key that would do two things in one context is refused and the conflict is shown instead
+fn rebind_key(keybindings: &mut Keybindings, action: InputAction, key: KeyCode) -> String {
+    match keybindings.bind(action, key) {
+        Ok(()) => save_keybindings(keybindings, action),
+        Err(e) => e,
+    }
+}
+
+// Input while a map dialog is open: W/S and Enter pick a map to open, typed text and Enter
+// give a name, a size (Tab switches between width and height), generator settings or an Exit's
+// link (Tab/arrows move between fields), Up/Down and Enter pick an action to rebind and the next
+// key pressed is its new key, ESC closes the dialog (or stops waiting for a key)
+pub fn map_dialog_input_system(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut key_events: EventReader<KeyboardInput>,
+    mut dialog: ResMut<MapDialog>,
+    mut keybindings: ResMut<Keybindings>,
+    grid: Res<GameGrid>,
+    entity_query: Query<(Entity, &EntityType, &Position)>,
+    ground_items_query: Query<(Entity, &GroundItems, &Position), Without<EntityType>>,
+    spawn_query: Query<(Entity, &SpawnPoint, &Position)>,
+    link_query: Query<(Entity, &ZoneLink, &Position)>,
+    items: Res<ItemRegistry>,
+    settings: Res<MapSettings>,
+    mut map_file: ResMut<EditorMapFile>,
+    mut history: ResMut<EditHistory>,
     mut commands: Commands,
-    mouse: Res<ButtonInput<MouseButton>>,
-    cursor: Res<EditorCursor>,
-    editor_state: Res<EditorState>,
-    mut grid: ResMut<GameGrid>,
-    entity_query: Query<(Entity, &Position, &EntityType)>,
-    mut ground_items_query: Query<(Entity, &Position, &mut crate::components::item::GroundItems), Without<EntityType>>,
 ) {
-    use crate::components::item::{Item, GroundItems};
+    // Always drain the events so the key that opened the dialog isn't typed into it
+    let (pressed, pressed_codes): (Vec<Key>, Vec<KeyCode>) = key_events
+        .read()
+        .filter(|event| event.state == ButtonState::Pressed)
+        .map(|event| (event.logical_key.clone(), event.key_code))
+        .unzip();
 
-    let Some((grid_x, grid_y)) = cursor.grid_position else {
+    // While waiting for a key to bind, every key (ESC aside) is that key
+    if let MapDialog::Keybindings { selected, capturing: capturing @ true, status } = &mut *dialog {
+        if let Some(key) = pressed_codes.first() {
+            *capturing = false;
+            *status = Some(match key {
+                KeyCode::Escape => "Cancelled".to_string(),
+                key => rebind_key(&mut keybindings, InputAction::ALL[*selected], *key),
+            });
+        }
         return;
-    };
+    }
 
-    // Left-click: Place terrain, entity, or item
-    if mouse.just_pressed(MouseButton::Left) {
-        match editor_state.mode {
-            EditorMode::Terrain => {
-                // Update the terrain tile
-                let new_tile = Tile::new(editor_state.selected_terrain);
-                grid.set_tile(grid_x, grid_y, new_tile);
+    if pressed.contains(&Key::Escape) {
+        commands.remove_resource::<MapDialog>();
+        return;
+    }
+
+    match &mut *dialog {
+        MapDialog::Open { maps, selected } => {
+            if keyboard.just_pressed(KeyCode::KeyS) || keyboard.just_pressed(KeyCode::ArrowDown) {
+                if *selected + 1 < maps.len() {
+                    *selected += 1;
+                }
+            } else if keyboard.just_pressed(KeyCode::KeyW) || keyboard.just_pressed(KeyCode::ArrowUp) {
+                *selected = selected.saturating_sub(1);
+            } else if pressed.contains(&Key::Enter) {
+                if let Some(path) = maps.get(*selected).cloned() {
+                    load_map(&mut commands, path, &items, &entity_query, &ground_items_query, &spawn_query, &link_query, &mut map_file, &mut history);
+                }
+                commands.remove_resource::<MapDialog>();
             }
-            EditorMode::Entity => {
-                // Check if entity already exists at this position
-                let existing_entity = entity_query.iter().find(|(_, pos, _)| {
-                    pos.x == grid_x as i32 && pos.y == grid_y as i32
-                });
-
-                // Don't place if there's already an entity here
-                if existing_entity.is_none() {
-                    spawn_placed_entity(
-                        &mut commands,
-                        editor_state.selected_entity,
-                        grid_x,
-                        grid_y,
-                        grid.width,
-                        grid.height,
-                    );
+        }
+        MapDialog::SaveAs { name } => {
+            for key in pressed {
+                match key {
+                    Key::Enter => {
+                        match map_path(MAPS_DIRECTORY, name.trim()) {
+                            Ok(path) => {
+                                let map_data = current_map_data(&grid, &entity_query, &ground_items_query, &spawn_query, &link_query, &settings);
+                                save_map(&map_data, path, &mut map_file);
+                                commands.remove_resource::<MapDialog>();
+                            }
+                            Err(e) => map_file.status = Some(e),
+                        }
+                        return;
+                    }
+                    Key::Backspace => {
+                        name.pop();
+                    }
+                    Key::Character(text) => {
+                        for c in text.chars().filter(|c| !c.is_control()) {
+                            if name.chars().count() < MAP_NAME_MAX_LENGTH {
+                                name.push(c);
+                            }
+                        }
+                    }
+                    _ => {}
                 }
             }
-            EditorMode::Item => {
-                // Check if there's already a GroundItems entity at this position
-                let existing_ground_items = ground_items_query.iter_mut().find(|(_, pos, _)| {
-                    pos.x == grid_x as i32 && pos.y == grid_y as i32
-                });
-
-                if let Some((_, _, mut ground_items)) = existing_ground_items {
-                    // Add item to existing GroundItems
-                    let item: Item = editor_state.selected_item.into();
-                    ground_items.add_item(item);
-                } else {
-                    // Create new GroundItems entity
-                    let item: Item = editor_state.selected_item.into();
-                    let mut ground_items = GroundItems::new();
-                    ground_items.add_item(item);
-
-                    commands.spawn((
-                        Position {
-                            x: grid_x as i32,
-                            y: grid_y as i32,
-                        },
-                        ground_items,
-                    ));
+        }
+        MapDialog::NewMap { width, height, editing_height } => {
+            for key in pressed {
+                let field = if *editing_height { &mut *height } else { &mut *width };
+                match key {
+                    Key::Enter => {
+                        match parse_map_size(width).and_then(|w| parse_map_size(height).map(|h| (w, h))) {
+                            Ok((w, h)) => {
+                                clear_map(&mut commands, &entity_query, &ground_items_query, &spawn_query, &link_query);
+                                history.clear();
+                                commands.insert_resource(GameGrid::new_empty(w, h));
+                                commands.insert_resource(MapSettings::default());
+                                map_file.path = None;
+                                map_file.unsaved_changes = false;
+                                map_file.status = Some(format!("New {}x{} map", w, h));
+                                commands.remove_resource::<MapDialog>();
+                            }
+                            Err(e) => map_file.status = Some(e),
+                        }
+                        return;
+                    }
+                    Key::Tab => *editing_height = !*editing_height,
+                    Key::Backspace => {
+                        field.pop();
+                    }
+                    Key::Character(text) => {
+                        for c in text.chars().filter(|c| c.is_ascii_digit()) {
+                            if field.len() < 4 {
+                                field.push(c);
+                            }
+                        }
+                    }
+                    _ => {}
                 }
             }
         }
-    }
-
-    // Right-click: Delete entity, reset tile to Floor, or remove all items
-    if mouse.just_pressed(MouseButton::Right) {
-        match editor_state.mode {
-            EditorMode::Terrain => {
-                // Reset to floor
-                let floor_tile = Tile::new(TileKind::Floor);
-                grid.set_tile(grid_x, grid_y, floor_tile);
+        MapDialog::Generate { fields, selected } => {
+            for key in pressed {
+                match key {
+                    Key::Enter => {
+                        let generated = parse_generate_fields(fields)
+                            .and_then(|(seed, params)| generate_zone(seed, &params, &items).map(|map| (seed, map)));
+                        match generated {
+                            Ok((seed, map_data)) => {
+                                // Generated into the map being edited: same file, nothing saved yet
+                                clear_map(&mut commands, &entity_query, &ground_items_query, &spawn_query, &link_query);
+                                spawn_map(&mut commands, &map_data);
+                                history.clear();
+                                map_file.unsaved_changes = true;
+                                map_file.status = Some(format!(
+                                    "Generated {}x{} zone from seed {}",
+                                    map_data.width, map_data.height, seed
+                                ));
+                                commands.remove_resource::<MapDialog>();
+                            }
+                            Err(e) => map_file.status = Some(e),
+                        }
+                        return;
+                    }
+                    Key::Tab | Key::ArrowDown => *selected = (*selected + 1) % fields.len(),
+                    Key::ArrowUp => *selected = (*selected + fields.len() - 1) % fields.len(),
+                    Key::Backspace => {
+                        fields[*selected].pop();
+                    }
+                    Key::Character(text) => {
+                        // Seeds are u64s; the other fields are sizes and counts
+                        let max_length = if *selected == 0 { 20 } else { 4 };
+                        for c in text.chars().filter(|c| c.is_ascii_digit()) {
+                            if fields[*selected].len() < max_length {
+                                fields[*selected].push(c);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
             }
-            EditorMode::Entity => {
-                // Find and delete entity at cursor position
-                for (entity, pos, _) in entity_query.iter() {
-                    if pos.x == grid_x as i32 && pos.y == grid_y as i32 {
-                        commands.entity(entity).despawn();
-                        break; // Only delete one entity
+        }
+        MapDialog::LinkExit { x, y, fields, selected } => {
+            for key in pressed {
+                match key {
+                    Key::Enter => {
+                        match parse_link_fields(fields) {
+                            Ok(after) => {
+                                let position = Position { x: *x as i32, y: *y as i32 };
+                                let before = link_query
+                                    .iter()
+                                    .find(|(_, _, pos)| **pos == position)
+                                    .map(|(_, link, _)| link.clone());
+                                if before != after {
+                                    for (entity, _, _) in link_query.iter().filter(|(_, _, pos)| **pos == position) {
+                                        commands.entity(entity).despawn();
+                                    }
+                                    if let Some(link) = &after {
+                                        spawn_zone_link_marker(&mut commands, link.clone(), *x, *y, grid.width, grid.height);
+                                    }
+                                    map_file.status = Some(match &after {
+                                        Some(link) => format!("Exit at ({}, {}): {}", x, y, link.label()),
+                                        None => format!("Exit at ({}, {}) no longer leads anywhere", x, y),
+                                    });
+                                    // One undo step, like any other edit
+                                    history.begin_stroke();
+                                    history.record(EditChange::Link { position, before, after });
+                                    history.end_stroke();
+                                    map_file.unsaved_changes = true;
+                                }
+                                commands.remove_resource::<MapDialog>();
+                            }
+                            Err(e) => map_file.status = Some(e),
+                        }
+                        return;
                     }
+                    Key::Tab | Key::ArrowDown => *selected = (*selected + 1) % fields.len(),
+                    Key::ArrowUp => *selected = (*selected + fields.len() - 1) % fields.len(),
+                    Key::Backspace => {
+                        fields[*selected].pop();
+                    }
+                    Key::Character(text) => {
+                        // The map field takes a map name; the entry fields are tile coordinates
+                        for c in text.chars() {
+                            let (allowed, max_length) = if *selected == 0 {
+                                (c.is_ascii_alphanumeric() || c == '-' || c == '_', MAP_NAME_MAX_LENGTH)
+                            } else {
+                                (c.is_ascii_digit(), 3)
+                            };
+                            if allowed && fields[*selected].chars().count() < max_length {
+                                fields[*selected].push(c);
+                            }
+                        }
+                    }
+                    _ => {}
                 }
             }
-            EditorMode::Item => {
-                // Find and delete GroundItems entity at cursor position
-                for (entity, pos, _) in ground_items_query.iter() {
-                    if pos.x == grid_x as i32 && pos.y == grid_y as i32 {
-                        commands.entity(entity).despawn();
-                        break;
+        }
+        MapDialog::Keybindings { selected, capturing, status } => {
+            let action = InputAction::ALL[*selected];
+            for key in pressed {
+                match key {
+                    Key::ArrowDown => *selected = (*selected + 1) % InputAction::ALL.len(),
+                    Key::ArrowUp => *selected = (*selected + InputAction::ALL.len() - 1) % InputAction::ALL.len(),
+                    Key::Enter => {
+                        *capturing = true;
+                        *status = None;
+                        return;
+                    }
+                    Key::Backspace => {
+                        // Defaults can clash with keys rebound since; the conflicts list shows it
+                        keybindings.reset(action);
+                        *status = Some(save_keybindings(&keybindings, action));
                     }
+                    _ => {}
                 }
             }
         }
     }
 }
 
-// Spawn editor HUD when entering editor mode
-pub fn spawn_editor_hud_system(
+// Text of the map dialog
+fn map_dialog_text(dialog: &MapDialog, map_file: &EditorMapFile, keybindings: &Keybindings) -> String {
+    let mut lines = Vec::new();
+    match dialog {
+        MapDialog::Open { maps, selected } => {
+            lines.push(format!("Open Map ({})", MAPS_DIRECTORY));
+            if maps.is_empty() {
+                lines.push("  (no maps)".to_string());
+            }
+            for (index, path) in maps.iter().enumerate() {
+                let cursor = if index == *selected { ">" } else { " " };
+                let current = if map_file.path.as_ref() == Some(path) { " (current)" } else { "" };
+                lines.push(format!("{} {}{}", cursor, map_name(path), current));
+            }
+            lines.push(String::new());
+            lines.push("[W/S] Select  [Enter] Open  [ESC] Cancel".to_string());
+        }
+        MapDialog::SaveAs { name } => {
+            lines.push("Save Map As".to_string());
+            lines.push(format!("Name: {}_", name));
+            lines.push(format!("Saves to {}/{}.json", MAPS_DIRECTORY, name.trim()));
+            lines.push(String::new());
+            lines.push("[Enter] Save  [ESC] Cancel".to_string());
+        }
+        MapDialog::NewMap { width, height, editing_height } => {
+            let (width_cursor, height_cursor) = if *editing_height { ("", "_") } else { ("_", "") };
+            lines.push("New Map".to_string());
+            lines.push(format!("Width: {}{}", width, width_cursor));
+            lines.push(format!("Height: {}{}", height, height_cursor));
+            lines.push(format!("({}-{} tiles)", MIN_MAP_SIZE, MAX_MAP_SIZE));
+            lines.push(String::new());
+            lines.push("[Tab] Width/Height  [Enter] Create  [ESC] Cancel".to_string());
+        }
+        MapDialog::Generate { fields, selected } => {
+            lines.push("Generate Zone (replaces the current map)".to_string());
+            for (index, (label, value)) in GENERATE_FIELDS.iter().zip(fields).enumerate() {
+                if index == *selected {
+                    lines.push(format!("> {}: {}_", label, value));
+                } else {
+                    lines.push(format!("  {}: {}", label, value));
+                }
+            }
+            lines.push(format!("(size {}-{} tiles, walls 0-90%)", MIN_MAP_SIZE, MAX_MAP_SIZE));
+            lines.push(String::new());
+            lines.push("[Tab/Up/Down] Field  [Enter] Generate  [ESC] Cancel".to_string());
+        }
+        MapDialog::LinkExit { x, y, fields, selected } => {
+            lines.push(format!("Link Exit at ({}, {})", x, y));
+            for (index, (label, value)) in LINK_FIELDS.iter().zip(fields).enumerate() {
+                if index == *selected {
+                    lines.push(format!("> {}: {}_", label, value));
+                } else {
+                    lines.push(format!("  {}: {}", label, value));
+                }
+            }
+            lines.push(format!("(a map in {}; leave it empty to remove the link)", MAPS_DIRECTORY));
+            lines.push(String::new());
+            lines.push("[Tab/Up/Down] Field  [Enter] Link  [ESC] Cancel".to_string());
+        }
+        MapDialog::Keybindings { selected, capturing, status } => {
+            lines.push(format!("Keybindings (saved to {})", KEYBINDINGS_PATH));
+            // A window of rows that keeps the selected action in view
+            let first = selected.saturating_sub(KEYBINDING_ROWS / 2).min(InputAction::ALL.len().saturating_sub(KEYBINDING_ROWS));
+            for (index, action) in InputAction::ALL.iter().enumerate().skip(first).take(KEYBINDING_ROWS) {
+                let cursor = if index == *selected { ">" } else { " " };
+                let keys = if index == *selected && *capturing {
+                    "press a key...".to_string()
+                } else {
+                    keybindings.label(*action)
+                };
+                lines.push(format!("{} {}: {}", cursor, action.label(), keys));
+            }
+            lines.push(format!("({} of {})", selected + 1, InputAction::ALL.len()));
+            if let Some(status) = status {
+                lines.push(status.clone());
+            }
+            for conflict in keybindings.conflicts() {
+                lines.push(format!("Conflict: {}", conflict));
+            }
+            lines.push(String::new());
+            if *capturing {
+                lines.push("[Any key] Bind  [ESC] Cancel".to_string());
+            } else {
+                lines.push("[Up/Down] Action  [Enter] Rebind  [Backspace] Default  [ESC] Close".to_string());
+            }
+        }
+    }
+
+    let map_dialog = !matches!(dialog, MapDialog::SaveAs { .. } | MapDialog::LinkExit { .. } | MapDialog::Keybindings { .. });
+    if map_file.unsaved_changes && map_dialog {
+        lines.push("Unsaved changes to this map will be lost".to_string());
+    }
+    lines.join("\n")
+}
+
+// Show the map dialog while one is open and keep its text current
+pub fn update_map_dialog_ui_system(
     mut commands: Commands,
-    hud_query: Query<Entity, With<EditorHudRoot>>,
+    dialog: Option<Res<MapDialog>>,
+    map_file: Res<EditorMapFile>,
+    keybindings: Res<Keybindings>,
+    panel_query: Query<Entity, With<MapDialogPanel>>,
+    mut text_query: Query<&mut Text, With<MapDialogText>>,
 ) {
-    // Only spawn if doesn't exist
-    if hud_query.iter().next().is_some() {
+    let Some(dialog) = dialog else {
+        for entity in panel_query.iter() {
+            commands.entity(entity).despawn();
+        }
+        return;
+    };
+
+    if let Ok(mut text) = text_query.single_mut() {
+        if dialog.is_changed() || map_file.is_changed() || keybindings.is_changed() {
+            text.0 = map_dialog_text(&dialog, &map_file, &keybindings);
+        }
+        return;
+    }
+    if panel_query.iter().next().is_some() {
         return;
     }
 
@@ -385,67 +963,780 @@ pub fn spawn_editor_hud_system(
         .spawn((
             Node {
                 position_type: PositionType::Absolute,
-                left: Val::Px(10.0),
-                top: Val::Px(10.0),
-                flex_direction: FlexDirection::Column,
-                row_gap: Val::Px(5.0),
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
                 ..default()
             },
-            EditorHudRoot,
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+            MapDialogPanel,
+            ZIndex(100),
         ))
         .with_children(|parent| {
-            // Mode line
-            parent.spawn((
-                Text::new("EDITOR MODE | F2: Play | Tab: Switch Mode"),
-                TextColor(Color::WHITE),
-                TextFont {
-                    font_size: 16.0,
-                    ..default()
-                },
-                EditorModeText,
-            ));
-
-            // Selection line
-            parent.spawn((
-                Text::new("TERRAIN: Floor"),
-                TextColor(Color::WHITE),
-                TextFont {
-                    font_size: 16.0,
-                    ..default()
-                },
-                EditorSelectionText,
-            ));
-
-            // Cursor position line
-            parent.spawn((
-                Text::new("Cursor: --"),
-                TextColor(Color::WHITE),
-                TextFont {
-                    font_size: 16.0,
-                    ..default()
-                },
-                EditorCursorText,
-            ));
+            parent
+                .spawn((
+                    Node {
+                        padding: UiRect::all(Val::Px(20.0)),
+                        min_width: Val::Px(360.0),
+                        max_height: Val::Percent(80.0),
+                        overflow: Overflow::clip_y(),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
+                    BorderColor(Color::srgb(0.5, 0.5, 0.5)),
+                ))
+                .with_child((
+                    Text::new(map_dialog_text(&dialog, &map_file, &keybindings)),
+                    TextFont {
+                        font_size: 16.0,
+                        ..default()
+                    },
+                    TextColor(Color::WHITE),
+                    MapDialogText,
+                ));
         });
 }
 
-// Update editor HUD text
-pub fn update_editor_hud_system(
-    editor_state: Res<EditorState>,
-    cursor: Res<EditorCursor>,
-    mut selection_text_query: Query<&mut Text, (With<EditorSelectionText>, Without<EditorCursorText>)>,
-    mut cursor_text_query: Query<&mut Text, (With<EditorCursorText>, Without<EditorSelectionText>)>,
-) {
-    use crate::resources::game_grid::ItemType;
+// Replace the grid and map settings and spawn the map's entities and ground items
+// Callers are responsible for despawning whatever was placed before
+pub fn spawn_map(commands: &mut Commands, map_data: &MapData) {
+    // Replace the grid (this will trigger tile sprite reload)
+    commands.insert_resource(map_data.to_game_grid());
+    commands.insert_resource(MapSettings { movement: map_data.movement });
+    spawn_map_contents(commands, map_data);
+}
 
-    // Update selection text
-    if let Ok(mut text) = selection_text_query.single_mut() {
-        let selection_str = match editor_state.mode {
-            EditorMode::Terrain => {
-                match editor_state.selected_terrain {
-                    TileKind::Floor => "TERRAIN: 1=Floor, 2=Wall | Selected: Floor (1)".to_string(),
-                    TileKind::Wall => "TERRAIN: 1=Floor, 2=Wall | Selected: Wall (2)".to_string(),
-                }
+// Spawn the map's entities and ground items, leaving the grid as it is
+fn spawn_map_contents(commands: &mut Commands, map_data: &MapData) {
+    // Spawn entities from loaded map
+    for placed_entity in &map_data.entities {
+        spawn_placed_entity(
+            commands,
+            placed_entity.entity_type.into(),
+            placed_entity.x,
+            placed_entity.y,
+            map_data.width,
+            map_data.height,
+        );
+    }
+
+    // Spawn ground items from loaded map
+    for placed_items in &map_data.items {
+        // The pile as it was saved
+        let ground_items = GroundItems { items: placed_items.items.clone() };
+
+        commands.spawn((
+            Position {
+                x: placed_items.x as i32,
+                y: placed_items.y as i32,
+            },
+            ground_items,
+        ));
+    }
+
+    for placed in &map_data.spawn_points {
+        spawn_spawn_point_marker(commands, placed.spawn.clone(), placed.x, placed.y, map_data.width, map_data.height);
+    }
+
+    for placed in &map_data.zone_links {
+        spawn_zone_link_marker(commands, placed.link.clone(), placed.x, placed.y, map_data.width, map_data.height);
+    }
+}
+
+// Open the map given on the command line and, for --play or --replay, start playing it
+pub fn load_startup_map_system(
+    mut commands: Commands,
+    startup: Option<Res<StartupMap>>,
+    items: Res<ItemRegistry>,
+    mut map_file: ResMut<EditorMapFile>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Some(startup) = startup else {
+        return;
+    };
+
+    spawn_map(&mut commands, &startup.map);
+    map_file.path = Some(startup.path.clone());
+    map_file.status = Some(format!("Loaded {} ({}x{})", startup.path, startup.map.width, startup.map.height));
+    info!("Opened map {}", startup.path);
+
+    if startup.play {
+        let errors: Vec<MapIssue> = validate_map(&startup.map, &items).into_iter().filter(MapIssue::is_error).collect();
+        if errors.is_empty() {
+            next_state.set(GameState::Running);
+        } else {
+            for issue in &errors {
+                warn!("{}: {}", startup.path, issue);
+            }
+            map_file.status = Some(format!("Can't play {}: the map has {} errors", startup.path, errors.len()));
+        }
+    }
+    commands.remove_resource::<StartupMap>();
+}
+
+// Set the authored map aside when entering Running mode
+// The session plays on the map's entities from here on; they are replaced by this snapshot on exit
+pub fn snapshot_authored_map_system(
+    mut commands: Commands,
+    grid: Res<GameGrid>,
+    entity_query: Query<(&EntityType, &Position)>,
+    ground_items_query: Query<(&GroundItems, &Position), Without<EntityType>>,
+    spawn_query: Query<(&SpawnPoint, &Position)>,
+    link_query: Query<(&ZoneLink, &Position)>,
+    settings: Res<MapSettings>,
+) {
+    let entities: Vec<(EntityType, usize, usize)> = entity_query
+        .iter()
+        .map(|(entity_type, pos)| (*entity_type, pos.x as usize, pos.y as usize))
+        .collect();
+    let ground_items: Vec<_> = ground_items_query
+        .iter()
+        .map(|(items, pos)| (items.clone(), pos.x as usize, pos.y as usize))
+        .collect();
+
+    let mut authored = MapData::from_game_state(&grid, &entities, &ground_items);
+    authored.spawn_points = spawn_points(spawn_query.iter());
+    authored.zone_links = zone_links(link_query.iter());
+    authored.movement = settings.movement;
+    commands.insert_resource(AuthoredMap(authored));
+}
+
+// Throw away the session's entities and ground items and put the authored map back when exiting Running mode
+// Pickups, drops, transmutations and rust never reach the map being edited, and every restart starts from it
+pub fn restore_authored_map_system(
+    mut commands: Commands,
+    authored: Option<Res<AuthoredMap>>,
+    session_query: Query<Entity, Or<(With<EntityType>, With<GroundItems>, With<SpawnPoint>, With<ZoneLink>)>>,
+    mut grid: ResMut<GameGrid>,
+) {
+    let Some(authored) = authored else {
+        return;
+    };
+
+    // The run may have ended in another zone
+    let authored_grid = authored.0.to_game_grid();
+    if *grid != authored_grid {
+        *grid = authored_grid;
+    }
+
+    for entity in session_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    spawn_map_contents(&mut commands, &authored.0);
+    commands.insert_resource(MapSettings { movement: authored.0.movement });
+    commands.remove_resource::<AuthoredMap>();
+}
+
+// Hand the map being edited over to the run when entering Running mode: the run starts in this map,
+// with its zone links and movement mode, and the editor-only spawn point and zone link markers go away
+// (what the spawn points rolled is mirrored from the simulation)
+pub fn start_zone_system(
+    mut commands: Commands,
+    sim: Option<ResMut<ZoneSim>>,
+    resume: Option<Res<PendingResume>>,
+    playback: Option<Res<ReplayPlayback>>,
+    map_file: Res<EditorMapFile>,
+    settings: Res<MapSettings>,
+    movement_setting: Res<MovementSetting>,
+    link_query: Query<(&ZoneLink, &Position)>,
+    marker_query: Query<Entity, Or<(With<SpawnPoint>, With<ZoneLink>)>>,
+) {
+    // A resumed run already knows its zone, links and movement mode
+    if let (Some(mut sim), None) = (sim, resume) {
+        // Only maps without zone links start unsaved (see `unnamed_linked_map`), and nothing leads back to them
+        sim.zone = map_file.path.as_deref().map(map_name).unwrap_or_default();
+        // Links lead to maps next to this one, wherever it was opened from
+        sim.maps_directory = map_file.path.as_deref().map_or_else(|| MAPS_DIRECTORY.to_string(), map_directory);
+        sim.links = zone_links(link_query.iter());
+        // A replay moves the way it was recorded
+        sim.movement_setting = playback.map_or(*movement_setting, |playback| MovementSetting(playback.replay.movement));
+        sim.set_map_movement(settings.movement);
+    }
+
+    for entity in marker_query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+// Make one change to the map being edited
+fn apply_change(
+    change: &EditChange,
+    commands: &mut Commands,
+    grid: &mut GameGrid,
+    entity_query: &Query<(Entity, &Position, &EntityType)>,
+    ground_items_query: &Query<(Entity, &Position, &GroundItems), Without<EntityType>>,
+    spawn_query: &Query<(Entity, &Position, &SpawnPoint)>,
+    link_query: &Query<(Entity, &Position, &ZoneLink)>,
+) {
+    match change {
+        EditChange::Terrain { x, y, after, .. } => {
+            grid.set_tile(*x, *y, Tile::new(*after));
+        }
+        EditChange::PlaceEntity { position, entity_type } => {
+            spawn_placed_entity(
+                commands,
+                *entity_type,
+                position.x as usize,
+                position.y as usize,
+                grid.width,
+                grid.height,
+            );
+        }
+        EditChange::RemoveEntity { position, entity_type } => {
+            let placed = entity_query
+                .iter()
+                .find(|(_, pos, placed_type)| **pos == *position && **placed_type == *entity_type);
+            if let Some((entity, _, _)) = placed {
+                commands.entity(entity).despawn();
+            }
+        }
+        EditChange::Items { position, after, .. } => {
+            let pile = ground_items_query
+                .iter()
+                .find(|(_, pos, _)| **pos == *position)
+                .map(|(entity, _, _)| entity);
+            match (pile, after.is_empty()) {
+                (Some(entity), true) => {
+                    commands.entity(entity).despawn();
+                }
+                (Some(entity), false) => {
+                    commands.entity(entity).insert(GroundItems { items: after.clone() });
+                }
+                (None, false) => {
+                    commands.spawn((*position, GroundItems { items: after.clone() }));
+                }
+                (None, true) => {}
+            }
+        }
+        EditChange::Spawn { position, after, .. } => {
+            for (entity, _, _) in spawn_query.iter().filter(|(_, pos, _)| **pos == *position) {
+                commands.entity(entity).despawn();
+            }
+            if let Some(spawn) = after {
+                spawn_spawn_point_marker(
+                    commands,
+                    spawn.clone(),
+                    position.x as usize,
+                    position.y as usize,
+                    grid.width,
+                    grid.height,
+                );
+            }
+        }
+        EditChange::Movement { after, .. } => {
+            commands.insert_resource(MapSettings { movement: *after });
+        }
+        EditChange::Link { position, after, .. } => {
+            for (entity, _, _) in link_query.iter().filter(|(_, pos, _)| **pos == *position) {
+                commands.entity(entity).despawn();
+            }
+            if let Some(link) = after {
+                spawn_zone_link_marker(
+                    commands,
+                    link.clone(),
+                    position.x as usize,
+                    position.y as usize,
+                    grid.width,
+                    grid.height,
+                );
+            }
+        }
+    }
+}
+
+// The change that sets a tile's terrain, if it isn't that already
+fn terrain_change(grid: &GameGrid, (x, y): (usize, usize), after: TileKind) -> Option<EditChange> {
+    grid.get_tile(x, y)
+        .map(|tile| tile.kind)
+        .filter(|before| *before != after)
+        .map(|before| EditChange::Terrain { x, y, before, after })
+}
+
+// Copy the terrain, entities and items of the rectangle spanned by two tiles
+fn copy_region(
+    grid: &GameGrid,
+    a: (usize, usize),
+    b: (usize, usize),
+    entity_query: &Query<(Entity, &Position, &EntityType)>,
+    ground_items_query: &Query<(Entity, &Position, &GroundItems), Without<EntityType>>,
+) -> MapRegion {
+    let (min, max) = bounds(a, b);
+    let (min, max) = (min, (max.0.min(grid.width - 1), max.1.min(grid.height - 1)));
+    let inside = |pos: &Position| -> Option<(usize, usize)> {
+        let (x, y) = (pos.x as usize, pos.y as usize);
+        (pos.x >= 0 && pos.y >= 0 && (min.0..=max.0).contains(&x) && (min.1..=max.1).contains(&y))
+            .then_some((x - min.0, y - min.1))
+    };
+
+    MapRegion {
+        width: max.0 - min.0 + 1,
+        height: max.1 - min.1 + 1,
+        terrain: (min.1..=max.1)
+            .map(|y| {
+                (min.0..=max.0)
+                    .map(|x| grid.get_tile(x, y).map_or(TileKind::Floor, |tile| tile.kind))
+                    .collect()
+            })
+            .collect(),
+        entities: entity_query
+            .iter()
+            .filter_map(|(_, pos, entity_type)| inside(pos).map(|offset| (offset, *entity_type)))
+            .collect(),
+        items: ground_items_query
+            .iter()
+            .filter(|(_, _, ground_items)| !ground_items.is_empty())
+            .filter_map(|(_, pos, ground_items)| inside(pos).map(|offset| (offset, ground_items.items.clone())))
+            .collect(),
+    }
+}
+
+// The changes that stamp a copied region onto the map with its top-left corner at `at`
+// The region replaces whatever was there; parts falling off the map are dropped
+fn paste_changes(
+    region: &MapRegion,
+    at: (usize, usize),
+    grid: &GameGrid,
+    entity_query: &Query<(Entity, &Position, &EntityType)>,
+    ground_items_query: &Query<(Entity, &Position, &GroundItems), Without<EntityType>>,
+) -> Vec<EditChange> {
+    let region_entities: HashMap<(usize, usize), EntityType> = region.entities.iter().cloned().collect();
+    let region_items: HashMap<(usize, usize), &Vec<Item>> = region.items.iter().map(|(offset, items)| (*offset, items)).collect();
+
+    let mut changes = Vec::new();
+    for (dx, dy) in rectangle((0, 0), (region.width - 1, region.height - 1)) {
+        let (x, y) = (at.0 + dx, at.1 + dy);
+        if x >= grid.width || y >= grid.height {
+            continue;
+        }
+        let position = Position { x: x as i32, y: y as i32 };
+
+        changes.extend(terrain_change(grid, (x, y), region.terrain[dy][dx]));
+
+        let existing = entity_query
+            .iter()
+            .find(|(_, pos, _)| **pos == position)
+            .map(|(_, _, entity_type)| *entity_type);
+        let pasted = region_entities.get(&(dx, dy)).copied();
+        if existing != pasted {
+            if let Some(entity_type) = existing {
+                changes.push(EditChange::RemoveEntity { position, entity_type });
+            }
+            if let Some(entity_type) = pasted {
+                changes.push(EditChange::PlaceEntity { position, entity_type });
+            }
+        }
+
+        let before = ground_items_query
+            .iter()
+            .find(|(_, pos, _)| **pos == position)
+            .map(|(_, _, ground_items)| ground_items.items.clone())
+            .unwrap_or_default();
+        let after = region_items.get(&(dx, dy)).map(|items| (*items).clone()).unwrap_or_default();
+        if before != after {
+            changes.push(EditChange::Items { position, before, after });
+        }
+    }
+    changes
+}
+
+// Place terrain, entities, items, or spawn points with the mouse
+// Holding a button paints every tile the cursor crosses, and the whole stroke is one undo step
+// Right button: delete entity, reset tile to Floor, remove all items, or remove the spawn point from the tile
+pub fn editor_placement_system(
+    mut commands: Commands,
+    mouse: Res<ButtonInput<MouseButton>>,
+    cursor: Res<EditorCursor>,
+    editor_state: Res<EditorState>,
+    items: Res<ItemRegistry>,
+    mut grid: ResMut<GameGrid>,
+    entity_query: Query<(Entity, &Position, &EntityType)>,
+    ground_items_query: Query<(Entity, &Position, &GroundItems), Without<EntityType>>,
+    spawn_query: Query<(Entity, &Position, &SpawnPoint)>,
+    link_query: Query<(Entity, &Position, &ZoneLink)>,
+    mut history: ResMut<EditHistory>,
+    mut map_file: ResMut<EditorMapFile>,
+) {
+    let placing = mouse.pressed(MouseButton::Left);
+    if editor_state.effective_tool() != EditorTool::Brush || (!placing && !mouse.pressed(MouseButton::Right)) {
+        history.end_stroke();
+        return;
+    }
+    if mouse.just_pressed(MouseButton::Left) || mouse.just_pressed(MouseButton::Right) {
+        history.begin_stroke();
+    }
+
+    let Some((grid_x, grid_y)) = cursor.grid_position else {
+        return;
+    };
+
+    // Each tile is painted once per stroke
+    if !history.visit(grid_x, grid_y) {
+        return;
+    }
+
+    let position = Position {
+        x: grid_x as i32,
+        y: grid_y as i32,
+    };
+    let pile = ground_items_query
+        .iter()
+        .find(|(_, pos, _)| **pos == position)
+        .map(|(_, _, ground_items)| ground_items.items.clone())
+        .unwrap_or_default();
+
+    let change = match (editor_state.mode, placing) {
+        (EditorMode::Terrain, _) => {
+            let after = if placing { editor_state.selected_terrain } else { TileKind::Floor };
+            terrain_change(&grid, (grid_x, grid_y), after)
+        }
+        (EditorMode::Entity, true) => {
+            // Don't place if there's already an entity here
+            let occupied = entity_query.iter().any(|(_, pos, _)| *pos == position);
+            (!occupied).then_some(EditChange::PlaceEntity {
+                position,
+                entity_type: editor_state.selected_entity,
+            })
+        }
+        (EditorMode::Entity, false) => entity_query
+            .iter()
+            .find(|(_, pos, _)| **pos == position)
+            .map(|(_, _, entity_type)| EditChange::RemoveEntity {
+                position,
+                entity_type: *entity_type,
+            }),
+        (EditorMode::Item, true) => items.iter().nth(editor_state.selected_item).map(|definition| {
+            let mut after = GroundItems { items: pile.clone() };
+            after.add_item(definition.create(), &items);
+            EditChange::Items { position, before: pile, after: after.items }
+        }),
+        (EditorMode::Item, false) => (!pile.is_empty()).then(|| EditChange::Items {
+            position,
+            before: pile,
+            after: Vec::new(),
+        }),
+        (EditorMode::Spawn, _) => {
+            // One spawn point per tile: placing replaces whatever point is there
+            let before = spawn_query
+                .iter()
+                .find(|(_, pos, _)| **pos == position)
+                .map(|(_, _, spawn)| spawn.clone());
+            let after = placing
+                .then(|| spawn_presets().into_iter().nth(editor_state.selected_spawn).map(|(_, spawn)| spawn))
+                .flatten();
+            (before != after).then_some(EditChange::Spawn { position, before, after })
+        }
+    };
+
+    if let Some(change) = change {
+        apply_change(&change, &mut commands, &mut grid, &entity_query, &ground_items_query, &spawn_query, &link_query);
+        history.record(change);
+        if !map_file.unsaved_changes {
+            map_file.unsaved_changes = true;
+        }
+    }
+}
+
+// Shape tools: Rect, Outline, Line and Copy drag out a shape from press to release, Fill floods the
+// connected terrain under the cursor, and Paste stamps the clipboard with its top-left at the cursor
+// The right button paints Floor instead of the selected terrain; each use is one undo step
+pub fn editor_tool_system(
+    mut commands: Commands,
+    mouse: Res<ButtonInput<MouseButton>>,
+    cursor: Res<EditorCursor>,
+    mut editor_state: ResMut<EditorState>,
+    mut grid: ResMut<GameGrid>,
+    entity_query: Query<(Entity, &Position, &EntityType)>,
+    ground_items_query: Query<(Entity, &Position, &GroundItems), Without<EntityType>>,
+    spawn_query: Query<(Entity, &Position, &SpawnPoint)>,
+    link_query: Query<(Entity, &Position, &ZoneLink)>,
+    mut history: ResMut<EditHistory>,
+    mut map_file: ResMut<EditorMapFile>,
+) {
+    let tool = editor_state.effective_tool();
+    let left = mouse.just_pressed(MouseButton::Left);
+    let right = mouse.just_pressed(MouseButton::Right);
+    let paint = if right { TileKind::Floor } else { editor_state.selected_terrain };
+
+    let changes: Vec<EditChange> = match tool {
+        EditorTool::Brush => return,
+        EditorTool::Fill => {
+            let Some(start) = cursor.grid_position.filter(|_| left || right) else {
+                return;
+            };
+            flood_fill(&grid, start)
+                .into_iter()
+                .filter_map(|tile| terrain_change(&grid, tile, paint))
+                .collect()
+        }
+        EditorTool::Paste => {
+            let Some(at) = cursor.grid_position.filter(|_| left) else {
+                return;
+            };
+            let Some(region) = editor_state.clipboard.as_ref() else {
+                map_file.status = Some("Nothing to paste: copy a region with the Copy tool (C) first".to_string());
+                return;
+            };
+            paste_changes(region, at, &grid, &entity_query, &ground_items_query)
+        }
+        _ => {
+            // Rect, Outline, Line and Copy
+            let starts = left || (right && tool != EditorTool::Copy);
+            if let Some(pos) = cursor.grid_position.filter(|_| starts) {
+                editor_state.drag = Some(ToolDrag { start: pos, end: pos, erase: right });
+            }
+            let Some(mut drag) = editor_state.drag else {
+                return;
+            };
+            if let Some(pos) = cursor.grid_position.filter(|pos| *pos != drag.end) {
+                drag.end = pos;
+                editor_state.drag = Some(drag);
+            }
+            if mouse.any_pressed([MouseButton::Left, MouseButton::Right]) {
+                return; // Still dragging
+            }
+            editor_state.drag = None;
+
+            if tool == EditorTool::Copy {
+                let region = copy_region(&grid, drag.start, drag.end, &entity_query, &ground_items_query);
+                map_file.status = Some(format!(
+                    "Copied {}x{} region ({} entities, {} item piles)",
+                    region.width, region.height, region.entities.len(), region.items.len()
+                ));
+                editor_state.clipboard = Some(region);
+                return;
+            }
+
+            let paint = if drag.erase { TileKind::Floor } else { editor_state.selected_terrain };
+            shape_tiles(tool, &drag)
+                .into_iter()
+                .filter_map(|tile| terrain_change(&grid, tile, paint))
+                .collect()
+        }
+    };
+
+    if changes.is_empty() {
+        return;
+    }
+    history.begin_stroke();
+    for change in changes {
+        apply_change(&change, &mut commands, &mut grid, &entity_query, &ground_items_query, &spawn_query, &link_query);
+        history.record(change);
+    }
+    history.end_stroke();
+    map_file.unsaved_changes = true;
+}
+
+// Pick the mouse tool: B Brush, R Rect, O Outline, L Line, F Fill, C Copy, V Paste
+pub fn editor_tool_select_system(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut editor_state: ResMut<EditorState>,
+) {
+    // Ctrl combinations are editor commands (undo/redo), not tool picks
+    if keyboard.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+
+    if let Some(tool) = EditorTool::ALL.into_iter().find(|tool| keyboard.just_pressed(tool.key())) {
+        editor_state.tool = tool;
+        editor_state.drag = None;
+    }
+}
+
+// Highlight the tiles the current tool will change: the shape being dragged, or where a paste lands
+pub fn update_tool_preview_system(
+    mut commands: Commands,
+    editor_state: Res<EditorState>,
+    cursor: Res<EditorCursor>,
+    grid: Res<GameGrid>,
+    preview_query: Query<Entity, With<ToolPreview>>,
+) {
+    if !editor_state.is_changed() && !cursor.is_changed() {
+        return;
+    }
+    for entity in preview_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let tool = editor_state.effective_tool();
+    let (tiles, color) = match (tool, editor_state.drag, cursor.grid_position, &editor_state.clipboard) {
+        (EditorTool::Copy, Some(drag), _, _) => (rectangle(drag.start, drag.end), Color::srgba(0.3, 0.8, 1.0, 0.3)),
+        (_, Some(drag), _, _) if tool.drags_shape() => (shape_tiles(tool, &drag), Color::srgba(1.0, 0.9, 0.2, 0.35)),
+        (EditorTool::Paste, _, Some(at), Some(region)) => (
+            rectangle(at, (at.0 + region.width - 1, at.1 + region.height - 1)),
+            Color::srgba(0.3, 1.0, 0.5, 0.25),
+        ),
+        _ => return,
+    };
+
+    for (x, y) in tiles.into_iter().filter(|(x, y)| *x < grid.width && *y < grid.height) {
+        let world_pos = grid_to_world(x, y, grid.width, grid.height);
+        commands.spawn((
+            Sprite {
+                color,
+                custom_size: Some(Vec2::new(TILE_SIZE, TILE_SIZE)),
+                ..default()
+            },
+            Transform::from_xyz(world_pos.x, world_pos.y, 4.5), // Just below the cursor highlight
+            ToolPreview,
+        ));
+    }
+}
+
+// Drop the tool preview and any half-finished drag when leaving Editing mode
+pub fn clear_tool_preview_system(
+    mut commands: Commands,
+    mut editor_state: ResMut<EditorState>,
+    preview_query: Query<Entity, With<ToolPreview>>,
+) {
+    for entity in preview_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    editor_state.drag = None;
+}
+
+// Ctrl+Z undoes the last click or stroke, Ctrl+Y (or Ctrl+Shift+Z) redoes it
+pub fn editor_undo_system(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut commands: Commands,
+    mut grid: ResMut<GameGrid>,
+    entity_query: Query<(Entity, &Position, &EntityType)>,
+    ground_items_query: Query<(Entity, &Position, &GroundItems), Without<EntityType>>,
+    spawn_query: Query<(Entity, &Position, &SpawnPoint)>,
+    link_query: Query<(Entity, &Position, &ZoneLink)>,
+    mut history: ResMut<EditHistory>,
+    mut map_file: ResMut<EditorMapFile>,
+) {
+    if !keyboard.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+    let shift = keyboard.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+
+    let (changes, nothing_left) = if keyboard.just_pressed(KeyCode::KeyZ) && !shift {
+        (history.undo(), "Nothing to undo")
+    } else if keyboard.just_pressed(KeyCode::KeyY) || keyboard.just_pressed(KeyCode::KeyZ) {
+        (history.redo(), "Nothing to redo")
+    } else {
+        return;
+    };
+
+    let Some(changes) = changes else {
+        map_file.status = Some(nothing_left.to_string());
+        return;
+    };
+    for change in &changes {
+        apply_change(change, &mut commands, &mut grid, &entity_query, &ground_items_query, &spawn_query, &link_query);
+    }
+    map_file.unsaved_changes = true;
+}
+
+// Spawn editor HUD when entering editor mode
+pub fn spawn_editor_hud_system(
+    mut commands: Commands,
+    hud_query: Query<Entity, With<EditorHudRoot>>,
+) {
+    // Only spawn if doesn't exist
+    if hud_query.iter().next().is_some() {
+        return;
+    }
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(10.0),
+                top: Val::Px(10.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(5.0),
+                ..default()
+            },
+            EditorHudRoot,
+        ))
+        .with_children(|parent| {
+            // Mode line
+            parent.spawn((
+                Text::new("EDITOR MODE | F2: Play | Tab: Switch Mode"),
+                TextColor(Color::WHITE),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+                EditorModeText,
+            ));
+
+            // Selection line
+            parent.spawn((
+                Text::new("TERRAIN: Floor"),
+                TextColor(Color::WHITE),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+                EditorSelectionText,
+            ));
+
+            // Tool palette line
+            parent.spawn((
+                Text::new(""),
+                TextColor(Color::WHITE),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+                EditorToolText,
+            ));
+
+            // Cursor position line
+            parent.spawn((
+                Text::new("Cursor: --"),
+                TextColor(Color::WHITE),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+                EditorCursorText,
+            ));
+
+            // Map file line
+            parent.spawn((
+                Text::new("MAP: untitled"),
+                TextColor(Color::WHITE),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+                EditorFileText,
+            ));
+
+            // Outcome of the last save/load
+            parent.spawn((
+                Text::new(""),
+                TextColor(Color::srgb(0.7, 0.7, 0.7)),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                EditorStatusText,
+            ));
+        });
+}
+
+// Update editor HUD text
+pub fn update_editor_hud_system(
+    editor_state: Res<EditorState>,
+    items: Res<ItemRegistry>,
+    cursor: Res<EditorCursor>,
+    mut selection_text_query: Query<&mut Text, (With<EditorSelectionText>, Without<EditorCursorText>)>,
+    mut cursor_text_query: Query<&mut Text, (With<EditorCursorText>, Without<EditorSelectionText>)>,
+) {
+    // Update selection text
+    if let Ok(mut text) = selection_text_query.single_mut() {
+        let selection_str = match editor_state.mode {
+            EditorMode::Terrain => {
+                match editor_state.selected_terrain {
+                    TileKind::Floor => "TERRAIN: 1=Floor, 2=Wall | Selected: Floor (1)".to_string(),
+                    TileKind::Wall => "TERRAIN: 1=Floor, 2=Wall | Selected: Wall (2)".to_string(),
+                }
             }
             EditorMode::Entity => {
                 let selected = match editor_state.selected_entity {
@@ -459,16 +1750,28 @@ pub fn update_editor_hud_system(
                 format!("ENTITY: 1=Grav, 2=Phil, 3=Rust, 4=Start, 5=Exit, 6=Lamp | Selected: {}", selected)
             }
             EditorMode::Item => {
-                let selected = match editor_state.selected_item {
-                    ItemType::FullyEmpty => "Fully Empty (1)",
-                    ItemType::Scrap => "Scrap (2)",
-                    ItemType::GlassJar => "Glass Jar (3)",
-                    ItemType::Battery => "Battery (4)",
-                    ItemType::Bolt => "Bolt (5)",
-                    ItemType::MetalDetector => "Metal Detector (6)",
-                    ItemType::RustSlag => "Rust Slag (7)",
-                };
-                format!("ITEM: 1-7 available | Selected: {}", selected)
+                let selected = items
+                    .iter()
+                    .nth(editor_state.selected_item)
+                    .map_or("--".to_string(), |definition| {
+                        format!("{} ({})", definition.name, editor_state.selected_item + 1)
+                    });
+                format!(
+                    "ITEM: 1-{} or [ ] to cycle ({} items) | Selected: {}",
+                    items.len().min(9),
+                    items.len(),
+                    selected
+                )
+            }
+            EditorMode::Spawn => {
+                let presets = spawn_presets();
+                let keys: Vec<String> = presets
+                    .iter()
+                    .enumerate()
+                    .map(|(index, (name, _))| format!("{}={}", index + 1, name))
+                    .collect();
+                let selected = presets.get(editor_state.selected_spawn).map_or("--", |(name, _)| name);
+                format!("SPAWN: {} | Selected: {}", keys.join(", "), selected)
             }
         };
         **text = selection_str;
@@ -484,6 +1787,57 @@ pub fn update_editor_hud_system(
     }
 }
 
+// Update the map file and status lines of the editor HUD
+pub fn update_editor_file_hud_system(
+    map_file: Res<EditorMapFile>,
+    mut text_query: Query<(&mut Text, Has<EditorStatusText>), Or<(With<EditorFileText>, With<EditorStatusText>)>>,
+) {
+    if !map_file.is_changed() {
+        return;
+    }
+
+    for (mut text, is_status) in text_query.iter_mut() {
+        **text = if is_status {
+            map_file.status.clone().unwrap_or_default()
+        } else {
+            format!("MAP: {} | F3: Save | F4: Open | F6: Save As | F7: New", map_file.title())
+        };
+    }
+}
+
+// Update the tool palette line of the editor HUD
+pub fn update_editor_tool_hud_system(
+    editor_state: Res<EditorState>,
+    mut text_query: Query<&mut Text, With<EditorToolText>>,
+) {
+    if !editor_state.is_changed() {
+        return;
+    }
+    let Ok(mut text) = text_query.single_mut() else {
+        return;
+    };
+
+    let palette: Vec<String> = EditorTool::ALL
+        .iter()
+        .map(|tool| {
+            let key = format!("{:?}", tool.key()).trim_start_matches("Key").to_string();
+            if *tool == editor_state.tool {
+                format!("[{}={}]", key, tool.name())
+            } else {
+                format!("{}={}", key, tool.name())
+            }
+        })
+        .collect();
+    let mut line = format!("TOOL: {}", palette.join(" "));
+    if editor_state.effective_tool() != editor_state.tool {
+        line.push_str(" | Terrain mode only, using Brush");
+    }
+    if let Some(region) = &editor_state.clipboard {
+        line.push_str(&format!(" | Clipboard: {}x{}", region.width, region.height));
+    }
+    **text = line;
+}
+
 // Show/hide editor HUD based on game state
 pub fn toggle_editor_hud_visibility_system(
     current_state: Res<State<GameState>>,
@@ -496,3 +1850,241 @@ pub fn toggle_editor_hud_visibility_system(
         };
     }
 }
+
+// Re-lint the map when its terrain, entities or items have changed, once the stroke making the
+// change is finished: linting walks the whole map, too much to do for every tile of a drag
+pub fn update_map_issues_system(
+    grid: Res<GameGrid>,
+    items: Res<ItemRegistry>,
+    entity_query: Query<(Entity, &EntityType, &Position)>,
+    ground_items_query: Query<(Entity, &GroundItems, &Position), Without<EntityType>>,
+    spawn_query: Query<(Entity, &SpawnPoint, &Position)>,
+    link_query: Query<(Entity, &ZoneLink, &Position)>,
+    changed_query: Query<
+        (),
+        Or<(Changed<EntityType>, Changed<Position>, Changed<GroundItems>, Changed<SpawnPoint>, Changed<ZoneLink>)>,
+    >,
+    mut removed_entities: RemovedComponents<EntityType>,
+    mut removed_items: RemovedComponents<GroundItems>,
+    mut removed_spawns: RemovedComponents<SpawnPoint>,
+    mut removed_links: RemovedComponents<ZoneLink>,
+    settings: Res<MapSettings>,
+    history: Res<EditHistory>,
+    mut issues: ResMut<MapIssues>,
+    mut stale: Local<bool>,
+) {
+    let removed = !removed_entities.is_empty()
+        || !removed_items.is_empty()
+        || !removed_spawns.is_empty()
+        || !removed_links.is_empty();
+    removed_entities.clear();
+    removed_items.clear();
+    removed_spawns.clear();
+    removed_links.clear();
+    if grid.is_changed() || !changed_query.is_empty() || removed {
+        *stale = true;
+    }
+    if !*stale || history.in_stroke() {
+        return;
+    }
+    *stale = false;
+
+    let map_data = current_map_data(&grid, &entity_query, &ground_items_query, &spawn_query, &link_query, &settings);
+    issues.0 = validate_map(&map_data, &items);
+}
+
+// Rebuild the map issues panel (top right) when the issues change; each issue is a button
+pub fn update_map_issues_panel_system(
+    mut commands: Commands,
+    issues: Res<MapIssues>,
+    panel_query: Query<Entity, With<MapIssuesPanel>>,
+) {
+    if !issues.is_changed() && !panel_query.is_empty() {
+        return;
+    }
+    for entity in panel_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let errors = issues.error_count();
+    let header = if issues.0.is_empty() {
+        "MAP ISSUES: none".to_string()
+    } else {
+        format!("MAP ISSUES: {} errors, {} warnings (click to jump)", errors, issues.0.len() - errors)
+    };
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(10.0),
+                top: Val::Px(10.0),
+                max_width: Val::Px(420.0),
+                padding: UiRect::all(Val::Px(8.0)),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(3.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+            Interaction::default(),
+            MapIssuesPanel,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(header),
+                TextColor(Color::WHITE),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+            ));
+
+            for (index, issue) in issues.0.iter().enumerate().take(MAX_ISSUE_ROWS) {
+                let (label, color) = match issue.severity {
+                    IssueSeverity::Error => ("ERROR", Color::srgb(1.0, 0.4, 0.4)),
+                    IssueSeverity::Warning => ("WARN", Color::srgb(1.0, 0.85, 0.3)),
+                };
+                parent
+                    .spawn((Button, Node::default(), MapIssueRow(index)))
+                    .with_child((
+                        Text::new(format!("{} {}", label, issue.message)),
+                        TextColor(color),
+                        TextFont {
+                            font_size: 14.0,
+                            ..default()
+                        },
+                    ));
+            }
+
+            if issues.0.len() > MAX_ISSUE_ROWS {
+                parent.spawn((
+                    Text::new(format!("...and {} more", issues.0.len() - MAX_ISSUE_ROWS)),
+                    TextColor(Color::srgb(0.7, 0.7, 0.7)),
+                    TextFont {
+                        font_size: 14.0,
+                        ..default()
+                    },
+                ));
+            }
+        });
+}
+
+// Clicking an issue centres the camera on the tile it is about
+pub fn map_issue_click_system(
+    mut commands: Commands,
+    row_query: Query<(&Interaction, &MapIssueRow), Changed<Interaction>>,
+    issues: Res<MapIssues>,
+    grid: Res<GameGrid>,
+    mut camera_position: ResMut<CameraPosition>,
+    camera_query: Query<Entity, With<Camera2d>>,
+    mut map_file: ResMut<EditorMapFile>,
+) {
+    let Some(issue) = row_query
+        .iter()
+        .find(|(interaction, _)| **interaction == Interaction::Pressed)
+        .and_then(|(_, row)| issues.0.get(row.0))
+    else {
+        return;
+    };
+    let Some((x, y)) = issue.position else {
+        map_file.status = Some(format!("{} (not tied to a tile)", issue.message));
+        return;
+    };
+
+    camera_position.0 = grid_to_world(x, y, grid.width, grid.height);
+    if let Ok(camera_entity) = camera_query.single() {
+        commands.entity(camera_entity).insert(Transform::from_translation(camera_position.0.extend(0.0)));
+    }
+    map_file.status = Some(format!("({}, {}): {}", x, y, issue.message));
+}
+
+// The issues panel only exists while editing
+pub fn despawn_map_issues_panel_system(
+    mut commands: Commands,
+    panel_query: Query<Entity, With<MapIssuesPanel>>,
+) {
+    for entity in panel_query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+    use crate::resources::item_registry::{ITEM_CATALOGUE_PATH, FULLY_EMPTY, RUST_SLAG};
+
+    // The map as the editor holds it, in a fixed order so two readings can be compared
+    fn edited_map(world: &mut World) -> String {
+        let mut map = world
+            .run_system_once(
+                |grid: Res<GameGrid>,
+                 entity_query: Query<(Entity, &EntityType, &Position)>,
+                 ground_items_query: Query<(Entity, &GroundItems, &Position), Without<EntityType>>,
+                 spawn_query: Query<(Entity, &SpawnPoint, &Position)>,
+                 link_query: Query<(Entity, &ZoneLink, &Position)>,
+                 settings: Res<MapSettings>| {
+                    current_map_data(&grid, &entity_query, &ground_items_query, &spawn_query, &link_query, &settings)
+                },
+            )
+            .unwrap();
+        map.entities.sort_by_key(|placed| (placed.x, placed.y));
+        map.items.sort_by_key(|placed| (placed.x, placed.y));
+        serde_json::to_string(&map).unwrap()
+    }
+
+    #[test]
+    fn test_a_run_never_changes_the_authored_map() {
+        let items = ItemRegistry::load_from_file(ITEM_CATALOGUE_PATH).unwrap();
+        let mut world = World::new();
+
+        let mut pile = GroundItems::new();
+        for id in ["scrap", "scrap", "glass_jar", "metal_detector"] {
+            pile.add_item(items.create(id), &items);
+        }
+        let mut authored = MapData::from_game_state(
+            &GameGrid::new_empty(10, 10),
+            &[(EntityType::PlayerStart, 1, 1), (EntityType::RustAnomaly, 5, 5), (EntityType::Exit, 8, 8)],
+            &[(pile, 2, 2)],
+        );
+        authored.spawn_points.push(PlacedSpawnPoint { x: 3, y: 3, spawn: SpawnPoint::Anomaly { choices: Vec::new() } });
+        authored.zone_links.push(PlacedZoneLink { x: 8, y: 8, link: ZoneLink { map: "deeper".to_string(), entry_x: 1, entry_y: 1 } });
+        authored.movement = Some(MovementMode::Diagonal);
+        world.run_system_once(move |mut commands: Commands| spawn_map(&mut commands, &authored)).unwrap();
+        let before = edited_map(&mut world);
+
+        world.run_system_once(snapshot_authored_map_system).unwrap();
+
+        // The run picks up a scrap, the Philosopher's Stone transmutes the jar, the Rust eats the detector,
+        // a bolt is dropped, the markers go and the walls change in another zone
+        world
+            .run_system_once(
+                move |mut commands: Commands,
+                      mut ground_items_query: Query<&mut GroundItems>,
+                      marker_query: Query<Entity, Or<(With<SpawnPoint>, With<ZoneLink>)>>,
+                      mut grid: ResMut<GameGrid>| {
+                    for mut pile in ground_items_query.iter_mut() {
+                        pile.remove_item(0, 1);
+                        let jar = pile.items.iter().position(|item| item.id == "glass_jar").unwrap();
+                        pile.items[jar] = items.create(FULLY_EMPTY);
+                        let detector = pile.items.iter().position(|item| item.id == "metal_detector").unwrap();
+                        pile.items[detector] = items.create(RUST_SLAG);
+                    }
+                    let mut dropped = GroundItems::new();
+                    dropped.add_item(items.create("bolt"), &items);
+                    commands.spawn((Position { x: 4, y: 4 }, dropped));
+                    for entity in marker_query.iter() {
+                        commands.entity(entity).despawn();
+                    }
+                    grid.set_tile(6, 6, Tile::new(TileKind::Wall));
+                    commands.insert_resource(MapSettings { movement: None });
+                },
+            )
+            .unwrap();
+        assert_ne!(edited_map(&mut world), before);
+
+        world.run_system_once(restore_authored_map_system).unwrap();
+        assert_eq!(edited_map(&mut world), before);
+        assert!(world.get_resource::<AuthoredMap>().is_none());
+    }
+}
@@ -0,0 +1,74 @@
+use bevy::prelude::*;
+use crate::components::components::Position;
+use crate::resources::{
+    game_grid::{EntityType, TileKind},
+    debug::DebugReveal,
+};
+use crate::sim::{fog::TileVisibility, zone_sim::{is_anomaly, ZoneSim}};
+use crate::systems::ground_items::GroundItemSprite;
+use crate::systems::rendering::get_tile_color;
+use crate::constants::REMEMBERED_TILE_BRIGHTNESS;
+
+fn dimmed(color: Color) -> Color {
+    let srgba = color.to_srgba();
+    Color::srgba(
+        srgba.red * REMEMBERED_TILE_BRIGHTNESS,
+        srgba.green * REMEMBERED_TILE_BRIGHTNESS,
+        srgba.blue * REMEMBERED_TILE_BRIGHTNESS,
+        srgba.alpha,
+    )
+}
+
+/// Color of a terrain tile as far as the player knows it: black if unseen, dimmed if remembered
+pub fn shaded_tile_color(kind: TileKind, visibility: TileVisibility) -> Color {
+    match visibility {
+        TileVisibility::Unseen => Color::BLACK,
+        TileVisibility::Remembered => dimmed(get_tile_color(kind)),
+        TileVisibility::Visible => get_tile_color(kind),
+    }
+}
+
+pub fn visibility_for(known: bool) -> Visibility {
+    if known {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    }
+}
+
+/// Applies the player's map knowledge to the scene during Running mode
+/// Entities and items only show on seen tiles (the terrain chunks shade themselves with
+/// shaded_tile_color); anomalies are left to update_anomaly_markers_system
+pub fn update_fog_system(
+    sim: Res<ZoneSim>,
+    debug_reveal: Res<DebugReveal>,
+    mut entity_query: Query<(&Position, &EntityType, &mut Visibility)>,
+    mut item_sprite_query: Query<(&GroundItemSprite, &mut Visibility), Without<EntityType>>,
+    ground_items_query: Query<&Position, Without<EntityType>>,
+) {
+    for (pos, entity_type, mut visibility) in entity_query.iter_mut() {
+        if is_anomaly(*entity_type) {
+            continue;
+        }
+        let seen = debug_reveal.0 || sim.known.visibility(*pos) != TileVisibility::Unseen;
+        visibility.set_if_neq(visibility_for(seen));
+    }
+
+    for (marker, mut visibility) in item_sprite_query.iter_mut() {
+        let seen = debug_reveal.0
+            || ground_items_query
+                .get(marker.ground_items_entity)
+                .is_ok_and(|pos| sim.known.visibility(*pos) != TileVisibility::Unseen);
+        visibility.set_if_neq(visibility_for(seen));
+    }
+}
+
+/// Lifts the fog off entities when leaving Running mode so the editor shows the whole map
+/// (the terrain chunks redraw unshaded on their own)
+pub fn clear_fog_system(
+    mut entity_query: Query<&mut Visibility, With<EntityType>>,
+) {
+    for mut visibility in entity_query.iter_mut() {
+        *visibility = Visibility::Inherited;
+    }
+}
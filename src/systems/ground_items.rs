@@ -1,9 +1,11 @@
 use bevy::prelude::*;
+use std::collections::HashSet;
 use crate::components::{
     components::Position,
     item::GroundItems,
 };
-use crate::resources::game_grid::GameGrid;
+use crate::resources::{game_grid::GameGrid, item_registry::ItemRegistry};
+use crate::sim::zone_sim::ZoneSim;
 use crate::systems::rendering::grid_to_world;
 use crate::constants::TILE_SIZE;
 
@@ -13,6 +15,16 @@ pub struct GroundItemSprite {
     pub ground_items_entity: Entity,
 }
 
+/// Image for a pile: the catalogue sprite of its first item, or the generic item texture
+fn pile_texture(asset_server: &AssetServer, items: &ItemRegistry, ground_items: &GroundItems) -> Handle<Image> {
+    let sprite = ground_items
+        .items
+        .first()
+        .and_then(|item| items.get(item.id.as_str()))
+        .and_then(|definition| definition.sprite.clone());
+    asset_server.load(sprite.unwrap_or_else(|| "Items.png".to_string()))
+}
+
 /// Spawns sprites for all GroundItems entities when entering Running mode
 pub fn spawn_ground_item_sprites_system(
     mut commands: Commands,
@@ -20,14 +32,13 @@ pub fn spawn_ground_item_sprites_system(
     ground_items_query: Query<(Entity, &Position, &GroundItems)>,
     existing_sprites: Query<Entity, With<GroundItemSprite>>,
     grid: Res<GameGrid>,
+    items: Res<ItemRegistry>,
 ) {
     // Only spawn if sprites don't already exist
     if existing_sprites.iter().next().is_some() {
         return;
     }
 
-    let items_texture = asset_server.load("Items.png");
-
     for (entity, position, ground_items) in ground_items_query.iter() {
         // Only spawn sprite if there are items on this tile
         if !ground_items.is_empty() {
@@ -40,7 +51,7 @@ pub fn spawn_ground_item_sprites_system(
 
             commands.spawn((
                 Sprite {
-                    image: items_texture.clone(),
+                    image: pile_texture(&asset_server, &items, ground_items),
                     custom_size: Some(Vec2::new(TILE_SIZE * 0.6, TILE_SIZE * 0.6)),
                     ..default()
                 },
@@ -70,9 +81,8 @@ pub fn update_ground_item_sprites_system(
     ground_items_query: Query<(Entity, &Position, &GroundItems)>,
     sprite_query: Query<(Entity, &GroundItemSprite)>,
     grid: Res<GameGrid>,
+    items: Res<ItemRegistry>,
 ) {
-    let items_texture = asset_server.load("Items.png");
-
     // Track which GroundItems entities have sprites
     let mut sprites_map: std::collections::HashMap<Entity, Entity> = sprite_query
         .iter()
@@ -98,7 +108,7 @@ pub fn update_ground_item_sprites_system(
 
                 commands.spawn((
                     Sprite {
-                        image: items_texture.clone(),
+                        image: pile_texture(&asset_server, &items, ground_items),
                         custom_size: Some(Vec2::new(TILE_SIZE * 0.6, TILE_SIZE * 0.6)),
                         ..default()
                     },
@@ -111,3 +121,39 @@ pub fn update_ground_item_sprites_system(
         }
     }
 }
+
+/// Mirrors the simulation's ground items onto GroundItems entities during Running mode
+/// Updates changed tiles, spawns entities for new piles and despawns emptied ones (with their sprites)
+pub fn sync_ground_items_from_sim_system(
+    mut commands: Commands,
+    sim: Res<ZoneSim>,
+    mut ground_items_query: Query<(Entity, &Position, &mut GroundItems)>,
+    sprite_query: Query<(Entity, &GroundItemSprite)>,
+) {
+    let mut mirrored: HashSet<Position> = HashSet::new();
+
+    for (entity, position, mut ground_items) in ground_items_query.iter_mut() {
+        match sim.ground_items_at(*position) {
+            Some(sim_items) if mirrored.insert(*position) => {
+                if *ground_items != *sim_items {
+                    *ground_items = sim_items.clone();
+                }
+            }
+            _ => {
+                // Pile no longer exists (or is a duplicate) - despawn it and its sprite
+                for (sprite_entity, sprite) in sprite_query.iter() {
+                    if sprite.ground_items_entity == entity {
+                        commands.entity(sprite_entity).despawn();
+                    }
+                }
+                commands.entity(entity).despawn();
+            }
+        }
+    }
+
+    for (position, sim_items) in sim.ground_items.iter() {
+        if !mirrored.contains(position) {
+            commands.spawn((*position, sim_items.clone()));
+        }
+    }
+}
@@ -21,6 +21,7 @@ pub fn spawn_game_hud_system(
                 ..default()
             },
             BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+            Interaction::default(), // So clicks on the HUD don't reach the map below
             GameHudRoot,
         ))
         .with_children(|parent| {
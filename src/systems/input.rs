@@ -11,19 +11,41 @@ use crate::resources::{
     camera::{CameraZoom, CameraPosition},
     game_grid::GameGrid,
     turn_state::TurnPhase,
+    editor_state::MapDialog,
+    keybindings::{InputAction, Keybindings},
+    action_input::InputDevice,
 };
 use crate::systems::rendering::grid_to_world;
 
+/// Keeps track of the device the player used last, so prompts show its keys or buttons
+pub fn detect_input_device_system(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut device: ResMut<InputDevice>,
+) {
+    if gamepads.iter().any(|gamepad| gamepad.get_just_pressed().next().is_some()) {
+        device.set_if_neq(InputDevice::Gamepad);
+    } else if keyboard.get_just_pressed().next().is_some() {
+        device.set_if_neq(InputDevice::Keyboard);
+    }
+}
+
 pub fn exit_on_escape_system(
     keyboard: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
     mut exit: EventWriter<AppExit>,
     turn_phase: Res<State<TurnPhase>>,
+    map_dialog: Option<Res<MapDialog>>,
 ) {
-    // Only exit the game if we're NOT in a modal UI phase
+    // Only exit the game if we're NOT in a modal UI phase or the editor's map dialog
     // (Modal phases have their own ESC handlers to close the UI)
-    if keyboard.just_pressed(KeyCode::Escape) {
+    if keybindings.just_pressed(&keyboard, InputAction::Quit) && map_dialog.is_none() {
         let phase = turn_phase.get();
-        if *phase != TurnPhase::InspectingItems && *phase != TurnPhase::ViewingInventory {
+        if *phase != TurnPhase::InspectingItems
+            && *phase != TurnPhase::ViewingInventory
+            && *phase != TurnPhase::PlacingMarkers
+            && *phase != TurnPhase::Throwing
+        {
             exit.write(AppExit::Success);
         }
     }
@@ -49,9 +71,7 @@ pub fn camera_zoom_system(
 
             let scale_for_width = map_width / window.width();
             let scale_for_height = map_height / window.height();
-            let max_zoom_out = scale_for_width.max(scale_for_height);
-
-            max_zoom_out
+            scale_for_width.max(scale_for_height)
         } else {
             5.0
         };
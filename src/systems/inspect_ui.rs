@@ -7,8 +7,10 @@ use crate::components::{
 use crate::resources::{
     turn_state::TurnPhase,
     message_log::MessageLog,
+    keybindings::InputAction,
+    action_input::ActionInput,
 };
-use crate::systems::ground_items::GroundItemSprite;
+use crate::sim::{action::Action, zone_sim::ZoneSim};
 
 /// Marker component for the inspect UI root
 #[derive(Component)]
@@ -28,25 +30,67 @@ pub struct InspectItemRow {
 #[derive(Component)]
 pub struct InspectSelection {
     pub selected_index: usize,
+    /// Units of the selected stack to take; `None` takes the whole stack
+    pub amount: Option<u32>,
 }
 
-/// Detects E key press and transitions to InspectingItems phase if player is on items tile
+/// Marker component for the help line, which also shows the amount to take
+#[derive(Component)]
+pub struct InspectHelpText;
+
+/// AmountLess/AmountMore (Left/Right arrows or the d-pad) change how many units of a stack to move; `None` means all of it
+pub fn adjust_amount(input: &ActionInput, amount: Option<u32>, stack: u32) -> Option<u32> {
+    let current = amount.unwrap_or(stack).min(stack);
+    let adjusted = if input.just_pressed(InputAction::AmountLess) {
+        current.saturating_sub(1).max(1)
+    } else if input.just_pressed(InputAction::AmountMore) {
+        (current + 1).min(stack)
+    } else {
+        current
+    };
+    (adjusted < stack).then_some(adjusted)
+}
+
+/// Help line for a stack UI with the keys as bound (or the buttons, on a gamepad), e.g. "W/S to select, ←/→ amount (3/10), E to pickup, ESC to close"
+pub fn stack_help_text(input: &ActionInput, actions: &[(InputAction, &str)], amount: Option<u32>, stack: u32) -> String {
+    let select = format!("{}/{} to select", input.label(InputAction::MenuUp), input.label(InputAction::MenuDown));
+    let act = actions
+        .iter()
+        .map(|(action, verb)| format!("{} to {}", input.label(*action), verb))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let close = format!("{} to close", input.label(InputAction::Cancel));
+    if stack > 1 {
+        format!(
+            "{}, {}/{} amount ({}/{}), {}, {}",
+            select,
+            input.label(InputAction::AmountLess),
+            input.label(InputAction::AmountMore),
+            amount.unwrap_or(stack).min(stack),
+            stack,
+            act,
+            close
+        )
+    } else {
+        format!("{}, {}, {}", select, act, close)
+    }
+}
+
+/// Detects the Inspect key (E) and transitions to InspectingItems phase if player is on items tile
 pub fn detect_inspect_input_system(
-    keyboard: Res<ButtonInput<KeyCode>>,
-    player_query: Query<&crate::components::components::Position, With<Player>>,
-    ground_items_query: Query<(&crate::components::components::Position, &GroundItems)>,
+    input: ActionInput,
+    sim: Res<ZoneSim>,
     mut next_phase: ResMut<NextState<TurnPhase>>,
 ) {
-    if keyboard.just_pressed(KeyCode::KeyE) {
-        if let Ok(player_pos) = player_query.single() {
-            // Check if there are items at player's position
-            for (item_pos, ground_items) in ground_items_query.iter() {
-                if item_pos.x == player_pos.x && item_pos.y == player_pos.y && !ground_items.is_empty() {
-                    // Transition to InspectingItems phase
-                    next_phase.set(TurnPhase::InspectingItems);
-                    return;
-                }
-            }
+    if input.just_pressed(InputAction::Inspect) {
+        // Check if there are items at player's position
+        let has_items = sim
+            .ground_items_at(sim.player.position)
+            .is_some_and(|ground_items| !ground_items.is_empty());
+
+        if has_items {
+            // Transition to InspectingItems phase
+            next_phase.set(TurnPhase::InspectingItems);
         }
     }
 }
@@ -144,7 +188,7 @@ pub fn spawn_inspect_ui_system(
                             },
                             BackgroundColor(Color::srgb(0.1, 0.1, 0.1)),
                             InspectItemList,
-                            InspectSelection { selected_index: 0 },
+                            InspectSelection { selected_index: 0, amount: None },
                         ))
                         .with_children(|parent| {
                             for (index, item) in items.iter().enumerate() {
@@ -156,8 +200,8 @@ pub fn spawn_inspect_ui_system(
                                 let item_text = format!(
                                     "{}. {} (Weight: {}, {}){}",
                                     index + 1,
-                                    item.name,
-                                    item.weight,
+                                    item.label(),
+                                    item.total_weight(),
                                     value_str,
                                     metal_str
                                 );
@@ -197,6 +241,7 @@ pub fn spawn_inspect_ui_system(
                             ..default()
                         },
                         TextColor(Color::srgb(0.6, 0.6, 0.6)),
+                        InspectHelpText,
                     ));
                 });
         });
@@ -212,20 +257,21 @@ pub fn despawn_inspect_ui_system(
     }
 }
 
-/// Handles ESC key to close inspect UI (when in InspectingItems phase)
+/// Handles the Cancel key (ESC) to close inspect UI (when in InspectingItems phase)
 /// Closing the inspect menu consumes 1 turn (transitions to WorldUpdate)
 pub fn close_inspect_ui_system(
-    keyboard: Res<ButtonInput<KeyCode>>,
+    input: ActionInput,
+    mut sim: ResMut<ZoneSim>,
     mut next_phase: ResMut<NextState<TurnPhase>>,
 ) {
-    if keyboard.just_pressed(KeyCode::Escape) {
+    if input.just_pressed(InputAction::Cancel) && sim.perform(Action::CloseUi, &mut Vec::new()) {
         next_phase.set(TurnPhase::WorldUpdate);
     }
 }
 
-/// Handles W/S key navigation in inspect UI
+/// Handles MenuUp/MenuDown (W/S) navigation in inspect UI
 pub fn inspect_navigation_system(
-    keyboard: Res<ButtonInput<KeyCode>>,
+    input: ActionInput,
     mut selection_query: Query<&mut InspectSelection>,
     player_query: Query<&crate::components::components::Position, With<Player>>,
     ground_items_query: Query<(&crate::components::components::Position, &GroundItems)>,
@@ -252,95 +298,84 @@ pub fn inspect_navigation_system(
 
     let max_index = ground_items.count() - 1;
 
-    // S = down, W = up (consistent with movement)
-    if keyboard.just_pressed(KeyCode::KeyS) {
+    // S = down, W = up by default (consistent with movement); a new selection starts with its whole stack
+    if input.just_pressed(InputAction::MenuDown) {
         if selection.selected_index < max_index {
             selection.selected_index += 1;
+            selection.amount = None;
         }
-    } else if keyboard.just_pressed(KeyCode::KeyW) {
-        if selection.selected_index > 0 {
-            selection.selected_index -= 1;
+    } else if input.just_pressed(InputAction::MenuUp) && selection.selected_index > 0 {
+        selection.selected_index -= 1;
+        selection.amount = None;
+    } else if let Some(stack) = ground_items.items.get(selection.selected_index) {
+        let amount = adjust_amount(&input, selection.amount, stack.quantity);
+        if amount != selection.amount {
+            selection.amount = amount;
         }
     }
 }
 
-/// Handles E key to pickup selected item
+/// Handles the Pickup key (E) to pickup the chosen amount of the selected stack
 pub fn pickup_item_system(
-    keyboard: Res<ButtonInput<KeyCode>>,
-    mut player_query: Query<(&crate::components::components::Position, &mut Inventory), With<Player>>,
-    mut ground_items_query: Query<(Entity, &crate::components::components::Position, &mut GroundItems)>,
-    sprite_query: Query<(Entity, &GroundItemSprite)>,
+    input: ActionInput,
+    mut sim: ResMut<ZoneSim>,
     selection_query: Query<&InspectSelection>,
     mut next_phase: ResMut<NextState<TurnPhase>>,
     mut message_log: ResMut<MessageLog>,
-    mut commands: Commands,
 ) {
-    if !keyboard.just_pressed(KeyCode::KeyE) {
+    if !input.just_pressed(InputAction::Pickup) {
         return;
     }
 
-    let Ok((player_pos, mut inventory)) = player_query.single_mut() else {
+    let Ok(selection) = selection_query.single() else {
         return;
     };
 
-    let Ok(selection) = selection_query.single() else {
+    let Some(stack) = sim
+        .ground_items_at(sim.player.position)
+        .and_then(|ground_items| ground_items.items.get(selection.selected_index))
+    else {
         return;
     };
-
-    // Find ground items at player position
-    let mut ground_entity = None;
-    let mut item_to_pickup = None;
-
-    for (entity, pos, mut ground_items) in ground_items_query.iter_mut() {
-        if pos.x == player_pos.x && pos.y == player_pos.y {
-            if selection.selected_index < ground_items.count() {
-                // Remove item from ground
-                item_to_pickup = ground_items.remove_item(selection.selected_index);
-                ground_entity = Some((entity, ground_items.is_empty()));
-                break;
-            }
-        }
-    }
-
-    if let Some(item) = item_to_pickup {
-        // Add to inventory (capacity is unlimited, but movement is blocked if over)
-        message_log.add_message(format!("Picked up: {}", item.name));
-        info!("Picked up: {} (weight: {})", item.name, item.weight);
-        inventory.add_item(item);
-
-        // If ground items are now empty, despawn the sprite and entity
-        if let Some((entity, is_empty)) = ground_entity {
-            if is_empty {
-                // First despawn the sprite to prevent orphaning
-                for (sprite_entity, sprite) in sprite_query.iter() {
-                    if sprite.ground_items_entity == entity {
-                        commands.entity(sprite_entity).despawn();
-                        break;
-                    }
-                }
-
-                // Then despawn the ground items entity
-                commands.entity(entity).despawn();
-
-                // Close inspect UI and return to player turn
-                next_phase.set(TurnPhase::PlayerTurn);
-            }
-        }
-        // If items remain, we stay in InspectingItems and the UI will rebuild
-        // (handled by rebuild system)
+    let quantity = selection.amount.unwrap_or(stack.quantity);
+
+    // Move the items from the ground into the inventory (capacity is unlimited, but movement is blocked if over)
+    let mut events = Vec::new();
+    sim.perform(Action::Pickup { index: selection.selected_index, quantity }, &mut events);
+    message_log.log_events(&events);
+
+    // If the tile is now empty, close inspect UI and return to player turn
+    // (the emptied GroundItems entity and its sprite are cleaned up by the sim sync)
+    // If items remain, we stay in InspectingItems and the UI will rebuild
+    if sim.ground_items_at(sim.player.position).is_none() {
+        next_phase.set(TurnPhase::PlayerTurn);
     }
 }
 
-/// Updates the visual highlighting of items in inspect UI when selection changes
+/// Updates the visual highlighting of items and the amount to take in inspect UI when selection changes
 pub fn update_inspect_ui_selection_system(
+    sim: Res<ZoneSim>,
+    input: ActionInput,
     selection_query: Query<&InspectSelection>,
     mut item_rows_query: Query<(&InspectItemRow, &mut BackgroundColor)>,
+    mut help_query: Query<&mut Text, With<InspectHelpText>>,
 ) {
     // Get current selection
     let Ok(selection) = selection_query.single() else {
         return;
     };
 
+    let stack = sim
+        .ground_items_at(sim.player.position)
+        .and_then(|ground_items| ground_items.items.get(selection.selected_index))
+        .map_or(1, |item| item.quantity);
+    let help = stack_help_text(&input, &[(InputAction::Pickup, "pickup")], selection.amount, stack);
+    for mut text in help_query.iter_mut() {
+        if text.0 != help {
+            text.0 = help.clone();
+        }
+    }
+
     // Update background color for all item rows every frame
     // (This is more reliable than Changed detection for UI updates)
     for (row, mut bg_color) in item_rows_query.iter_mut() {
@@ -461,7 +496,7 @@ pub fn rebuild_inspect_ui_system(
                             },
                             BackgroundColor(Color::srgb(0.1, 0.1, 0.1)),
                             InspectItemList,
-                            InspectSelection { selected_index: adjusted_selection },
+                            InspectSelection { selected_index: adjusted_selection, amount: None },
                         ))
                         .with_children(|parent| {
                             for (index, item) in items.iter().enumerate() {
@@ -473,8 +508,8 @@ pub fn rebuild_inspect_ui_system(
                                 let item_text = format!(
                                     "{}. {} (Weight: {}, {}){}",
                                     index + 1,
-                                    item.name,
-                                    item.weight,
+                                    item.label(),
+                                    item.total_weight(),
                                     value_str,
                                     metal_str
                                 );
@@ -513,6 +548,7 @@ pub fn rebuild_inspect_ui_system(
                             ..default()
                         },
                         TextColor(Color::srgb(0.6, 0.6, 0.6)),
+                        InspectHelpText,
                     ));
                 });
         });
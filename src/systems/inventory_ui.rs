@@ -1,12 +1,16 @@
 use bevy::prelude::*;
 use crate::components::{
-    components::{Player, Position},
+    components::Player,
     inventory::{Inventory, CarryCapacity},
-    item::GroundItems,
 };
 use crate::resources::{
     turn_state::TurnPhase,
+    message_log::MessageLog,
+    keybindings::InputAction,
+    action_input::ActionInput,
 };
+use crate::sim::{action::Action, zone_sim::{throw_range, ZoneSim}};
+use crate::systems::{bolt_throwing::ThrowingItem, inspect_ui::{adjust_amount, stack_help_text}};
 
 /// Marker component for the inventory UI root
 #[derive(Component)]
@@ -20,20 +24,26 @@ pub struct InventoryItemList;
 #[derive(Component)]
 pub struct InventorySelection {
     pub selected_index: usize,
+    /// Units of the selected stack to drop; `None` drops the whole stack
+    pub amount: Option<u32>,
 }
 
+/// Marker component for the help line, which also shows the amount to drop
+#[derive(Component)]
+pub struct InventoryHelpText;
+
 /// Marker component for individual inventory item rows with their index
 #[derive(Component)]
 pub struct InventoryItemRow {
     pub index: usize,
 }
 
-/// Detects Tab key press and transitions to ViewingInventory phase
+/// Detects the Inventory key (Tab) and transitions to ViewingInventory phase
 pub fn detect_inventory_input_system(
-    keyboard: Res<ButtonInput<KeyCode>>,
+    input: ActionInput,
     mut next_phase: ResMut<NextState<TurnPhase>>,
 ) {
-    if keyboard.just_pressed(KeyCode::Tab) {
+    if input.just_pressed(InputAction::Inventory) {
         next_phase.set(TurnPhase::ViewingInventory);
     }
 }
@@ -136,7 +146,7 @@ pub fn spawn_inventory_ui_system(
                             },
                             BackgroundColor(Color::srgb(0.1, 0.1, 0.1)),
                             InventoryItemList,
-                            InventorySelection { selected_index: 0 },
+                            InventorySelection { selected_index: 0, amount: None },
                         ))
                         .with_children(|parent| {
                             if inventory.is_empty() {
@@ -158,8 +168,8 @@ pub fn spawn_inventory_ui_system(
                                     let item_text = format!(
                                         "{}. {} (Weight: {}, {}){}",
                                         index + 1,
-                                        item.name,
-                                        item.weight,
+                                        item.label(),
+                                        item.total_weight(),
                                         value_str,
                                         metal_str
                                     );
@@ -194,12 +204,13 @@ pub fn spawn_inventory_ui_system(
 
                     // Help text
                     parent.spawn((
-                        Text::new("W/S to select, D to drop, ESC to close"),
+                        Text::new("W/S to select, D to drop, T to throw, ESC to close"),
                         TextFont {
                             font_size: 16.0,
                             ..default()
                         },
                         TextColor(Color::srgb(0.6, 0.6, 0.6)),
+                        InventoryHelpText,
                     ));
                 });
         });
@@ -215,20 +226,21 @@ pub fn despawn_inventory_ui_system(
     }
 }
 
-/// Handles ESC key to close inventory UI
+/// Handles the Cancel key (ESC) to close inventory UI
 /// Closing the inventory menu consumes 1 turn (transitions to WorldUpdate)
 pub fn close_inventory_ui_system(
-    keyboard: Res<ButtonInput<KeyCode>>,
+    input: ActionInput,
+    mut sim: ResMut<ZoneSim>,
     mut next_phase: ResMut<NextState<TurnPhase>>,
 ) {
-    if keyboard.just_pressed(KeyCode::Escape) {
+    if input.just_pressed(InputAction::Cancel) && sim.perform(Action::CloseUi, &mut Vec::new()) {
         next_phase.set(TurnPhase::WorldUpdate);
     }
 }
 
-/// Handles W/S key navigation in inventory
+/// Handles MenuUp/MenuDown (W/S) navigation in inventory
 pub fn inventory_navigation_system(
-    keyboard: Res<ButtonInput<KeyCode>>,
+    input: ActionInput,
     mut selection_query: Query<&mut InventorySelection>,
     player_query: Query<&Inventory, With<Player>>,
 ) {
@@ -246,80 +258,105 @@ pub fn inventory_navigation_system(
 
     let max_index = inventory.count() - 1;
 
-    // S = down, W = up (consistent with movement)
-    if keyboard.just_pressed(KeyCode::KeyS) {
+    // S = down, W = up by default (consistent with movement); a new selection starts with its whole stack
+    if input.just_pressed(InputAction::MenuDown) {
         if selection.selected_index < max_index {
             selection.selected_index += 1;
+            selection.amount = None;
         }
-    } else if keyboard.just_pressed(KeyCode::KeyW) {
-        if selection.selected_index > 0 {
-            selection.selected_index -= 1;
+    } else if input.just_pressed(InputAction::MenuUp) && selection.selected_index > 0 {
+        selection.selected_index -= 1;
+        selection.amount = None;
+    } else if let Some(stack) = inventory.items.get(selection.selected_index) {
+        let amount = adjust_amount(&input, selection.amount, stack.quantity);
+        if amount != selection.amount {
+            selection.amount = amount;
         }
     }
 }
 
-/// Handles D key to drop selected item (always drops on player's current tile)
+/// Handles the Drop key (D) to drop the chosen amount of the selected stack (always drops on player's current tile)
 pub fn drop_item_system(
-    keyboard: Res<ButtonInput<KeyCode>>,
-    mut commands: Commands,
-    mut player_query: Query<(&mut Inventory, &Position), With<Player>>,
+    input: ActionInput,
+    mut sim: ResMut<ZoneSim>,
     selection_query: Query<&InventorySelection>,
-    mut ground_items_query: Query<(Entity, &Position, &mut GroundItems), Without<Player>>,
+    mut message_log: ResMut<MessageLog>,
 ) {
-    if !keyboard.just_pressed(KeyCode::KeyD) {
+    if !input.just_pressed(InputAction::Drop) {
         return;
     }
 
-    let Ok((mut inventory, player_pos)) = player_query.single_mut() else {
+    let Ok(selection) = selection_query.single() else {
         return;
     };
 
-    let Ok(selection) = selection_query.single() else {
+    let Some(stack) = sim.player.inventory.items.get(selection.selected_index) else {
         return;
     };
+    let quantity = selection.amount.unwrap_or(stack.quantity);
 
-    if inventory.is_empty() || selection.selected_index >= inventory.count() {
+    let mut events = Vec::new();
+    sim.perform(Action::Drop { index: selection.selected_index, quantity }, &mut events);
+    message_log.log_events(&events);
+}
+
+/// Handles the Throw key (T) to aim the selected item: one unit is thrown at a direction or clicked tile
+/// in the Throwing phase, as far as its weight allows (see `throw_range`)
+pub fn throw_item_select_system(
+    mut commands: Commands,
+    input: ActionInput,
+    sim: Res<ZoneSim>,
+    selection_query: Query<&InventorySelection>,
+    mut next_phase: ResMut<NextState<TurnPhase>>,
+    mut message_log: ResMut<MessageLog>,
+) {
+    if !input.just_pressed(InputAction::Throw) {
         return;
     }
 
-    // Remove item from inventory
-    let Some(dropped_item) = inventory.remove_item(selection.selected_index) else {
+    let Ok(selection) = selection_query.single() else {
         return;
     };
 
-    // Drop on player's current tile
-    let drop_pos = *player_pos;
-
-    // Find or create GroundItems entity at drop position
-    let mut found = false;
-    for (_, pos, mut ground_items) in ground_items_query.iter_mut() {
-        if pos.x == drop_pos.x && pos.y == drop_pos.y {
-            ground_items.add_item(dropped_item.clone());
-            found = true;
-            break;
-        }
-    }
-
-    if !found {
-        // Create new GroundItems entity
-        let mut new_ground_items = GroundItems::new();
-        new_ground_items.add_item(dropped_item.clone());
-        commands.spawn((drop_pos, new_ground_items));
-    }
+    let Some(stack) = sim.player.inventory.items.get(selection.selected_index) else {
+        return;
+    };
 
-    info!("Dropped {} at ({}, {})", dropped_item.name, drop_pos.x, drop_pos.y);
+    commands.insert_resource(ThrowingItem { index: selection.selected_index });
+    message_log.add_message(format!(
+        "Throwing the {} (range {}): pick a direction or click a tile.",
+        stack.name.to_lowercase(),
+        throw_range(stack)
+    ));
+    next_phase.set(TurnPhase::Throwing);
 }
 
-/// Updates UI highlighting based on selection
+/// Updates UI highlighting and the amount to drop based on selection
 pub fn update_inventory_ui_selection_system(
+    sim: Res<ZoneSim>,
+    input: ActionInput,
     selection_query: Query<&InventorySelection>,
     mut item_rows_query: Query<(&InventoryItemRow, &mut BackgroundColor)>,
+    mut help_query: Query<&mut Text, With<InventoryHelpText>>,
 ) {
     // Get current selection
     let Ok(selection) = selection_query.single() else {
         return;
     };
 
+    let stack = sim
+        .player
+        .inventory
+        .items
+        .get(selection.selected_index)
+        .map_or(1, |item| item.quantity);
+    let help = stack_help_text(&input, &[(InputAction::Drop, "drop"), (InputAction::Throw, "throw")], selection.amount, stack);
+    for mut text in help_query.iter_mut() {
+        if text.0 != help {
+            text.0 = help.clone();
+        }
+    }
+
     // Update background color for all item rows every frame
     // (This is more reliable than Changed detection for UI updates)
     for (row, mut bg_color) in item_rows_query.iter_mut() {
@@ -491,7 +528,7 @@ pub fn rebuild_inventory_ui_system(
                             },
                             BackgroundColor(Color::srgb(0.1, 0.1, 0.1)),
                             InventoryItemList,
-                            InventorySelection { selected_index: clamped_selection },
+                            InventorySelection { selected_index: clamped_selection, amount: None },
                         ))
                         .with_children(|parent| {
                             if inventory.is_empty() {
@@ -513,8 +550,8 @@ pub fn rebuild_inventory_ui_system(
                                     let item_text = format!(
                                         "{}. {} (Weight: {}, {}){}",
                                         index + 1,
-                                        item.name,
-                                        item.weight,
+                                        item.label(),
+                                        item.total_weight(),
                                         value_str,
                                         metal_str
                                     );
@@ -548,12 +585,13 @@ pub fn rebuild_inventory_ui_system(
                         });
 
                     parent.spawn((
-                        Text::new("W/S to select, D to drop, ESC to close"),
+                        Text::new("W/S to select, D to drop, T to throw, ESC to close"),
                         TextFont {
                             font_size: 16.0,
                             ..default()
                         },
                         TextColor(Color::srgb(0.6, 0.6, 0.6)),
+                        InventoryHelpText,
                     ));
                 });
         });
@@ -0,0 +1,379 @@
+use bevy::prelude::*;
+use bevy::input::{keyboard::{Key, KeyboardInput}, ButtonState};
+use crate::components::components::{ActionTravelTo, Player, Position};
+use crate::resources::{
+    game_grid::GameGrid,
+    turn_state::TurnPhase,
+    message_log::MessageLog,
+    map_markers::{MapMarkers, MarkerKind},
+    keybindings::{InputAction, Keybindings},
+    action_input::ActionInput,
+};
+use crate::sim::zone_sim::ZoneSim;
+use crate::systems::rendering::{grid_to_world, marker_style};
+use crate::constants::{MARKER_NOTE_MAX_LENGTH, TILE_SIZE};
+
+// --- Components & Resources ---
+
+/// Overlay sprite for one player-placed marker
+#[derive(Component)]
+pub struct MarkerSprite;
+
+/// Highlight on the tile the marking cursor is over
+#[derive(Component)]
+pub struct MarkerCursor;
+
+/// Marker component for the markers panel root
+#[derive(Component)]
+pub struct MarkersPanel;
+
+/// Marker component for the markers panel text
+#[derive(Component)]
+pub struct MarkersPanelText;
+
+/// Cursor and note being typed while in PlacingMarkers phase
+#[derive(Resource)]
+pub struct MarkingState {
+    pub cursor: Position,
+    /// Text of a note being typed, if any
+    pub note: Option<String>,
+}
+
+// --- System 1: Detect M key to enter PlacingMarkers mode ---
+
+/// Detects the MarkMap key and transitions to PlacingMarkers phase with the cursor on the player
+/// Placing markers doesn't take a turn
+pub fn detect_marking_input_system(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    sim: Res<ZoneSim>,
+    mut next_phase: ResMut<NextState<TurnPhase>>,
+) {
+    if keybindings.just_pressed(&keyboard, InputAction::MarkMap) {
+        commands.insert_resource(MarkingState {
+            cursor: sim.player.position,
+            note: None,
+        });
+        next_phase.set(TurnPhase::PlacingMarkers);
+    }
+}
+
+// --- System 2: Handle marking input ---
+
+/// The move keys move the cursor, NextMarker jumps it to the next marker, MarkDanger/MarkSafe/MarkBolt
+/// place markers, MarkNote types a note, RemoveMarker removes the marker under the cursor,
+/// KeepMarkers keeps the markers for the next run, WalkToCursor leaves and walks the player to the cursor,
+/// MarkMap or Cancel leaves
+/// While a note is being typed, keys go into the note until Enter (save) or ESC (discard)
+pub fn marking_input_system(
+    mut commands: Commands,
+    input: ActionInput,
+    mut key_events: EventReader<KeyboardInput>,
+    sim: Res<ZoneSim>,
+    grid: Res<GameGrid>,
+    mut state: ResMut<MarkingState>,
+    mut markers: ResMut<MapMarkers>,
+    mut next_phase: ResMut<NextState<TurnPhase>>,
+    mut message_log: ResMut<MessageLog>,
+    player_query: Query<Entity, With<Player>>,
+) {
+    // Always drain the events so the key that starts a note isn't typed into it
+    let pressed: Vec<Key> = key_events
+        .read()
+        .filter(|event| event.state == ButtonState::Pressed)
+        .map(|event| event.logical_key.clone())
+        .collect();
+
+    let cursor = state.cursor;
+    if let Some(note) = state.note.as_mut() {
+        for key in pressed {
+            match key {
+                Key::Enter => {
+                    let text = note.trim().to_string();
+                    if !text.is_empty() {
+                        markers.place(cursor, MarkerKind::Note(text));
+                    }
+                    state.note = None;
+                    return;
+                }
+                Key::Escape => {
+                    state.note = None;
+                    return;
+                }
+                Key::Backspace => {
+                    note.pop();
+                }
+                Key::Space if note.chars().count() < MARKER_NOTE_MAX_LENGTH => note.push(' '),
+                Key::Character(text) => {
+                    for c in text.chars().filter(|c| !c.is_control()) {
+                        if note.chars().count() < MARKER_NOTE_MAX_LENGTH {
+                            note.push(c);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        return;
+    }
+
+    if input.just_pressed(InputAction::MarkMap) || input.just_pressed(InputAction::Cancel) {
+        next_phase.set(TurnPhase::PlayerTurn);
+        return;
+    }
+    if input.just_pressed(InputAction::WalkToCursor) {
+        if let Ok(player) = player_query.single() {
+            commands.entity(player).insert(ActionTravelTo { destination: cursor });
+        }
+        next_phase.set(TurnPhase::PlayerTurn);
+        return;
+    }
+    if input.just_pressed(InputAction::NextMarker) {
+        // The marker after the one under the cursor, in the order the panel lists them
+        let next = markers
+            .markers
+            .iter()
+            .position(|marker| marker.position == cursor)
+            .map_or(0, |index| index + 1);
+        if let Some(marker) = markers.markers.get(next).or(markers.markers.first()) {
+            state.cursor = marker.position;
+        }
+        return;
+    }
+
+    // Cursor movement, the way the player moves, kept on the map
+    if let Some((dx, dy)) = input.step(sim.movement) {
+        state.cursor.x = (cursor.x + dx).clamp(0, grid.width as i32 - 1);
+        state.cursor.y = (cursor.y + dy).clamp(0, grid.height as i32 - 1);
+        return;
+    }
+
+    if input.just_pressed(InputAction::MarkDanger) {
+        markers.place(cursor, MarkerKind::Danger);
+    } else if input.just_pressed(InputAction::MarkSafe) {
+        markers.place(cursor, MarkerKind::Safe);
+    } else if input.just_pressed(InputAction::MarkBolt) {
+        markers.place(cursor, MarkerKind::BoltLanded);
+    } else if input.just_pressed(InputAction::MarkNote) {
+        // Start from the note already on the tile so it can be edited
+        state.note = Some(match markers.at(cursor).map(|marker| &marker.kind) {
+            Some(MarkerKind::Note(text)) => text.clone(),
+            _ => String::new(),
+        });
+    } else if input.just_pressed(InputAction::RemoveMarker) {
+        markers.remove(cursor);
+    } else if input.just_pressed(InputAction::KeepMarkers) {
+        markers.keep_across_runs = !markers.keep_across_runs;
+        message_log.add_message(if markers.keep_across_runs {
+            "Your markers will be kept for the next run on this map."
+        } else {
+            "Your markers will be forgotten after this run."
+        });
+    }
+}
+
+// --- System 3: Marking cursor and markers panel ---
+
+/// Spawns the marking cursor and the markers panel when entering PlacingMarkers phase
+pub fn spawn_marking_ui_system(mut commands: Commands) {
+    commands.spawn((
+        Sprite {
+            color: Color::srgba(1.0, 1.0, 1.0, 0.25),
+            custom_size: Some(Vec2::new(TILE_SIZE, TILE_SIZE)),
+            ..default()
+        },
+        Transform::from_xyz(0.0, 0.0, 14.0), // Above markers and the player
+        MarkerCursor,
+    ));
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(20.0),
+                top: Val::Px(80.0),
+                width: Val::Px(320.0),
+                max_height: Val::Percent(70.0),
+                padding: UiRect::all(Val::Px(12.0)),
+                overflow: Overflow::clip_y(),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.15, 0.15, 0.15, 0.9)),
+            BorderColor(Color::srgb(0.5, 0.5, 0.5)),
+            Interaction::default(),
+            MarkersPanel,
+            ZIndex(60),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                MarkersPanelText,
+            ));
+        });
+}
+
+/// Despawns the marking cursor and markers panel when exiting PlacingMarkers phase
+pub fn despawn_marking_ui_system(
+    mut commands: Commands,
+    ui_query: Query<Entity, Or<(With<MarkerCursor>, With<MarkersPanel>)>>,
+) {
+    for entity in ui_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    commands.remove_resource::<MarkingState>();
+}
+
+/// Moves the cursor highlight and rewrites the markers panel when something changed
+pub fn update_marking_ui_system(
+    state: Res<MarkingState>,
+    markers: Res<MapMarkers>,
+    keybindings: Res<Keybindings>,
+    grid: Res<GameGrid>,
+    mut cursor_query: Query<&mut Transform, With<MarkerCursor>>,
+    mut text_query: Query<&mut Text, With<MarkersPanelText>>,
+) {
+    if let Ok(mut transform) = cursor_query.single_mut() {
+        let world_pos = grid_to_world(
+            state.cursor.x as usize,
+            state.cursor.y as usize,
+            grid.width,
+            grid.height,
+        );
+        transform.translation.x = world_pos.x;
+        transform.translation.y = world_pos.y;
+    }
+
+    if !state.is_changed() && !markers.is_changed() && !keybindings.is_changed() {
+        return;
+    }
+    let Ok(mut text) = text_query.single_mut() else {
+        return;
+    };
+
+    let mut lines = vec!["Markers".to_string()];
+    if markers.markers.is_empty() {
+        lines.push("  (none)".to_string());
+    }
+    for marker in &markers.markers {
+        let here = if marker.position == state.cursor { ">" } else { " " };
+        lines.push(format!(
+            "{} ({}, {}) {}",
+            here, marker.position.x, marker.position.y, marker.kind.describe()
+        ));
+    }
+
+    lines.push(String::new());
+    match &state.note {
+        Some(note) => {
+            lines.push(format!("Note: {}_", note));
+            lines.push("[Enter] Save  [ESC] Cancel".to_string());
+        }
+        None => {
+            let key = |action| keybindings.label(action);
+            lines.push(format!(
+                "[Move keys] Move  [{}] Next marker  [{}] Walk here",
+                key(InputAction::NextMarker),
+                key(InputAction::WalkToCursor)
+            ));
+            lines.push(format!(
+                "[{}] Danger  [{}] Safe  [{}] Bolt  [{}] Note  [{}] Remove",
+                key(InputAction::MarkDanger),
+                key(InputAction::MarkSafe),
+                key(InputAction::MarkBolt),
+                key(InputAction::MarkNote),
+                key(InputAction::RemoveMarker)
+            ));
+            lines.push(format!("[{}/{}] Done", key(InputAction::MarkMap), key(InputAction::Cancel)));
+            lines.push(format!(
+                "[{}] Keep for next run: {}",
+                key(InputAction::KeepMarkers),
+                if markers.keep_across_runs { "on" } else { "off" }
+            ));
+        }
+    }
+
+    text.0 = lines.join("\n");
+}
+
+// --- System 4: Marker overlay ---
+
+/// Redraws the marker overlay whenever the markers change
+pub fn update_marker_sprites_system(
+    mut commands: Commands,
+    markers: Res<MapMarkers>,
+    grid: Res<GameGrid>,
+    sprite_query: Query<Entity, With<MarkerSprite>>,
+) {
+    if !markers.is_changed() {
+        return;
+    }
+
+    for entity in sprite_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    for marker in &markers.markers {
+        let world_pos = grid_to_world(
+            marker.position.x as usize,
+            marker.position.y as usize,
+            grid.width,
+            grid.height,
+        );
+        let (color, glyph) = marker_style(&marker.kind);
+
+        commands
+            .spawn((
+                Sprite {
+                    color,
+                    custom_size: Some(Vec2::new(TILE_SIZE, TILE_SIZE)),
+                    ..default()
+                },
+                Transform::from_xyz(world_pos.x, world_pos.y, 3.0), // Above entities, below the player
+                MarkerSprite,
+            ))
+            .with_child((
+                Text2d::new(glyph),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                Transform::from_xyz(0.0, 0.0, 0.1),
+            ));
+    }
+}
+
+/// Keeps the markers in the zone they were placed in: when the player changes zone, the markers
+/// of the zone they left are put away and those of the zone they entered come back
+pub fn sync_markers_zone_system(
+    sim: Res<ZoneSim>,
+    mut markers: ResMut<MapMarkers>,
+) {
+    if markers.zone != sim.zone {
+        markers.switch_zone(&sim.zone);
+    }
+}
+
+/// Keeps or forgets the markers for this map, then clears them, when exiting Running mode
+pub fn store_map_markers_system(
+    mut commands: Commands,
+    sim: Option<Res<ZoneSim>>,
+    mut markers: ResMut<MapMarkers>,
+    sprite_query: Query<Entity, With<MarkerSprite>>,
+) {
+    let stored = sim.map_or(Ok(()), |sim| markers.store_for_map(sim.map_hash));
+    if let Err(e) = stored {
+        error!("Failed to store map markers: {}", e);
+    }
+
+    for entity in sprite_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    *markers = MapMarkers::default();
+}
@@ -1,9 +1,5 @@
 use bevy::prelude::*;
-use crate::components::{
-    components::{Player, Position},
-    inventory::Inventory,
-    item::GroundItems,
-};
+use crate::sim::zone_sim::ZoneSim;
 
 /// Marker component for the metal detector indicator in HUD
 #[derive(Component)]
@@ -33,6 +29,7 @@ pub fn spawn_metal_detector_indicator_system(
         BackgroundColor(Color::srgba(0.2, 0.2, 0.2, 0.8)),
         BorderColor(Color::srgb(0.5, 0.5, 0.5)),
         Visibility::Hidden, // Hidden by default
+        Interaction::default(),
         MetalDetectorIndicator,
         ZIndex(50),
     ))
@@ -59,44 +56,16 @@ pub fn despawn_metal_detector_indicator_system(
 }
 
 /// Updates metal detector indicator visibility based on nearby metal items
+/// The scan is the simulation's (`ZoneSim::metal_detected`), so its range is measured the same way as the rest of the Zone's
 pub fn update_metal_detector_system(
-    player_query: Query<(&Position, &Inventory), With<Player>>,
-    ground_items_query: Query<(&Position, &GroundItems)>,
+    sim: Option<Res<ZoneSim>>,
     mut indicator_query: Query<&mut Visibility, With<MetalDetectorIndicator>>,
 ) {
     let Ok(mut visibility) = indicator_query.single_mut() else {
         return;
     };
 
-    let Ok((player_pos, inventory)) = player_query.single() else {
-        *visibility = Visibility::Hidden;
-        return;
-    };
-
-    // Check if player has metal detector
-    if !inventory.has_metal_detector() {
-        *visibility = Visibility::Hidden;
-        return;
-    }
-
-    // Scan for metal items within 2-tile radius
-    let mut metal_detected = false;
-    for (item_pos, ground_items) in ground_items_query.iter() {
-        // Calculate Manhattan distance
-        let dx = (player_pos.x - item_pos.x).abs();
-        let dy = (player_pos.y - item_pos.y).abs();
-        let distance = dx + dy;
-
-        // Check if within 2-tile radius
-        if distance <= 2 {
-            // Check if any items are metal
-            if ground_items.items.iter().any(|item| item.is_metal) {
-                metal_detected = true;
-                break;
-            }
-        }
-    }
-
+    let metal_detected = sim.is_some_and(|sim| sim.metal_detected());
     *visibility = if metal_detected {
         Visibility::Visible
     } else {
@@ -13,3 +13,9 @@ pub mod inventory_ui;
 pub mod metal_detector;
 pub mod contract_ui;
 pub mod bolt_throwing;
+pub mod replay;
+pub mod run_save;
+pub mod fog;
+pub mod anomaly_markers;
+pub mod map_markers;
+pub mod travel;
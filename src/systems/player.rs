@@ -1,93 +1,133 @@
 use bevy::prelude::*;
-use crate::components::components::{Player, Position};
-use crate::components::inventory::Inventory;
+use crate::components::components::{Player, Position, GravitationalAnomalyTimer};
+use crate::components::inventory::{Inventory, CarryCapacity};
+use crate::components::item::GroundItems;
 use crate::resources::{
-    game_grid::{EntityType, GameGrid, ItemType},
+    game_grid::{EntityType, GameGrid},
     camera::CameraPosition,
-    turn_state::{TurnPhase, TurnCounter},
+    turn_state::TurnCounter,
     message_log::MessageLog,
+    contract_system::ContractSystem,
+    seed::WorldSeed,
+    map_markers::MapMarkers,
+    item_registry::ItemRegistry,
+    map_data::PlacedSpawnPoint,
+    spawn_points::{SpawnPoint, resolve_spawn_points},
 };
+use crate::sim::zone_sim::ZoneSim;
 use crate::systems::rendering::grid_to_world;
+use crate::systems::replay::ReplayPlayback;
+use crate::systems::run_save::PendingResume;
 use crate::constants::TILE_SIZE;
 
-/// Spawns the player entity when entering Running mode
-/// Finds PlayerStart marker and spawns player at that location
+/// Builds the run's ZoneSim from the map and spawns the player entity when entering Running mode
+/// The player starts on the PlayerStart marker, or where they left off when resuming a saved run
 pub fn spawn_player_system(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     entity_query: Query<(&Position, &EntityType)>,
+    ground_items_query: Query<(&Position, &GroundItems)>,
+    spawn_point_query: Query<(&Position, &SpawnPoint)>,
     grid: Res<GameGrid>,
+    capacity: Res<CarryCapacity>,
+    items: Res<ItemRegistry>,
+    mut world_seed: ResMut<WorldSeed>,
+    playback: Option<Res<ReplayPlayback>>,
+    resume: Option<Res<PendingResume>>,
     mut camera_position: ResMut<CameraPosition>,
     mut turn_counter: ResMut<TurnCounter>,
-    mut next_phase: ResMut<NextState<TurnPhase>>,
     mut message_log: ResMut<MessageLog>,
+    mut contract_system: ResMut<ContractSystem>,
+    mut map_markers: ResMut<MapMarkers>,
 ) {
-    // Find the PlayerStart entity
-    let player_start_pos = entity_query
+    let mut entities: Vec<(Position, EntityType)> = entity_query
         .iter()
-        .find_map(|(pos, entity_type)| {
-            if matches!(entity_type, EntityType::PlayerStart) {
-                Some(*pos)
-            } else {
-                None
-            }
-        });
+        .map(|(pos, entity_type)| (*pos, *entity_type))
+        .collect();
+    let mut ground_items: Vec<(Position, GroundItems)> = ground_items_query
+        .iter()
+        .map(|(pos, items)| (*pos, items.clone()))
+        .collect();
 
-    if let Some(start_pos) = player_start_pos {
-        // Load the player sprite
-        let texture = asset_server.load("Red.png");
+    // A replay runs on the seed it was recorded with
+    if let Some(playback) = &playback {
+        world_seed.0 = playback.replay.seed;
+    }
 
-        // Convert grid position to world coordinates
-        let world_pos = grid_to_world(
-            start_pos.x as usize,
-            start_pos.y as usize,
-            grid.width,
-            grid.height,
-        );
+    // Spawn points roll from the run seed; a resumed run already has what they rolled
+    if resume.is_none() {
+        let spawn_points: Vec<PlacedSpawnPoint> = spawn_point_query
+            .iter()
+            .map(|(pos, spawn)| PlacedSpawnPoint { x: pos.x as usize, y: pos.y as usize, spawn: spawn.clone() })
+            .collect();
+        let (rolled_entities, rolled_items) = resolve_spawn_points(&spawn_points, &items, world_seed.0);
+        entities.extend(rolled_entities);
+        ground_items.extend(rolled_items);
+    }
 
-        // Create starting inventory (10 Bolts + Metal Detector)
-        let mut starting_inventory = Inventory::new();
+    let built = match &resume {
+        Some(resume) => resume.0.to_sim(*capacity, items.clone()),
+        None => ZoneSim::new(grid.clone(), entities, ground_items, *capacity, items.clone(), world_seed.0),
+    };
 
-        // Add 10 Bolts
-        for _ in 0..10 {
-            starting_inventory.add_item(ItemType::Bolt.into());
+    let sim = match built {
+        Ok(sim) => sim,
+        Err(e) => {
+            warn!("{}", e);
+            return;
         }
+    };
+
+    let start_pos = sim.player.position;
+    let map_hash = sim.map_hash;
+    turn_counter.0 = sim.turn;
 
-        // Add Metal Detector
-        starting_inventory.add_item(ItemType::MetalDetector.into());
+    // Load the player sprite
+    let texture = asset_server.load("Red.png");
 
-        // Spawn player entity with inventory
-        commands.spawn((
-            Sprite {
-                image: texture,
-                custom_size: Some(Vec2::new(TILE_SIZE * 0.8, TILE_SIZE * 0.8)),
-                ..default()
-            },
-            Transform::from_xyz(world_pos.x, world_pos.y, 10.0),
-            Player,
-            start_pos,
-            starting_inventory,
-        ));
+    // Convert grid position to world coordinates
+    let world_pos = grid_to_world(
+        start_pos.x as usize,
+        start_pos.y as usize,
+        grid.width,
+        grid.height,
+    );
 
-        // Center camera on player
-        camera_position.0 = world_pos;
+    // Spawn player entity mirroring the simulation's player
+    commands.spawn((
+        Sprite {
+            image: texture,
+            custom_size: Some(Vec2::new(TILE_SIZE * 0.8, TILE_SIZE * 0.8)),
+            ..default()
+        },
+        Transform::from_xyz(world_pos.x, world_pos.y, 10.0),
+        Player,
+        start_pos,
+        sim.player.inventory.clone(),
+    ));
 
-        // Reset turn counter
-        turn_counter.0 = 0;
+    commands.insert_resource(sim);
 
+    // Center camera on player
+    camera_position.0 = world_pos;
+
+    if let Some(resume) = &resume {
+        // Pick up the log and contracts where the saved run left them
+        world_seed.0 = resume.0.rng.seed();
+        resume.0.restore_hud(&mut message_log, &mut contract_system, &mut map_markers);
+    } else {
         // Clear old messages and add spawn message
         message_log.clear();
         message_log.add_message("You enter the Zone...");
+        *map_markers = MapMarkers::remembered_for_map(map_hash);
+    }
 
-        // Note: TurnPhase will be set by set_entering_zone_phase_system after spawn
+    // Note: TurnPhase will be set by set_entering_zone_phase_system after spawn
 
-        info!("Player spawned at position ({}, {})", start_pos.x, start_pos.y);
-    } else {
-        warn!("No PlayerStart marker found in the map!");
-    }
+    info!("Player spawned at position ({}, {}) with seed {}", start_pos.x, start_pos.y, world_seed.0);
 }
 
-/// Despawns the player entity when exiting Running mode
+/// Despawns the player entity and drops the run's simulation when exiting Running mode
 pub fn despawn_player_system(
     mut commands: Commands,
     player_query: Query<Entity, With<Player>>,
@@ -96,6 +136,42 @@ pub fn despawn_player_system(
         commands.entity(entity).despawn();
         info!("Player despawned");
     }
+    commands.remove_resource::<ZoneSim>();
+}
+
+/// Mirrors the simulation's player state onto the player entity
+/// Only writes components that differ so change detection stays meaningful for the UI
+pub fn sync_player_from_sim_system(
+    mut commands: Commands,
+    sim: Res<ZoneSim>,
+    mut player_query: Query<(Entity, &mut Position, &mut Inventory, Option<&mut GravitationalAnomalyTimer>), With<Player>>,
+) {
+    let Ok((entity, mut position, mut inventory, timer)) = player_query.single_mut() else {
+        return;
+    };
+
+    if *position != sim.player.position {
+        *position = sim.player.position;
+    }
+
+    if *inventory != sim.player.inventory {
+        *inventory = sim.player.inventory.clone();
+    }
+
+    match (timer, sim.player.gravity_timer) {
+        (Some(mut timer), Some(turns_left)) => {
+            if timer.0 != turns_left {
+                timer.0 = turns_left;
+            }
+        }
+        (None, Some(turns_left)) => {
+            commands.entity(entity).insert(GravitationalAnomalyTimer(turns_left));
+        }
+        (Some(_), None) => {
+            commands.entity(entity).remove::<GravitationalAnomalyTimer>();
+        }
+        (None, None) => {}
+    }
 }
 
 /// Syncs the player's visual position (Transform) with their logical Position
@@ -1,52 +1,188 @@
 use bevy::prelude::*;
-use crate::resources::game_grid::{GameGrid, TileKind, EntityType};
+use bevy::asset::RenderAssetUsages;
+use bevy::image::ImageSampler;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use std::collections::HashMap;
+use std::ops::Range;
+use crate::resources::game_grid::{GameGrid, Tile, TileKind, EntityType};
+use crate::resources::camera::{CameraPosition, CameraZoom};
+use crate::resources::debug::DebugReveal;
 use crate::resources::game_state::GameState;
-use crate::components::components::{Position, TileMarker};
-use crate::constants::TILE_SIZE;
+use crate::resources::map_markers::MarkerKind;
+use crate::resources::spawn_points::SpawnPoint;
+use crate::resources::zone_links::ZoneLink;
+use crate::sim::fog::KnownMap;
+use crate::sim::knowledge::AnomalyClue;
+use crate::sim::zone_sim::ZoneSim;
+use crate::systems::fog::shaded_tile_color;
+use crate::systems::ground_items::GroundItemSprite;
+use crate::components::{components::{Position, TileMarker}, item::GroundItems};
+use crate::constants::{TILE_SIZE, CHUNK_SIZE, CHUNK_VIEW_MARGIN, DEFAULT_WINDOW_WIDTH, DEFAULT_WINDOW_HEIGHT};
 
-// Component to link tile entities to their grid position
+// Component on the sprite that draws one chunk of the terrain
 #[derive(Component)]
-pub struct TileEntity {
-    pub grid_x: usize,
-    pub grid_y: usize,
+pub struct TileChunk {
+    pub chunk_x: usize,
+    pub chunk_y: usize,
 }
 
-// Spawn all tile sprites at startup
-pub fn spawn_tile_sprites_system(
+// A terrain chunk that is on screen: its sprite, its texture (one pixel per tile) and the tiles
+// the texture was last drawn from
+struct DrawnChunk {
+    entity: Entity,
+    image: Handle<Image>,
+    tiles: Vec<Tile>,
+}
+
+// Terrain chunks currently drawn, by chunk coordinates, for a grid of `size`
+#[derive(Resource, Default)]
+pub struct TileChunks {
+    size: (usize, usize),
+    drawn: HashMap<(usize, usize), DrawnChunk>,
+}
+
+// Draw the terrain as one textured sprite per chunk, for the chunks in (or just around) the camera view
+// Chunks leaving the view are despawned; a chunk is only redrawn when its tiles change, or when
+// fog of war may have changed how it looks (each turn of a run, F12, switching modes)
+pub fn update_tile_chunks_system(
     mut commands: Commands,
     grid: Res<GameGrid>,
+    mut chunks: ResMut<TileChunks>,
+    mut images: ResMut<Assets<Image>>,
+    camera_position: Res<CameraPosition>,
+    camera_zoom: Res<CameraZoom>,
+    windows: Query<&Window>,
+    game_state: Res<State<GameState>>,
+    sim: Option<Res<ZoneSim>>,
+    debug_reveal: Res<DebugReveal>,
 ) {
-    for y in 0..grid.height {
-        for x in 0..grid.width {
-            let tile = &grid.tiles[y][x];
-            let color = get_tile_color(tile.kind);
-
-            // Calculate world position
-            // Grid coordinates: (0,0) is top-left
-            // World coordinates: (0,0) is center
-            let world_x = (x as f32 - grid.width as f32 / 2.0) * TILE_SIZE + TILE_SIZE / 2.0;
-            let world_y = (grid.height as f32 / 2.0 - y as f32) * TILE_SIZE - TILE_SIZE / 2.0;
-
-            commands.spawn((
-                Sprite {
-                    color,
-                    custom_size: Some(Vec2::new(TILE_SIZE, TILE_SIZE)),
-                    ..default()
-                },
-                Transform::from_xyz(world_x, world_y, -10.0),
-                TileMarker,
-                TileEntity { grid_x: x, grid_y: y },
-                Position {
-                    x: x as i32,
-                    y: y as i32
-                },
-            ));
+    // A new map size moves every chunk
+    let size = (grid.width, grid.height);
+    if chunks.size != size {
+        for (_, chunk) in chunks.drawn.drain() {
+            commands.entity(chunk.entity).despawn();
+            images.remove(&chunk.image);
+        }
+        chunks.size = size;
+    }
+
+    let window_size = windows
+        .single()
+        .map_or(Vec2::new(DEFAULT_WINDOW_WIDTH, DEFAULT_WINDOW_HEIGHT), |window| window.size());
+    let (columns, rows) = chunks_in_view(&grid, camera_position.0, window_size * camera_zoom.0);
+
+    let hidden: Vec<(usize, usize)> = chunks
+        .drawn
+        .keys()
+        .filter(|(x, y)| !columns.contains(x) || !rows.contains(y))
+        .copied()
+        .collect();
+    for key in hidden {
+        if let Some(chunk) = chunks.drawn.remove(&key) {
+            commands.entity(chunk.entity).despawn();
+            images.remove(&chunk.image);
+        }
+    }
+
+    // Fog of war only covers the map during a run
+    let known = sim
+        .as_ref()
+        .filter(|_| *game_state.get() == GameState::Running && !debug_reveal.0)
+        .map(|sim| &sim.known);
+    let reshade = sim.as_ref().is_some_and(|sim| sim.is_changed()) || debug_reveal.is_changed() || game_state.is_changed();
+
+    for chunk_y in rows {
+        for chunk_x in columns.clone() {
+            let Some(tiles) = grid.chunk(chunk_x, chunk_y) else {
+                continue;
+            };
+            match chunks.drawn.get_mut(&(chunk_x, chunk_y)) {
+                Some(drawn) => {
+                    if !reshade && (!grid.is_changed() || drawn.tiles == tiles) {
+                        continue;
+                    }
+                    if let Some(image) = images.get_mut(&drawn.image) {
+                        draw_chunk(image, &grid, (chunk_x, chunk_y), known);
+                    }
+                    drawn.tiles = tiles.to_vec();
+                }
+                None => {
+                    let mut image = Image::new_fill(
+                        Extent3d {
+                            width: CHUNK_SIZE as u32,
+                            height: CHUNK_SIZE as u32,
+                            depth_or_array_layers: 1,
+                        },
+                        TextureDimension::D2,
+                        &[0, 0, 0, 0],
+                        TextureFormat::Rgba8UnormSrgb,
+                        RenderAssetUsages::default(),
+                    );
+                    image.sampler = ImageSampler::nearest(); // One pixel per tile, so keep the edges sharp
+                    draw_chunk(&mut image, &grid, (chunk_x, chunk_y), known);
+                    let image = images.add(image);
+
+                    // Centre of the chunk: its top-left tile plus half a chunk less half a tile
+                    let corner = grid_to_world(chunk_x * CHUNK_SIZE, chunk_y * CHUNK_SIZE, grid.width, grid.height);
+                    let offset = (CHUNK_SIZE as f32 - 1.0) * TILE_SIZE / 2.0;
+                    let entity = commands
+                        .spawn((
+                            Sprite {
+                                image: image.clone(),
+                                custom_size: Some(Vec2::splat(CHUNK_SIZE as f32 * TILE_SIZE)),
+                                ..default()
+                            },
+                            Transform::from_xyz(corner.x + offset, corner.y - offset, -10.0),
+                            TileMarker,
+                            TileChunk { chunk_x, chunk_y },
+                        ))
+                        .id();
+                    chunks.drawn.insert((chunk_x, chunk_y), DrawnChunk { entity, image, tiles: tiles.to_vec() });
+                }
+            }
+        }
+    }
+}
+
+// Chunk columns and rows that overlap a view of `view_size` world units centred on `center`,
+// widened by CHUNK_VIEW_MARGIN and clamped to the map
+fn chunks_in_view(grid: &GameGrid, center: Vec2, view_size: Vec2) -> (Range<usize>, Range<usize>) {
+    // World position to (fractional) tile column and row; row 0 is the top of the map
+    let left = (center.x - view_size.x / 2.0) / TILE_SIZE + grid.width as f32 / 2.0;
+    let right = (center.x + view_size.x / 2.0) / TILE_SIZE + grid.width as f32 / 2.0;
+    let top = grid.height as f32 / 2.0 - (center.y + view_size.y / 2.0) / TILE_SIZE;
+    let bottom = grid.height as f32 / 2.0 - (center.y - view_size.y / 2.0) / TILE_SIZE;
+
+    let span = |low: f32, high: f32, count: usize| {
+        let first = (low.max(0.0) as usize / CHUNK_SIZE).saturating_sub(CHUNK_VIEW_MARGIN);
+        let last = (high.max(0.0) as usize / CHUNK_SIZE + CHUNK_VIEW_MARGIN + 1).min(count);
+        first..last.max(first)
+    };
+    (span(left, right, grid.chunks_wide()), span(top, bottom, grid.chunks_high()))
+}
+
+// Paint a chunk's texture: tile colors, shaded by what the player knows when `known` is given;
+// padding past the map edge is transparent
+fn draw_chunk(image: &mut Image, grid: &GameGrid, (chunk_x, chunk_y): (usize, usize), known: Option<&KnownMap>) {
+    for row in 0..CHUNK_SIZE {
+        for column in 0..CHUNK_SIZE {
+            let (x, y) = (chunk_x * CHUNK_SIZE + column, chunk_y * CHUNK_SIZE + row);
+            let color = match (grid.get_tile(x, y), known) {
+                (None, _) => Color::NONE,
+                (Some(tile), None) => get_tile_color(tile.kind),
+                (Some(tile), Some(known)) => {
+                    shaded_tile_color(tile.kind, known.visibility(Position { x: x as i32, y: y as i32 }))
+                }
+            };
+            if let Err(e) = image.set_color_at(column as u32, row as u32, color) {
+                warn!("Failed to draw tile ({}, {}): {:?}", x, y, e);
+            }
         }
     }
 }
 
 // Get color for terrain tiles
-fn get_tile_color(kind: TileKind) -> Color {
+pub fn get_tile_color(kind: TileKind) -> Color {
     match kind {
         TileKind::Floor => Color::srgb(0.33, 0.33, 0.33), // Gray
         TileKind::Wall => Color::srgb(0.13, 0.13, 0.13),  // Dark gray
@@ -54,7 +190,8 @@ fn get_tile_color(kind: TileKind) -> Color {
 }
 
 // Get color for entity types
-// In Running mode, all anomalies appear as identical purple overlays (obscured)
+// In Running mode, all anomalies appear as identical purple overlays (obscured);
+// discovered anomalies are recolored by clue_color
 // In Editor mode, anomalies are color-coded for easy placement
 pub fn get_entity_color(entity_type: EntityType, game_state: &GameState) -> Color {
     match game_state {
@@ -83,19 +220,23 @@ pub fn get_entity_color(entity_type: EntityType, game_state: &GameState) -> Colo
     }
 }
 
-// Update tile sprites when terrain changes (called from editor)
-pub fn update_tile_sprite_system(
-    grid: Res<GameGrid>,
-    mut tile_query: Query<(&TileEntity, &mut Sprite), With<TileMarker>>,
-) {
-    if !grid.is_changed() {
-        return;
+// Get overlay color for a discovered anomaly, based only on what the player has learned
+pub fn clue_color(clue: AnomalyClue) -> Color {
+    match clue {
+        AnomalyClue::Unknown => Color::srgba(0.53, 0.0, 1.0, 0.6),       // Semi-transparent purple
+        AnomalyClue::Gravitational => Color::srgba(0.3, 0.2, 1.0, 0.6),  // Semi-transparent indigo
+        AnomalyClue::Shimmering => Color::srgba(1.0, 0.84, 0.0, 0.5),    // Semi-transparent gold
+        AnomalyClue::Corrosive => Color::srgba(1.0, 0.4, 0.0, 0.5),      // Semi-transparent orange
     }
+}
 
-    for (tile_entity, mut sprite) in tile_query.iter_mut() {
-        if let Some(tile) = grid.get_tile(tile_entity.grid_x, tile_entity.grid_y) {
-            sprite.color = get_tile_color(tile.kind);
-        }
+// Get overlay color and glyph for a player-placed map marker
+pub fn marker_style(kind: &MarkerKind) -> (Color, &'static str) {
+    match kind {
+        MarkerKind::Danger => (Color::srgba(1.0, 0.1, 0.1, 0.35), "!"),     // Translucent red
+        MarkerKind::Safe => (Color::srgba(0.1, 0.9, 0.2, 0.3), "ok"),       // Translucent green
+        MarkerKind::BoltLanded => (Color::srgba(0.7, 0.7, 0.75, 0.35), "B"), // Translucent steel
+        MarkerKind::Note(_) => (Color::srgba(0.3, 0.6, 1.0, 0.35), "?"),    // Translucent blue
     }
 }
 
@@ -135,52 +276,107 @@ pub fn spawn_placed_entity(
     ));
 }
 
-// Reload all tile sprites when grid dimensions change (e.g., after loading a map)
-// This system detects when the grid resource changes and respawns all tiles
-pub fn reload_tile_sprites_system(
+// Spawn a spawn point marker at a grid position (editor only; markers are swapped for what they roll when a run starts)
+pub fn spawn_spawn_point_marker(
+    commands: &mut Commands,
+    spawn: SpawnPoint,
+    grid_x: usize,
+    grid_y: usize,
+    grid_width: usize,
+    grid_height: usize,
+) {
+    let world_pos = grid_to_world(grid_x, grid_y, grid_width, grid_height);
+    let color = match spawn {
+        SpawnPoint::Item { .. } => Color::srgba(0.2, 0.9, 0.9, 0.7),    // Cyan for loot
+        SpawnPoint::Anomaly { .. } => Color::srgba(1.0, 0.3, 1.0, 0.7), // Magenta for anomaly slots
+    };
+
+    commands.spawn((
+        Sprite {
+            color,
+            custom_size: Some(Vec2::new(TILE_SIZE * 0.5, TILE_SIZE * 0.5)),
+            ..default()
+        },
+        Transform::from_xyz(world_pos.x, world_pos.y, 2.5)
+            .with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_4)), // Diamond, unlike placed entities
+        spawn,
+        Position {
+            x: grid_x as i32,
+            y: grid_y as i32,
+        },
+    ));
+}
+
+// Spawn a zone link marker at a grid position: a small white square in the corner of the linked Exit
+// (editor only, like spawn point markers)
+pub fn spawn_zone_link_marker(
+    commands: &mut Commands,
+    link: ZoneLink,
+    grid_x: usize,
+    grid_y: usize,
+    grid_width: usize,
+    grid_height: usize,
+) {
+    let world_pos = grid_to_world(grid_x, grid_y, grid_width, grid_height);
+
+    commands.spawn((
+        Sprite {
+            color: Color::srgba(1.0, 1.0, 1.0, 0.9),
+            custom_size: Some(Vec2::new(TILE_SIZE * 0.3, TILE_SIZE * 0.3)),
+            ..default()
+        },
+        Transform::from_xyz(world_pos.x + TILE_SIZE * 0.3, world_pos.y + TILE_SIZE * 0.3, 2.6),
+        link,
+        Position {
+            x: grid_x as i32,
+            y: grid_y as i32,
+        },
+    ));
+}
+
+// Mirror the simulation's terrain onto the grid during Running mode
+// The terrain only changes when the player moves into another zone; the entities and ground items
+// of the zone they left are despawned along with it, and mirrored afresh from the simulation
+pub fn sync_grid_from_sim_system(
     mut commands: Commands,
-    grid: Res<GameGrid>,
-    tile_query: Query<Entity, With<TileMarker>>,
+    sim: Res<ZoneSim>,
+    mut grid: ResMut<GameGrid>,
+    zone_query: Query<Entity, Or<(With<EntityType>, With<GroundItems>, With<GroundItemSprite>)>>,
 ) {
-    // Only run when grid changes (indicates a map load)
-    if !grid.is_changed() {
+    if !sim.is_changed() || *grid == sim.grid {
         return;
     }
 
-    // Check if grid dimensions changed by counting existing tiles
-    let existing_tile_count = tile_query.iter().count();
-    let expected_tile_count = grid.width * grid.height;
+    *grid = sim.grid.clone();
+    for entity in zone_query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
 
-    if existing_tile_count != expected_tile_count {
-        // Despawn all existing tiles
-        for entity in tile_query.iter() {
+// Mirror the simulation's entities during Running mode: spawn the ones the ECS is missing
+// (anomalies rolled from spawn points, the entities of a zone just entered) and despawn the rest
+pub fn sync_entities_from_sim_system(
+    mut commands: Commands,
+    sim: Res<ZoneSim>,
+    entity_query: Query<(Entity, &Position, &EntityType)>,
+) {
+    for (entity, position, entity_type) in entity_query.iter() {
+        if !sim.has_entity_at(*position, *entity_type) {
             commands.entity(entity).despawn();
         }
+    }
 
-        // Respawn all tiles with new grid dimensions
-        for y in 0..grid.height {
-            for x in 0..grid.width {
-                let tile = &grid.tiles[y][x];
-                let color = get_tile_color(tile.kind);
-
-                let world_x = (x as f32 - grid.width as f32 / 2.0) * TILE_SIZE + TILE_SIZE / 2.0;
-                let world_y = (grid.height as f32 / 2.0 - y as f32) * TILE_SIZE - TILE_SIZE / 2.0;
-
-                commands.spawn((
-                    Sprite {
-                        color,
-                        custom_size: Some(Vec2::new(TILE_SIZE, TILE_SIZE)),
-                        ..default()
-                    },
-                    Transform::from_xyz(world_x, world_y, -10.0),
-                    TileMarker,
-                    TileEntity { grid_x: x, grid_y: y },
-                    Position {
-                        x: x as i32,
-                        y: y as i32
-                    },
-                ));
-            }
+    for (position, entity_type) in &sim.entities {
+        let mirrored = entity_query.iter().any(|(_, pos, placed)| pos == position && placed == entity_type);
+        if !mirrored {
+            spawn_placed_entity(
+                &mut commands,
+                *entity_type,
+                position.x as usize,
+                position.y as usize,
+                sim.grid.width,
+                sim.grid.height,
+            );
         }
     }
 }
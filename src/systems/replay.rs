@@ -0,0 +1,151 @@
+use bevy::prelude::*;
+use crate::resources::{
+    turn_state::TurnPhase,
+    message_log::MessageLog,
+    keybindings::{InputAction, Keybindings},
+};
+use crate::sim::{replay::Replay, zone_sim::ZoneSim};
+use crate::constants::REPLAY_STEP_SECONDS;
+
+/// Where the last finished run is recorded
+pub const REPLAY_FILE_PATH: &str = "replays/last_run.json";
+
+/// Present while a recorded run is being played back (`--replay <path>`)
+/// Player input is ignored and recorded actions are fed through the turn pipeline instead
+#[derive(Resource)]
+pub struct ReplayPlayback {
+    pub replay: Replay,
+    /// Index of the next recorded action to feed in
+    pub next_action: usize,
+    pub paused: bool,
+    pub fast_forward: bool,
+    pub step_requested: bool,
+    pub finished: bool,
+    pub step_timer: Timer,
+}
+
+impl ReplayPlayback {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            replay,
+            next_action: 0,
+            paused: false,
+            fast_forward: false,
+            step_requested: false,
+            finished: false,
+            step_timer: Timer::from_seconds(REPLAY_STEP_SECONDS, TimerMode::Repeating),
+        }
+    }
+
+    fn stop(&mut self, message_log: &mut MessageLog, message: String) {
+        self.finished = true;
+        info!("{}", message);
+        message_log.add_message(message);
+    }
+}
+
+/// Replay controls: ReplayPause (Space) pauses/resumes, ReplayStep (N) steps one action while paused,
+/// ReplayFastForward (F) toggles fast-forward
+pub fn replay_controls_system(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    mut playback: ResMut<ReplayPlayback>,
+) {
+    if keybindings.just_pressed(&keyboard, InputAction::ReplayPause) {
+        playback.paused = !playback.paused;
+    }
+    if keybindings.just_pressed(&keyboard, InputAction::ReplayStep) {
+        playback.step_requested = true;
+    }
+    if keybindings.just_pressed(&keyboard, InputAction::ReplayFastForward) {
+        playback.fast_forward = !playback.fast_forward;
+    }
+}
+
+/// Feeds the next recorded action into the simulation, the same way the input systems do,
+/// and checks each completed turn and the final state against the recording
+pub fn replay_playback_system(
+    time: Res<Time>,
+    phase: Res<State<TurnPhase>>,
+    mut playback: ResMut<ReplayPlayback>,
+    mut sim: ResMut<ZoneSim>,
+    mut next_phase: ResMut<NextState<TurnPhase>>,
+    mut message_log: ResMut<MessageLog>,
+) {
+    if playback.finished {
+        return;
+    }
+
+    // Only look at the run between turns, once the world update has fully resolved
+    if !matches!(phase.get(), TurnPhase::PlayerTurn | TurnPhase::PlayerDead | TurnPhase::ExitingZone) {
+        return;
+    }
+
+    if playback.next_action == 0 {
+        if let Err(e) = playback.replay.check_start(&sim) {
+            playback.stop(&mut message_log, e);
+            return;
+        }
+    } else if let Err(e) = playback.replay.check_step(&sim, playback.next_action - 1) {
+        playback.stop(&mut message_log, e);
+        return;
+    }
+
+    if sim.is_finished() || playback.next_action >= playback.replay.actions.len() {
+        let message = match playback.replay.check_summary(&sim) {
+            Ok(()) => format!("Replay finished on turn {}: matches the recording.", sim.turn),
+            Err(e) => e,
+        };
+        playback.stop(&mut message_log, message);
+        return;
+    }
+
+    if *phase.get() != TurnPhase::PlayerTurn {
+        return;
+    }
+
+    // Pacing: one action per tick, every frame when fast-forwarding, or on demand while paused
+    let step_requested = std::mem::take(&mut playback.step_requested);
+    if playback.paused && !step_requested {
+        return;
+    }
+    if !playback.fast_forward && !step_requested && !playback.step_timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let action = playback.replay.actions[playback.next_action].action;
+    playback.next_action += 1;
+
+    let mut events = Vec::new();
+    let consumed = sim.perform(action, &mut events);
+    message_log.log_events(&events);
+
+    if consumed {
+        next_phase.set(TurnPhase::WorldUpdate);
+    }
+}
+
+/// Writes the replay of the run that just ended
+/// After a playback the recording is left untouched and control goes back to the player
+pub fn save_replay_system(
+    mut commands: Commands,
+    sim: Option<Res<ZoneSim>>,
+    playback: Option<Res<ReplayPlayback>>,
+) {
+    if playback.is_some() {
+        commands.remove_resource::<ReplayPlayback>();
+        return;
+    }
+
+    let Some(sim) = sim else {
+        return;
+    };
+    if sim.actions.is_empty() {
+        return;
+    }
+
+    match sim.to_replay().save_to_file(REPLAY_FILE_PATH) {
+        Ok(_) => info!("Replay saved to {}", REPLAY_FILE_PATH),
+        Err(e) => error!("Failed to save replay: {}", e),
+    }
+}
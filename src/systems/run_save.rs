@@ -0,0 +1,224 @@
+use bevy::prelude::*;
+use bevy::app::AppExit;
+use crate::components::{components::Position, item::GroundItems};
+use crate::resources::{
+    game_grid::EntityType,
+    game_state::GameState,
+    message_log::MessageLog,
+    contract_system::ContractSystem,
+    run_save::RunSave,
+    map_markers::MapMarkers,
+    editor_state::EditorMapFile,
+    edit_history::EditHistory,
+    keybindings::{InputAction, Keybindings},
+    map_data::MapData,
+    spawn_points::SpawnPoint,
+    zone_links::ZoneLink,
+    item_registry::ItemRegistry,
+};
+use crate::sim::zone_sim::ZoneSim;
+use crate::systems::editor::{clear_map, spawn_map};
+
+/// Where the in-progress run is saved
+pub const RUN_SAVE_PATH: &str = "saves/run.json";
+
+/// A loaded save waiting for the run to start; the player spawn builds the simulation from it
+#[derive(Resource)]
+pub struct PendingResume(pub RunSave);
+
+fn save_run(
+    sim: &ZoneSim,
+    message_log: &MessageLog,
+    contract_system: &ContractSystem,
+    markers: &MapMarkers,
+) -> Result<(), String> {
+    RunSave::capture(sim, message_log, contract_system, markers).save_to_file(RUN_SAVE_PATH)
+}
+
+/// SaveRun key (F5) during the player's turn: save the run
+pub fn save_run_system(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    sim: Res<ZoneSim>,
+    contract_system: Res<ContractSystem>,
+    markers: Res<MapMarkers>,
+    mut message_log: ResMut<MessageLog>,
+) {
+    if !keybindings.just_pressed(&keyboard, InputAction::SaveRun) {
+        return;
+    }
+
+    match save_run(&sim, &message_log, &contract_system, &markers) {
+        Ok(_) => {
+            info!("Run saved to {}", RUN_SAVE_PATH);
+            message_log.add_message("Run saved.");
+        }
+        Err(e) => error!("Failed to save run: {}", e),
+    }
+}
+
+/// Saves an unfinished run when the game is quit so it can be resumed next time
+pub fn autosave_on_exit_system(
+    mut exit_events: EventReader<AppExit>,
+    sim: Res<ZoneSim>,
+    contract_system: Res<ContractSystem>,
+    markers: Res<MapMarkers>,
+    message_log: Res<MessageLog>,
+) {
+    if exit_events.read().next().is_none() || sim.is_finished() {
+        return;
+    }
+
+    match save_run(&sim, &message_log, &contract_system, &markers) {
+        Ok(_) => info!("Run autosaved to {}", RUN_SAVE_PATH),
+        Err(e) => error!("Failed to autosave run: {}", e),
+    }
+}
+
+/// ResumeRun key (F9) in Editing mode: load the saved run's map and resume the run where it was left
+/// With unsaved changes to the edited map the first press only warns that they will be lost;
+/// the next press resumes unless the map was edited, saved or loaded in between
+pub fn resume_run_system(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    entity_query: Query<(Entity, &EntityType, &Position)>,
+    ground_items_query: Query<(Entity, &GroundItems, &Position), Without<EntityType>>,
+    spawn_query: Query<(Entity, &SpawnPoint, &Position)>,
+    link_query: Query<(Entity, &ZoneLink, &Position)>,
+    items: Res<ItemRegistry>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut map_file: ResMut<EditorMapFile>,
+    mut history: ResMut<EditHistory>,
+    mut confirming: Local<bool>,
+    mut commands: Commands,
+) {
+    // A warning only stands until the map file changes: an edit, save or load since asks again
+    if map_file.is_changed() {
+        *confirming = false;
+    }
+    if !keybindings.just_pressed(&keyboard, InputAction::ResumeRun) {
+        return;
+    }
+
+    let save = match RunSave::load_from_file(RUN_SAVE_PATH, &items) {
+        Ok(save) => save,
+        Err(e) => {
+            error!("Failed to load run: {}", e);
+            map_file.status = Some(format!("Failed to load run: {}", e));
+            return;
+        }
+    };
+
+    let warning = format!(
+        "Unsaved changes to this map will be lost: press {} again to resume the run",
+        keybindings.label(InputAction::ResumeRun)
+    );
+    let confirmed = *confirming && map_file.status.as_deref() == Some(warning.as_str());
+    if map_file.unsaved_changes && !confirmed {
+        *confirming = true;
+        map_file.status = Some(warning);
+        return;
+    }
+    *confirming = false;
+
+    swap_in_run_map(
+        &mut commands,
+        &save.map,
+        &entity_query,
+        &ground_items_query,
+        &spawn_query,
+        &link_query,
+        &mut map_file,
+        &mut history,
+    );
+
+    info!("Resuming run from {} (turn {})", RUN_SAVE_PATH, save.turn);
+    commands.insert_resource(PendingResume(save));
+    next_state.set(GameState::Running);
+}
+
+/// Swaps the editor's map for the one the run was saved on
+/// That map belongs to no file, so saving it asks for a name instead of overwriting the map that was open
+fn swap_in_run_map(
+    commands: &mut Commands,
+    map: &MapData,
+    entity_query: &Query<(Entity, &EntityType, &Position)>,
+    ground_items_query: &Query<(Entity, &GroundItems, &Position), Without<EntityType>>,
+    spawn_query: &Query<(Entity, &SpawnPoint, &Position)>,
+    link_query: &Query<(Entity, &ZoneLink, &Position)>,
+    map_file: &mut EditorMapFile,
+    history: &mut EditHistory,
+) {
+    clear_map(commands, entity_query, ground_items_query, spawn_query, link_query);
+    spawn_map(commands, map);
+    map_file.path = None;
+    map_file.unsaved_changes = true;
+    map_file.status = None;
+    history.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+    use crate::resources::{game_grid::GameGrid, map_data::{PlacedSpawnPoint, PlacedZoneLink}};
+
+    fn link(map: &str) -> ZoneLink {
+        ZoneLink { map: map.to_string(), entry_x: 1, entry_y: 1 }
+    }
+
+    #[test]
+    fn test_resumed_map_has_no_leftover_spawn_or_link_markers() {
+        let mut world = World::new();
+        world.insert_resource(EditorMapFile { path: Some("maps/other.json".to_string()), ..default() });
+        world.init_resource::<EditHistory>();
+
+        // The map open in the editor
+        let mut edited = MapData::from_game_state(&GameGrid::new_empty(10, 10), &[], &[]);
+        edited.spawn_points.push(PlacedSpawnPoint {
+            x: 2,
+            y: 2,
+            spawn: SpawnPoint::Anomaly { choices: Vec::new() },
+        });
+        edited.zone_links.push(PlacedZoneLink { x: 3, y: 3, link: link("other_side") });
+        world
+            .run_system_once(move |mut commands: Commands| spawn_map(&mut commands, &edited))
+            .unwrap();
+
+        // The run's zone
+        let mut resumed = MapData::from_game_state(&GameGrid::new_empty(10, 10), &[], &[]);
+        resumed.zone_links.push(PlacedZoneLink { x: 5, y: 5, link: link("deeper") });
+        world
+            .run_system_once(
+                move |mut commands: Commands,
+                      entity_query: Query<(Entity, &EntityType, &Position)>,
+                      ground_items_query: Query<(Entity, &GroundItems, &Position), Without<EntityType>>,
+                      spawn_query: Query<(Entity, &SpawnPoint, &Position)>,
+                      link_query: Query<(Entity, &ZoneLink, &Position)>,
+                      mut map_file: ResMut<EditorMapFile>,
+                      mut history: ResMut<EditHistory>| {
+                    swap_in_run_map(
+                        &mut commands,
+                        &resumed,
+                        &entity_query,
+                        &ground_items_query,
+                        &spawn_query,
+                        &link_query,
+                        &mut map_file,
+                        &mut history,
+                    )
+                },
+            )
+            .unwrap();
+
+        assert_eq!(world.query::<&SpawnPoint>().iter(&world).count(), 0);
+        let links: Vec<(ZoneLink, Position)> =
+            world.query::<(&ZoneLink, &Position)>().iter(&world).map(|(link, pos)| (link.clone(), *pos)).collect();
+        assert_eq!(links, vec![(link("deeper"), Position { x: 5, y: 5 })]);
+
+        // F3 must not write the run's zone over the map that was open
+        let map_file = world.resource::<EditorMapFile>();
+        assert_eq!(map_file.path, None);
+        assert!(map_file.unsaved_changes);
+    }
+}
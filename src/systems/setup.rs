@@ -1,6 +1,5 @@
 use bevy::prelude::*;
 use rand::Rng;
-use rand_pcg::Pcg32;
 
 use crate::resources::{
     game_grid::{
@@ -8,12 +7,12 @@ use crate::resources::{
         SpatialGrid,
     },
     ui_elements::TickCount,
-    seed::WorldSeed,
+    seed::{WorldSeed, SeedOverride},
 };
 
-pub fn setup_system(mut commands: Commands) {
+pub fn setup_system(mut commands: Commands, seed_override: Res<SeedOverride>) {
     // --- Resource Setup ---
-    let world_seed = generate_seed();
+    let world_seed = next_run_seed(&seed_override);
 
     // Default to 25x25 grid - can be changed by loading different sized maps
     let game_grid = GameGrid::new_empty(25, 25);
@@ -25,17 +24,18 @@ pub fn setup_system(mut commands: Commands) {
 }
 
 pub fn setup_camera_system(mut commands: Commands) {
-    commands.spawn(Camera2d::default());
+    commands.spawn(Camera2d);
 }
 
 
 // --- Helper Functions ---
 
-fn generate_seed() -> u32 {
-    let mut rng = Pcg32::new(
-        rand::rng().random_range(0..u64::MAX),
-        rand::rng().random_range(0..u64::MAX),
-    );
-    rng.random_range(0..u32::MAX)
+/// Picks the seed for the next run: the command-line seed if one was given, otherwise a fresh one
+pub fn next_run_seed(seed_override: &SeedOverride) -> u64 {
+    seed_override.0.unwrap_or_else(generate_seed)
+}
+
+fn generate_seed() -> u64 {
+    rand::rng().random()
 }
 
@@ -0,0 +1,196 @@
+use bevy::prelude::*;
+use crate::components::components::{ActionTravelTo, ActivePath, PathMarker, Player, Position};
+use crate::resources::{
+    turn_state::TurnPhase,
+    message_log::MessageLog,
+    game_grid::GameGrid,
+    action_input::ActionInput,
+};
+use crate::sim::{action::Action, travel::TravelWatch, zone_sim::ZoneSim};
+use crate::systems::rendering::grid_to_world;
+use crate::constants::{TILE_SIZE, TRAVEL_STEP_SECONDS};
+
+// --- Components ---
+
+/// What a walk in progress compares against before each step, and the pace it keeps
+#[derive(Component)]
+pub struct TravelState {
+    /// The player's situation before the last step
+    pub watch: TravelWatch,
+    /// Messages logged before the last step
+    pub messages: usize,
+    pub step_timer: Timer,
+}
+
+// --- System 1: Click a tile to travel there ---
+
+/// Left click on a tile during the player's turn sets the player travelling to it
+/// Clicks on UI over the map (HUD, message log, panels) are left to the UI
+pub fn travel_click_system(
+    mut commands: Commands,
+    mouse: Res<ButtonInput<MouseButton>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+    windows: Query<&Window>,
+    grid: Res<GameGrid>,
+    player_query: Query<Entity, With<Player>>,
+    ui_query: Query<&Interaction>,
+) {
+    if !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+    if ui_query.iter().any(|interaction| *interaction != Interaction::None) {
+        return;
+    }
+    let Some(destination) = cursor_tile(&windows, &camera_query, &grid) else {
+        return;
+    };
+
+    if let Ok(player) = player_query.single() {
+        commands.entity(player).insert(ActionTravelTo { destination });
+    }
+}
+
+/// The grid tile under the mouse cursor, if the cursor is over the map
+pub fn cursor_tile(
+    windows: &Query<&Window>,
+    camera_query: &Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+    grid: &GameGrid,
+) -> Option<Position> {
+    let window = windows.single().ok()?;
+    let (camera, camera_transform) = camera_query.single().ok()?;
+    let world_pos = window
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor).ok())?;
+
+    // World position to grid coordinates, as for the editor cursor
+    let grid_x = (world_pos.x / TILE_SIZE) + (grid.width as f32 / 2.0);
+    let grid_y = (grid.height as f32 / 2.0) - (world_pos.y / TILE_SIZE);
+    if grid_x < 0.0 || grid_x >= grid.width as f32 || grid_y < 0.0 || grid_y >= grid.height as f32 {
+        return None;
+    }
+    Some(Position { x: grid_x as i32, y: grid_y as i32 })
+}
+
+// --- System 2: Plan the walk ---
+
+/// Turns a travel order into an A* path (see `ZoneSim::find_path`) and shows it with path markers
+/// A new order replaces the walk in progress
+pub fn start_travel_system(
+    mut commands: Commands,
+    sim: Res<ZoneSim>,
+    grid: Res<GameGrid>,
+    mut message_log: ResMut<MessageLog>,
+    player_query: Query<(Entity, &ActionTravelTo), With<Player>>,
+    marker_query: Query<Entity, With<PathMarker>>,
+) {
+    let Ok((player, order)) = player_query.single() else {
+        return;
+    };
+    commands.entity(player).remove::<ActionTravelTo>();
+    stop_travel(&mut commands, player, marker_query.iter());
+
+    if order.destination == sim.player.position {
+        return;
+    }
+    let Some(nodes) = sim.find_path(order.destination) else {
+        message_log.add_message("You don't know a safe way there.");
+        return;
+    };
+
+    for node in &nodes {
+        let world_pos = grid_to_world(node.x as usize, node.y as usize, grid.width, grid.height);
+        commands.spawn((
+            Sprite {
+                color: Color::srgba(1.0, 1.0, 0.6, 0.7), // Pale yellow
+                custom_size: Some(Vec2::new(TILE_SIZE * 0.2, TILE_SIZE * 0.2)),
+                ..default()
+            },
+            Transform::from_xyz(world_pos.x, world_pos.y, 1.2), // Under the player and entities
+            PathMarker { entity: player },
+            *node,
+        ));
+    }
+    commands.entity(player).insert((
+        ActivePath { nodes },
+        TravelState {
+            watch: sim.travel_watch(),
+            messages: message_log.total(),
+            step_timer: Timer::from_seconds(TRAVEL_STEP_SECONDS, TimerMode::Repeating),
+        },
+    ));
+}
+
+// --- System 3: Walk the path ---
+
+/// Takes one step of the active path per turn, as a normal move
+/// Stops at the destination, on any key or button press, and as soon as something happens on the way: a new
+/// message, being caught by gravity, an item pile coming into sight, the metal detector going off
+pub fn auto_travel_system(
+    mut commands: Commands,
+    input: ActionInput,
+    time: Res<Time>,
+    mut sim: ResMut<ZoneSim>,
+    mut next_phase: ResMut<NextState<TurnPhase>>,
+    mut message_log: ResMut<MessageLog>,
+    mut player_query: Query<(Entity, &mut ActivePath, &mut TravelState), With<Player>>,
+    marker_query: Query<(Entity, &Position), With<PathMarker>>,
+) {
+    let Ok((player, mut path, mut state)) = player_query.single_mut() else {
+        return;
+    };
+    let interrupted = input.any_just_pressed()
+        || message_log.total() != state.messages
+        || state.watch.interrupted_by(&sim.travel_watch());
+    if interrupted || path.nodes.is_empty() {
+        stop_travel(&mut commands, player, marker_query.iter().map(|(entity, _)| entity));
+        return;
+    }
+
+    if !state.step_timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    // A step that isn't next to the player (pulled off the path) ends the walk at the next check
+    let next = path.nodes.remove(0);
+    let from = sim.player.position;
+    let (dx, dy) = (next.x - from.x, next.y - from.y);
+    if sim.movement.distance(from, next) != 1 {
+        path.nodes.clear();
+        return;
+    }
+
+    state.watch = sim.travel_watch();
+    state.messages = message_log.total();
+    let mut events = Vec::new();
+    let moved = sim.perform(Action::Move { dx, dy }, &mut events);
+    message_log.log_events(&events);
+
+    if moved {
+        for (entity, _) in marker_query.iter().filter(|(_, pos)| **pos == next) {
+            commands.entity(entity).despawn();
+        }
+        next_phase.set(TurnPhase::WorldUpdate);
+    } else {
+        path.nodes.clear();
+    }
+}
+
+/// Removes the path markers when leaving Running mode
+pub fn despawn_path_markers_system(
+    mut commands: Commands,
+    marker_query: Query<Entity, With<PathMarker>>,
+) {
+    for entity in marker_query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+// --- Helper Functions ---
+
+// End the walk in progress, if any, and clear its markers
+fn stop_travel(commands: &mut Commands, player: Entity, markers: impl Iterator<Item = Entity>) {
+    commands.entity(player).remove::<(ActivePath, TravelState)>();
+    for entity in markers {
+        commands.entity(entity).despawn();
+    }
+}
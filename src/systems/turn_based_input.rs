@@ -1,84 +1,32 @@
 use bevy::prelude::*;
-use crate::components::components::{Player, Position, GravitationalAnomalyTimer};
-use crate::components::inventory::{Inventory, CarryCapacity};
 use crate::resources::{
-    game_grid::{GameGrid, TileKind},
     turn_state::TurnPhase,
     message_log::MessageLog,
+    action_input::ActionInput,
 };
+use crate::sim::{action::Action, zone_sim::ZoneSim};
 
 /// Handles player movement input during PlayerTurn phase
-/// WASD moves the player in 4 directions if the destination is valid
+/// The movement keys (WASD, the numpad or vi-keys by default) or the d-pad move the player,
+/// diagonally too in Diagonal mode, if the destination is valid
 pub fn player_movement_system(
-    keyboard: Res<ButtonInput<KeyCode>>,
-    mut player_query: Query<(&mut Position, &Inventory, Option<&GravitationalAnomalyTimer>), With<Player>>,
-    grid: Res<GameGrid>,
+    input: ActionInput,
+    mut sim: ResMut<ZoneSim>,
     mut next_phase: ResMut<NextState<TurnPhase>>,
-    capacity: Res<CarryCapacity>,
     mut message_log: ResMut<MessageLog>,
 ) {
-    // Get the player's current position and inventory
-    let Ok((mut player_pos, inventory, gravity_timer)) = player_query.single_mut() else {
-        return;
-    };
-
-    // Determine movement direction from WASD input
-    let mut delta_x = 0;
-    let mut delta_y = 0;
-
-    if keyboard.just_pressed(KeyCode::KeyW) {
-        delta_y = -1; // Up (negative Y in grid coordinates)
-    } else if keyboard.just_pressed(KeyCode::KeyS) {
-        delta_y = 1; // Down (positive Y in grid coordinates)
-    } else if keyboard.just_pressed(KeyCode::KeyA) {
-        delta_x = -1; // Left
-    } else if keyboard.just_pressed(KeyCode::KeyD) {
-        delta_x = 1; // Right
-    }
-
     // If no movement input, do nothing
-    if delta_x == 0 && delta_y == 0 {
+    let Some((delta_x, delta_y)) = input.step(sim.movement) else {
         return;
-    }
-
-    // Check if player is over carry capacity
-    let current_weight = inventory.total_weight();
-    let max_capacity = if gravity_timer.is_some() {
-        capacity.in_gravity
-    } else {
-        capacity.normal
     };
 
-    if current_weight > max_capacity {
-        message_log.add_message("You're carrying too much weight to move!");
-        info!("Movement blocked: over carry capacity ({}/{})", current_weight, max_capacity);
-        return;
-    }
-
-    // Calculate intended destination
-    let new_x = player_pos.x + delta_x;
-    let new_y = player_pos.y + delta_y;
-
-    // Check if destination is within grid bounds
-    if new_x < 0 || new_y < 0 || new_x >= grid.width as i32 || new_y >= grid.height as i32 {
-        info!("Movement blocked: out of bounds");
-        return;
-    }
+    // Walls, corners, bounds and carry capacity are checked by the simulation
+    let mut events = Vec::new();
+    let moved = sim.perform(Action::Move { dx: delta_x, dy: delta_y }, &mut events);
+    message_log.log_events(&events);
 
-    // Check if destination tile is walkable (not a wall)
-    if let Some(tile) = grid.get_tile(new_x as usize, new_y as usize) {
-        if tile.kind == TileKind::Wall {
-            info!("Movement blocked: wall at ({}, {})", new_x, new_y);
-            return;
-        }
+    if moved {
+        // Transition to WorldUpdate phase to process effects
+        next_phase.set(TurnPhase::WorldUpdate);
     }
-
-    // Valid move - update position and advance to WorldUpdate phase
-    player_pos.x = new_x;
-    player_pos.y = new_y;
-
-    info!("Player moved to ({}, {})", new_x, new_y);
-
-    // Transition to WorldUpdate phase to process effects
-    next_phase.set(TurnPhase::WorldUpdate);
 }
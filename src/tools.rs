@@ -0,0 +1,174 @@
+//! Command-line tools that run without a window: map validation, zone generation and the headless simulation
+
+use std::io::{BufRead, Write};
+use crate::resources::{
+    item_registry::ItemRegistry,
+    map_data::{MapData, map_directory, map_name},
+    map_validation::validate_map,
+    zone_gen::{ZoneGenParams, generate_zone},
+};
+use crate::sim::{
+    action::{Action, GameEvent},
+    movement::MovementSetting,
+    replay::{Replay, ReplaySummary},
+    zone_sim::ZoneSim,
+};
+
+/// `--validate <path>`: prints every issue with the map and returns the exit status
+/// 0 = playable (there may be warnings), 1 = errors found, 2 = the map couldn't be read
+pub fn validate(path: &str, items: &ItemRegistry) -> i32 {
+    let map = match MapData::load_from_file(path, items) {
+        Ok(map) => map,
+        Err(e) => {
+            eprintln!("{}: {}", path, e);
+            return 2;
+        }
+    };
+
+    let issues = validate_map(&map, items);
+    for issue in &issues {
+        println!("{}: {}", path, issue);
+    }
+
+    let errors = issues.iter().filter(|issue| issue.is_error()).count();
+    if errors > 0 {
+        println!("{}: {} errors, {} warnings", path, errors, issues.len() - errors);
+        return 1;
+    }
+    println!("{}: OK ({}x{}, {} warnings)", path, map.width, map.height, issues.len());
+    0
+}
+
+/// `--generate <path>`: generates a zone from `seed` and saves it to `path`, then lints it
+/// 0 = written and playable, 1 = the generator or the save failed, 2 = the params file couldn't be read
+pub fn generate(path: &str, params_path: Option<&str>, seed: u64, items: &ItemRegistry) -> i32 {
+    let params = match params_path.map(ZoneGenParams::load_from_file) {
+        Some(Ok(params)) => params,
+        Some(Err(e)) => {
+            eprintln!("{}: {}", params_path.unwrap_or_default(), e);
+            return 2;
+        }
+        None => ZoneGenParams::default(),
+    };
+
+    let map = match generate_zone(seed, &params, items) {
+        Ok(map) => map,
+        Err(e) => {
+            eprintln!("Seed {}: {}", seed, e);
+            return 1;
+        }
+    };
+    if let Err(e) = map.save_to_file(path) {
+        eprintln!("{}: {}", path, e);
+        return 1;
+    }
+
+    println!("{}: generated {}x{} zone from seed {}", path, map.width, map.height, seed);
+    for issue in validate_map(&map, items) {
+        println!("{}: {}", path, issue);
+    }
+    0
+}
+
+/// `--headless`: runs the simulation on `map` (the one at `path`, next to the maps its links lead to) without a window
+/// With a replay, plays it back and checks every turn against the recording. Otherwise reads one
+/// action per line from `input` as JSON (e.g. `{"Move":{"dx":1,"dy":0}}` or `"CloseUi"`) until the
+/// run ends or the input runs out, writing the player's messages to `output`
+pub fn run_headless(
+    map: &MapData,
+    path: &str,
+    items: ItemRegistry,
+    seed: u64,
+    movement: MovementSetting,
+    replay: Option<&Replay>,
+    input: impl BufRead,
+    output: &mut impl Write,
+) -> Result<ReplaySummary, String> {
+    let mut sim = ZoneSim::from_map_data(map, items, seed)?;
+    sim.zone = map_name(path);
+    sim.maps_directory = map_directory(path);
+    sim.set_movement_setting(movement);
+
+    if let Some(replay) = replay {
+        replay.play(&mut sim)?;
+        return Ok(ReplaySummary::of(&sim));
+    }
+
+    for line in input.lines() {
+        if sim.is_finished() {
+            break;
+        }
+
+        let line = line.map_err(|e| format!("Failed to read input: {}", e))?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let action: Action = serde_json::from_str(line)
+            .map_err(|e| format!("Invalid action '{}': {}", line, e))?;
+        for event in sim.apply(action) {
+            if let GameEvent::Message(message) = event {
+                writeln!(output, "[turn {}] {}", sim.turn, message)
+                    .map_err(|e| format!("Failed to write output: {}", e))?;
+            }
+        }
+    }
+
+    Ok(ReplaySummary::of(&sim))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::item_registry::ITEM_CATALOGUE_PATH;
+
+    #[test]
+    fn test_headless_run_and_replay() {
+        let items = ItemRegistry::load_from_file(ITEM_CATALOGUE_PATH).unwrap();
+        let map = MapData::load_from_file("assets/maps/current.json", &items).unwrap();
+        let input = "{\"Move\":{\"dx\":1,\"dy\":0}}\n\n{\"ThrowBolt\":{\"dx\":0,\"dy\":1}}\n\"CloseUi\"\n";
+
+        let mut output = Vec::new();
+        let summary = run_headless(&map, "assets/maps/current.json", items.clone(), 7, MovementSetting::default(), None, input.as_bytes(), &mut output).unwrap();
+        assert!(summary.turn > 0);
+
+        // Playing the same actions on the simulation gives a replay the headless run accepts
+        let mut sim = ZoneSim::from_map_data(&map, items.clone(), 7).unwrap();
+        for line in input.lines().filter(|line| !line.is_empty()) {
+            sim.apply(serde_json::from_str(line).unwrap());
+        }
+        let replay = sim.to_replay();
+        assert_eq!(run_headless(&map, "assets/maps/current.json", items.clone(), 7, MovementSetting::default(), Some(&replay), "".as_bytes(), &mut Vec::new()).unwrap(), summary);
+
+        assert!(run_headless(&map, "assets/maps/current.json", items, 7, MovementSetting::default(), None, "jump\n".as_bytes(), &mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_headless_links_lead_to_maps_next_to_the_map() {
+        use crate::resources::{game_grid::{EntityType, GameGrid}, map_data::PlacedZoneLink, zone_links::ZoneLink};
+
+        let items = ItemRegistry::load_from_file(ITEM_CATALOGUE_PATH).unwrap();
+        let directory = std::env::temp_dir().join("picnic_headless_links_test");
+        let (first_path, second_path) = (directory.join("first.json"), directory.join("second.json"));
+
+        // The only "second" is the one next to "first", not in the maps directory
+        let mut first = MapData::from_game_state(
+            &GameGrid::new_empty(5, 5),
+            &[(EntityType::PlayerStart, 2, 2), (EntityType::Exit, 2, 3)],
+            &[],
+        );
+        first.zone_links = vec![PlacedZoneLink { x: 2, y: 3, link: ZoneLink { map: "second".to_string(), entry_x: 1, entry_y: 1 } }];
+        first.save_to_file(&first_path.to_string_lossy()).unwrap();
+        MapData::from_game_state(&GameGrid::new_empty(4, 4), &[], &[]).save_to_file(&second_path.to_string_lossy()).unwrap();
+
+        let first_path = first_path.to_string_lossy().into_owned();
+        let input = "{\"Move\":{\"dx\":0,\"dy\":1}}\n";
+        let mut sim = ZoneSim::from_map_data(&first, items.clone(), 7).unwrap();
+        sim.apply(serde_json::from_str(input.trim()).unwrap());
+        let summary = run_headless(&first, &first_path, items, 7, MovementSetting::default(), None, input.as_bytes(), &mut Vec::new()).unwrap();
+        // Still running, in the second zone, where a sim that looks in the maps directory extracts instead
+        assert_eq!(summary.outcome, None);
+        assert!(sim.outcome.is_some());
+    }
+}